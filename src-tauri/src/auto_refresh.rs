@@ -0,0 +1,143 @@
+//! Background refresh of the subscriptions
+//!
+//! At launch and then every `REFRESH_INTERVAL`, the episodes of the subscribed webtoons are updated a few at a
+//! time. A webtoon is only checked when it may have released something: completed series are skipped, and
+//! series with a weekly schedule are checked on their release days (and once a day otherwise, to catch delays).
+
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::StreamExt;
+use tauri::{Emitter, Manager};
+use tokio::sync::Mutex;
+use webtoon_sdk::{webtoon::WebtoonInfo, Schedule, WebtoonId, Weekday};
+
+use crate::{db::Database, store::UserData};
+
+/// delay between two checks of the subscriptions
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2 * 3600);
+/// max number of webtoons refreshed at the same time
+const MAX_PARALLEL_REFRESH: usize = 4;
+
+/// episodes found by the scheduler since the user last opened each webtoon, keyed by `WebtoonId::store_key`
+///
+/// also the payload of the `new_episodes` event
+pub type NewEpisodes = HashMap<String, Vec<usize>>;
+
+/// day of the week in UTC, the backend doesn't know the user's timezone
+fn utc_weekday(now: SystemTime) -> Weekday {
+    let days = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / 86400;
+    // 1970-01-01 was a thursday
+    match (days + 4) % 7 {
+        0 => Weekday::Sunday,
+        1 => Weekday::Monday,
+        2 => Weekday::Tuesday,
+        3 => Weekday::Wednesday,
+        4 => Weekday::Thursday,
+        5 => Weekday::Friday,
+        _ => Weekday::Saturday,
+    }
+}
+
+/// whether the webtoon may have new episodes worth fetching
+fn is_due(webtoon: &WebtoonInfo, today: Weekday, now: SystemTime) -> bool {
+    let releases_today = match &webtoon.schedule {
+        Some(Schedule::Completed) => return false,
+        Some(Schedule::Daily) => true,
+        Some(Schedule::Weekday(day)) => *day == today,
+        Some(Schedule::Weekdays(days)) => days.contains(&today),
+        // canvas and local series don't have a schedule
+        None => false,
+    };
+    releases_today || webtoon.refresh_eps_at <= now
+}
+
+/// returns the numbers of the episodes added since the last refresh
+async fn refresh_webtoon(
+    app: &tauri::AppHandle,
+    id: WebtoonId,
+    today: Weekday,
+    now: SystemTime,
+) -> Result<Vec<usize>, String> {
+    let db = app.state::<Database>();
+    // the user may delete episodes or open the webtoon while it is fetched
+    let _series_lock = db.lock_series(id).await?;
+    let Some(mut webtoon) = db.get_series(id)? else {
+        return Ok(vec![]);
+    };
+    if !is_due(&webtoon, today, now) {
+        return Ok(vec![]);
+    }
+
+    let thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
+    let changes = webtoon.update_episodes(&thumb_path, |_| {}).await?;
+
+    db.save_series(&webtoon)?;
+    db.index_series_images(&webtoon)?;
+    Ok(changes.added)
+}
+
+/// checks every subscription once, emits `new_episodes` if some were found
+async fn refresh_subscriptions(app: &tauri::AppHandle) {
+    let subscriptions = {
+        let user_state = app.state::<Mutex<UserData>>();
+        let user_data = user_state.lock().await;
+        user_data
+            .webtoons
+            .values()
+            .map(|uwt| uwt.id)
+            .collect::<Vec<_>>()
+    };
+
+    let now = SystemTime::now();
+    let today = utc_weekday(now);
+    let refreshed = futures::stream::iter(subscriptions)
+        .map(|id| async move { (id, refresh_webtoon(app, id, today, now).await) })
+        .buffer_unordered(MAX_PARALLEL_REFRESH)
+        .collect::<Vec<_>>()
+        .await;
+
+    // a failed refresh is just retried at the next check
+    let found = refreshed
+        .into_iter()
+        .filter_map(|(id, added)| match added {
+            Ok(added) if !added.is_empty() => Some((id, added)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if found.is_empty() {
+        return;
+    }
+
+    let new_episodes = {
+        let new_episodes_state = app.state::<Mutex<NewEpisodes>>();
+        let mut new_episodes = new_episodes_state.lock().await;
+        for (id, added) in found {
+            new_episodes
+                .entry(id.store_key())
+                .or_default()
+                .extend(added);
+        }
+        new_episodes.clone()
+    };
+    let _ = app.emit("new_episodes", new_episodes);
+}
+
+/// runs until the app exits, spawn it once at startup
+pub async fn run_scheduler(app: tauri::AppHandle) {
+    loop {
+        refresh_subscriptions(&app).await;
+        tokio::time::sleep(REFRESH_INTERVAL).await;
+    }
+}
+
+/* Commands */
+
+#[tauri::command]
+pub async fn get_new_episodes(
+    new_episodes_state: tauri::State<'_, Mutex<NewEpisodes>>,
+) -> Result<NewEpisodes, String> {
+    Ok(new_episodes_state.lock().await.clone())
+}
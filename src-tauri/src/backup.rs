@@ -0,0 +1,59 @@
+//! Backup export of everything that belongs to the user
+//!
+//! The caches (series, episodes, comments, images) are left out: they are fetched again when needed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tokio::sync::Mutex;
+
+use crate::{db::Database, history::HistoryEntry, store::UserData};
+
+/// bumped whenever the layout of `Backup` changes
+const BACKUP_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Backup {
+    pub version: u32,
+    /// unix timestamp in seconds
+    pub exported_at: u64,
+    /// settings, subscriptions with their read progress, shelves and bookmarks
+    pub user_data: UserData,
+    /// oldest first
+    pub history: Vec<HistoryEntry>,
+}
+
+/* Commands */
+
+#[tauri::command]
+/// writes the backup as json in the downloads directory (the app data one when there is none),
+/// returns the path of the file
+pub async fn export_backup(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs();
+    let backup = Backup {
+        version: BACKUP_VERSION,
+        exported_at,
+        user_data: user_state.lock().await.clone(),
+        history: db.full_history()?,
+    };
+
+    let dir = match app.path().download_dir() {
+        Ok(dir) => dir,
+        Err(_) => app.path().app_data_dir().map_err(|e| e.to_string())?,
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("fosstoon-backup-{exported_at}.json"));
+
+    let raw_backup = serde_json::to_vec_pretty(&backup).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw_backup)
+        .map_err(|e| format!("Failed to write {}: {e}", path.to_string_lossy()))?;
+    Ok(path.to_string_lossy().to_string())
+}
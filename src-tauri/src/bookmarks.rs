@@ -0,0 +1,152 @@
+//! Panels bookmarked by the user
+//!
+//! Kept in the user store rather than in the database: they are the user's own data, like the mute rules, and
+//! outlive the subscription of their series.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+use webtoon_sdk::WebtoonId;
+
+use crate::{
+    constants::{USER_BOOKMARKS_KEY, USER_STORE},
+    db::Database,
+    store::UserData,
+};
+
+/// Part of a panel, in fractions of its width and height
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct CropRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Bookmark {
+    pub id: u64,
+    pub wt_id: WebtoonId,
+    /// titles when bookmarked, the series may not be in the database anymore
+    pub title: String,
+    pub episode: usize,
+    pub episode_title: String,
+    /// index of the panel in the episode
+    pub panel: usize,
+    /// the whole panel when `None`
+    pub crop: Option<CropRect>,
+    pub note: String,
+    /// unix timestamp in seconds
+    pub created_at: u64,
+}
+
+/// What `add_bookmark` bookmarks
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct BookmarkTarget {
+    pub wt_id: WebtoonId,
+    pub ep_num: usize,
+    pub panel: usize,
+    pub crop: Option<CropRect>,
+}
+
+impl CropRect {
+    fn is_valid(&self) -> bool {
+        let in_panel = |start: f64, len: f64| start >= 0.0 && len > 0.0 && start + len <= 1.0;
+        in_panel(self.x, self.width) && in_panel(self.y, self.height)
+    }
+}
+
+fn save_bookmarks(app: &tauri::AppHandle, bookmarks: &[Bookmark]) -> Result<(), String> {
+    let user_store = app
+        .store(USER_STORE)
+        .map_err(|_| "Failed to open user store")?;
+    user_store.set(
+        USER_BOOKMARKS_KEY,
+        serde_json::to_value(bookmarks).map_err(|_| "Couldn't serialize bookmarks")?,
+    );
+    Ok(())
+}
+
+/* Commands */
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn add_bookmark(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    app: tauri::AppHandle,
+    target: BookmarkTarget,
+    note: String,
+) -> Result<Bookmark, String> {
+    let BookmarkTarget {
+        wt_id,
+        ep_num,
+        panel,
+        crop,
+    } = target;
+    if crop.is_some_and(|crop| !crop.is_valid()) {
+        return Err("The bookmarked area is outside of the panel".to_string());
+    }
+    let webtoon = db.get_series(wt_id)?.ok_or("No webtoon found in store")?;
+    let episode_title = webtoon
+        .episode(ep_num)
+        .map(|episode| episode.title.clone())
+        .ok_or("Requested episode not found in store")?;
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs();
+
+    let mut user_data = user_state.lock().await;
+    let bookmark = Bookmark {
+        id: user_data
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.id + 1)
+            .max()
+            .unwrap_or_default(),
+        wt_id,
+        title: webtoon.title,
+        episode: ep_num,
+        episode_title,
+        panel,
+        crop,
+        note,
+        created_at,
+    };
+    user_data.bookmarks.push(bookmark.clone());
+    save_bookmarks(&app, &user_data.bookmarks)?;
+
+    Ok(bookmark)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_bookmark_note(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    app: tauri::AppHandle,
+    bookmark_id: u64,
+    note: String,
+) -> Result<(), String> {
+    let mut user_data = user_state.lock().await;
+    let bookmark = user_data
+        .bookmarks
+        .iter_mut()
+        .find(|bookmark| bookmark.id == bookmark_id)
+        .ok_or("Bookmark not found")?;
+    bookmark.note = note;
+    save_bookmarks(&app, &user_data.bookmarks)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_bookmark(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    app: tauri::AppHandle,
+    bookmark_id: u64,
+) -> Result<(), String> {
+    let mut user_data = user_state.lock().await;
+    user_data
+        .bookmarks
+        .retain(|bookmark| bookmark.id != bookmark_id);
+    save_bookmarks(&app, &user_data.bookmarks)
+}
@@ -1,6 +1,26 @@
+/// present in every store, see `migrations`
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
 pub const USER_STORE: &str = "user_store.json";
 
 pub const USER_LANG_KEY: &str = "user_language";
+/// only read when importing the json stores into the database
 pub const USER_WEBTOONS_KEY: &str = "user_webtoons";
+pub const USER_MUTE_RULES_KEY: &str = "user_mute_rules";
+pub const USER_READER_SETTINGS_KEY: &str = "user_reader_settings";
+pub const USER_BOOKMARKS_KEY: &str = "user_bookmarks";
+pub const USER_SHELVES_KEY: &str = "user_shelves";
+pub const LOCAL_LIBRARY_KEY: &str = "local_library";
 
+/// only read when importing the json stores into the database
 pub const WEBTOONS_STORE: &str = "webtoons_store.json";
+
+pub const CACHE_STORE: &str = "cache_store.json";
+
+pub const RECOMMENDATIONS_KEY: &str = "homepage_recommandations";
+
+/// only read when importing the json stores into the database
+pub const COMMENTS_STORE: &str = "comments_store.json";
+
+/// in the app data dir
+pub const DATABASE_FILE: &str = "library.db";
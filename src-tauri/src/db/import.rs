@@ -0,0 +1,77 @@
+use tauri_plugin_store::StoreExt;
+use webtoon_sdk::webtoon::WebtoonInfo;
+
+use super::{
+    repository::{write_cached_comments, write_meta, SeriesRow, SubscriptionRow},
+    Database,
+};
+use crate::{
+    constants::{
+        COMMENTS_STORE, SCHEMA_VERSION_KEY, USER_STORE, USER_WEBTOONS_KEY, WEBTOONS_STORE,
+    },
+    store::{load_key, UserWebtoons},
+};
+
+/// set in `meta` once the json stores were imported
+const JSON_IMPORTED_KEY: &str = "json_stores_imported";
+
+/// one-time import of the json stores of the previous versions, the json files are left untouched
+///
+/// everything is written in a single transaction: an interrupted import is simply run again at the next launch
+///
+/// must run after `migrations::run_migrations`, the stores have to be at their latest schema
+pub fn import_json_stores(app: &tauri::AppHandle, db: &Database) -> Result<(), String> {
+    if db.get_meta(JSON_IMPORTED_KEY)?.is_some() {
+        return Ok(());
+    }
+
+    // subscriptions and read state, the user data can't be lost so any error stops the import
+    let user_store = app
+        .store(USER_STORE)
+        .map_err(|_| "Failed to open user store")?;
+    let user_webtoons = load_key::<UserWebtoons>(&user_store, USER_STORE, USER_WEBTOONS_KEY)?;
+    let subscriptions = user_webtoons
+        .values()
+        .map(|uwt| SubscriptionRow::new(uwt).map(|row| (uwt.title.as_str(), row)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // the other stores are caches, an unreadable entry is just fetched again later
+    let webtoons_store = app
+        .store(WEBTOONS_STORE)
+        .map_err(|_| "Failed to open wt store")?;
+    let series = webtoons_store
+        .entries()
+        .into_iter()
+        .filter(|(key, _)| key != SCHEMA_VERSION_KEY)
+        .filter_map(|(_, raw_webtoon)| serde_json::from_value::<WebtoonInfo>(raw_webtoon).ok())
+        .map(|webtoon| SeriesRow::new(&webtoon))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let comments_store = app
+        .store(COMMENTS_STORE)
+        .map_err(|_| "Failed to open comments store")?;
+    let comments = comments_store
+        .entries()
+        .into_iter()
+        .map(|(key, cached_posts)| {
+            serde_json::to_string(&cached_posts)
+                .map(|posts| (key, posts))
+                .map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    db.in_transaction(|conn| {
+        for (title, subscription) in &subscriptions {
+            subscription
+                .write(conn)
+                .map_err(|e| format!("Failed to import the subscription to '{title}': {e}"))?;
+        }
+        for webtoon in &series {
+            webtoon.write(conn)?;
+        }
+        for (key, posts) in &comments {
+            write_cached_comments(conn, key, posts)?;
+        }
+        write_meta(conn, JSON_IMPORTED_KEY, "1")
+    })
+}
@@ -0,0 +1,196 @@
+//! Embedded SQLite library database
+//!
+//! Replaces the json stores for everything that grows with the library: series, episodes, read state,
+//! reading history, comments cache and the index of the images cached on disk. Queries go through the methods of
+//! [`Database`] in `repository`, commands never write SQL themselves.
+
+mod import;
+mod repository;
+
+pub use import::import_json_stores;
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use rusqlite::Connection;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use webtoon_sdk::WebtoonId;
+
+/// Every schema version of the database, `SCHEMA[n]` brings it from version `n` to `n + 1`
+///
+/// the version is kept in sqlite's `user_version`, never edit a released step: add a new one instead
+const SCHEMA: [&str; 5] = [
+    r#"
+    CREATE TABLE series (
+        key TEXT PRIMARY KEY NOT NULL,
+        -- WebtoonInfo as json, without its episodes
+        info TEXT NOT NULL
+    );
+
+    CREATE TABLE episodes (
+        series_key TEXT NOT NULL REFERENCES series(key) ON DELETE CASCADE,
+        number INTEGER NOT NULL,
+        -- EpisodePreview as json
+        preview TEXT NOT NULL,
+        PRIMARY KEY (series_key, number)
+    );
+
+    CREATE TABLE subscriptions (
+        key TEXT PRIMARY KEY NOT NULL,
+        -- UserWebtoon as json, without its read state
+        webtoon TEXT NOT NULL
+    );
+
+    CREATE TABLE read_state (
+        series_key TEXT NOT NULL REFERENCES subscriptions(key) ON DELETE CASCADE,
+        number INTEGER NOT NULL,
+        seen INTEGER NOT NULL,
+        PRIMARY KEY (series_key, number)
+    );
+
+    CREATE TABLE comments_cache (
+        key TEXT PRIMARY KEY NOT NULL,
+        posts TEXT NOT NULL
+    );
+
+    CREATE TABLE image_cache (
+        path TEXT PRIMARY KEY NOT NULL,
+        series_key TEXT NOT NULL,
+        -- unix timestamp in seconds
+        cached_at INTEGER NOT NULL
+    );
+    CREATE INDEX image_cache_series ON image_cache(series_key);
+
+    CREATE TABLE meta (
+        key TEXT PRIMARY KEY NOT NULL,
+        value TEXT NOT NULL
+    );
+"#,
+    // read state as a percentage, with the position of the reader
+    r#"
+    CREATE TABLE read_progress (
+        series_key TEXT NOT NULL REFERENCES subscriptions(key) ON DELETE CASCADE,
+        number INTEGER NOT NULL,
+        -- from 0 to 100, an episode at 100 is read
+        percent INTEGER NOT NULL,
+        -- panel at the top of the reader and the scrolled fraction of it
+        panel INTEGER NOT NULL DEFAULT 0,
+        panel_offset REAL NOT NULL DEFAULT 0,
+        PRIMARY KEY (series_key, number)
+    );
+
+    INSERT INTO read_progress (series_key, number, percent)
+        SELECT series_key, number, CASE WHEN seen THEN 100 ELSE 0 END FROM read_state;
+    DROP TABLE read_state;
+
+    UPDATE subscriptions
+        SET webtoon = json_remove(json_set(webtoon, '$.episode_progress', json('{}')), '$.episode_seen');
+"#,
+    // append-only reading history, kept when the webtoon is unsubscribed or deleted
+    r#"
+    CREATE TABLE history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        series_key TEXT NOT NULL,
+        -- WebtoonId as json
+        webtoon_id TEXT NOT NULL,
+        title TEXT NOT NULL,
+        episode INTEGER NOT NULL,
+        episode_title TEXT NOT NULL,
+        -- unix timestamps in seconds
+        opened_at INTEGER NOT NULL,
+        finished_at INTEGER,
+        -- seconds between the opening and the last activity in the episode
+        duration INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX history_opened_at ON history(opened_at);
+    CREATE INDEX history_episode ON history(series_key, episode);
+"#,
+    // tells a series whose episodes were all deleted from one whose episodes were never fetched
+    r#"
+    ALTER TABLE series ADD COLUMN episodes_fetched INTEGER NOT NULL DEFAULT 0;
+    UPDATE series SET episodes_fetched = 1 WHERE key IN (SELECT series_key FROM episodes);
+"#,
+    // the duration of a history entry only adds up the time spent reading, see `touch_history_entry`
+    r#"
+    -- unix timestamp in seconds of the last activity in the episode
+    ALTER TABLE history ADD COLUMN last_active_at INTEGER NOT NULL DEFAULT 0;
+    UPDATE history SET last_active_at = opened_at + duration;
+"#,
+];
+
+/// Handle on the library database, managed as a tauri state
+///
+/// the connection is behind a blocking mutex used from async commands: the methods only hold it for their
+/// queries, (de)serializing the json columns happens before or after
+pub struct Database {
+    conn: Mutex<Connection>,
+    /// keyed by `WebtoonId::store_key`, see `lock_series`
+    series_locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+impl Database {
+    /// opens (or creates) the database and brings its schema up to date
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let mut conn = Connection::open(path).map_err(|e| e.to_string())?;
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+            .map_err(|e| e.to_string())?;
+        Self::migrate(&mut conn)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            series_locks: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn migrate(conn: &mut Connection) -> Result<(), String> {
+        let version = conn
+            .query_row("PRAGMA user_version", [], |row| row.get::<_, usize>(0))
+            .map_err(|e| e.to_string())?;
+        if version > SCHEMA.len() {
+            return Err(format!(
+                "The library database is at version {version} but this app only knows up to {}, it was written by a newer version of the app",
+                SCHEMA.len()
+            ));
+        }
+
+        for (i, step) in SCHEMA.iter().enumerate().skip(version) {
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            tx.execute_batch(step)
+                .and_then(|_| tx.pragma_update(None, "user_version", i + 1))
+                .map_err(|e| {
+                    format!(
+                        "Failed to migrate the library database to version {}: {e}",
+                        i + 1
+                    )
+                })?;
+            tx.commit().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|_| "The library database is poisoned".to_string())
+    }
+
+    /// held from reading a series until it is saved back, so that two updates of the same series
+    /// (e.g. the background refresh and the user deleting episodes) don't overwrite each other
+    pub async fn lock_series(&self, id: WebtoonId) -> Result<OwnedMutexGuard<()>, String> {
+        let lock = self
+            .series_locks
+            .lock()
+            .map_err(|_| "The series locks are poisoned".to_string())?
+            .entry(id.store_key())
+            .or_default()
+            .clone();
+        Ok(lock.lock_owned().await)
+    }
+}
@@ -0,0 +1,654 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Params};
+use serde::{de::DeserializeOwned, Serialize};
+use webtoon_sdk::{
+    episodes::{EpisodePreview, Episodes},
+    webtoon::WebtoonInfo,
+    WebtoonId,
+};
+
+use super::Database;
+use crate::{
+    history::HistoryEntry,
+    store::{ReadProgress, UserWebtoon, UserWebtoons},
+};
+
+const UPSERT_PROGRESS: &str =
+    "INSERT INTO read_progress (series_key, number, percent, panel, panel_offset) VALUES (?1, ?2, ?3, ?4, ?5)
+     ON CONFLICT (series_key, number) DO UPDATE
+     SET percent = excluded.percent, panel = excluded.panel, panel_offset = excluded.panel_offset";
+
+/// an episode reopened within this delay after the last activity in it continues its history entry
+const HISTORY_RESUME_WINDOW: u64 = 30 * 60;
+/// longest pause between two activities still counted as reading time
+///
+/// the reader saves its position a second after each scroll, a longer pause means the user stepped away
+const HISTORY_MAX_PAUSE: u64 = 2 * 60;
+
+fn unix_now() -> Result<u64, String> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .map_err(|e| e.to_string())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| e.to_string())
+}
+
+fn from_json<T: DeserializeOwned>(raw: &str) -> Result<T, String> {
+    serde_json::from_str::<T>(raw).map_err(|e| e.to_string())
+}
+
+impl Database {
+    /// runs `f` in a single transaction, nothing is written if it fails
+    pub(super) fn in_transaction(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        f(&tx)?;
+        tx.commit().map_err(|e| e.to_string())
+    }
+}
+
+/// A webtoon serialized for the `series` and `episodes` tables, built before locking the connection
+pub(super) struct SeriesRow {
+    key: String,
+    info: String,
+    /// `None` when the episodes weren't fetched yet, the stored ones are then kept
+    episodes: Option<Vec<(usize, String)>>,
+}
+
+impl SeriesRow {
+    pub(super) fn new(webtoon: &WebtoonInfo) -> Result<Self, String> {
+        Ok(Self {
+            key: webtoon.id.store_key(),
+            info: to_json(&WebtoonInfo {
+                episodes: None,
+                ..webtoon.clone()
+            })?,
+            episodes: webtoon
+                .episodes
+                .as_ref()
+                .map(|episodes| {
+                    episodes
+                        .values()
+                        .map(|ep| to_json(ep).map(|preview| (ep.number, preview)))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?,
+        })
+    }
+
+    /// upserts the webtoon info and reconciles its episodes: only the changed rows are written
+    ///
+    /// once fetched, the episodes stay flagged as such even when an info without them is saved
+    pub(super) fn write(&self, conn: &Connection) -> Result<(), String> {
+        conn.execute(
+            "INSERT INTO series (key, info, episodes_fetched) VALUES (?1, ?2, ?3)
+             ON CONFLICT (key) DO UPDATE SET info = excluded.info,
+                episodes_fetched = max(episodes_fetched, excluded.episodes_fetched)",
+            params![self.key, self.info, self.episodes.is_some()],
+        )
+        .map_err(|e| e.to_string())?;
+
+        if let Some(episodes) = &self.episodes {
+            {
+                let mut upsert_ep = conn
+                    .prepare_cached(
+                        "INSERT INTO episodes (series_key, number, preview) VALUES (?1, ?2, ?3)
+                         ON CONFLICT (series_key, number) DO UPDATE SET preview = excluded.preview
+                         WHERE preview != excluded.preview",
+                    )
+                    .map_err(|e| e.to_string())?;
+                for (number, preview) in episodes {
+                    upsert_ep
+                        .execute(params![self.key, number, preview])
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+
+            // episodes that disappeared from the listing
+            let numbers = to_json(&episodes.iter().map(|(n, _)| n).collect::<Vec<_>>())?;
+            conn.execute(
+                "DELETE FROM episodes WHERE series_key = ?1
+                 AND number NOT IN (SELECT value FROM json_each(?2))",
+                params![self.key, numbers],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// A subscription serialized for the `subscriptions` and `read_progress` tables
+pub(super) struct SubscriptionRow {
+    key: String,
+    webtoon: String,
+    progress: Vec<(usize, ReadProgress)>,
+}
+
+impl SubscriptionRow {
+    pub(super) fn new(webtoon: &UserWebtoon) -> Result<Self, String> {
+        Ok(Self {
+            key: webtoon.id.store_key(),
+            webtoon: to_json(&UserWebtoon {
+                episode_progress: Default::default(),
+                ..webtoon.clone()
+            })?,
+            progress: webtoon
+                .episode_progress
+                .iter()
+                .map(|(number, progress)| (*number, *progress))
+                .collect(),
+        })
+    }
+
+    /// adds or replaces the subscription along with its read progress
+    pub(super) fn write(&self, conn: &Connection) -> Result<(), String> {
+        conn.execute(
+            "INSERT INTO subscriptions (key, webtoon) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET webtoon = excluded.webtoon",
+            params![self.key, self.webtoon],
+        )
+        .map_err(|e| e.to_string())?;
+
+        let mut upsert_progress = conn
+            .prepare_cached(UPSERT_PROGRESS)
+            .map_err(|e| e.to_string())?;
+        for (number, progress) in &self.progress {
+            upsert_progress
+                .execute(params![
+                    self.key,
+                    number,
+                    progress.percent,
+                    progress.panel,
+                    progress.offset
+                ])
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// `posts` is already serialized
+pub(super) fn write_cached_comments(
+    conn: &Connection,
+    key: &str,
+    posts: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO comments_cache (key, posts) VALUES (?1, ?2)
+         ON CONFLICT (key) DO UPDATE SET posts = excluded.posts",
+        params![key, posts],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+pub(super) fn write_meta(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+/* SERIES */
+
+impl Database {
+    /// webtoon info with its episodes
+    pub fn get_series(&self, id: WebtoonId) -> Result<Option<WebtoonInfo>, String> {
+        let Some(mut webtoon) = self.get_series_info(id)? else {
+            return Ok(None);
+        };
+
+        let (episodes_fetched, previews) = {
+            let conn = self.conn()?;
+            let episodes_fetched = conn
+                .query_row(
+                    "SELECT episodes_fetched FROM series WHERE key = ?1",
+                    params![id.store_key()],
+                    |row| row.get::<_, bool>(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default();
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT preview FROM episodes WHERE series_key = ?1 ORDER BY number",
+                )
+                .map_err(|e| e.to_string())?;
+            let previews = stmt
+                .query_map(params![id.store_key()], |row| row.get::<_, String>(0))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            (episodes_fetched, previews)
+        };
+
+        // an info saved before its episodes were fetched has none, not an empty list
+        if episodes_fetched {
+            webtoon.episodes = Some(
+                previews
+                    .iter()
+                    .map(|raw| from_json::<EpisodePreview>(raw).map(|ep| (ep.number, ep)))
+                    .collect::<Result<Episodes, _>>()?,
+            );
+        }
+        Ok(Some(webtoon))
+    }
+
+    /// webtoon info **without** its episodes, for when only the metadata are needed
+    pub fn get_series_info(&self, id: WebtoonId) -> Result<Option<WebtoonInfo>, String> {
+        let raw_info = self
+            .conn()?
+            .query_row(
+                "SELECT info FROM series WHERE key = ?1",
+                params![id.store_key()],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        raw_info
+            .map(|raw| from_json::<WebtoonInfo>(&raw))
+            .transpose()
+    }
+
+    /// upserts the webtoon info and reconciles its episodes: only the changed rows are written
+    pub fn save_series(&self, webtoon: &WebtoonInfo) -> Result<(), String> {
+        let row = SeriesRow::new(webtoon)?;
+        self.in_transaction(|conn| row.write(conn))
+    }
+
+    /// returns whether the webtoon was stored
+    pub fn delete_series(&self, id: WebtoonId) -> Result<bool, String> {
+        let conn = self.conn()?;
+        conn.execute("DELETE FROM series WHERE key = ?1", params![id.store_key()])
+            .map(|deleted| deleted > 0)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn delete_episodes(&self, id: WebtoonId, numbers: &[usize]) -> Result<(), String> {
+        let numbers = to_json(&numbers)?;
+        let conn = self.conn()?;
+        conn.execute(
+            "DELETE FROM episodes WHERE series_key = ?1
+             AND number IN (SELECT value FROM json_each(?2))",
+            params![id.store_key(), numbers],
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+}
+
+/* SUBSCRIPTIONS */
+
+impl Database {
+    /// every subscribed webtoon with its read progress
+    pub fn subscriptions(&self) -> Result<UserWebtoons, String> {
+        let (rows, read_progress) = {
+            let conn = self.conn()?;
+
+            let mut stmt = conn
+                .prepare("SELECT key, webtoon FROM subscriptions")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+
+            let mut stmt = conn
+                .prepare(
+                    "SELECT series_key, number, percent, panel, panel_offset FROM read_progress",
+                )
+                .map_err(|e| e.to_string())?;
+            let read_progress = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, usize>(1)?,
+                        ReadProgress {
+                            percent: row.get(2)?,
+                            panel: row.get(3)?,
+                            offset: row.get(4)?,
+                        },
+                    ))
+                })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            (rows, read_progress)
+        };
+
+        let mut webtoons = rows
+            .into_iter()
+            .map(|(key, raw)| from_json::<UserWebtoon>(&raw).map(|uwt| (key, uwt)))
+            .collect::<Result<UserWebtoons, _>>()?;
+        for (key, number, progress) in read_progress {
+            if let Some(uwt) = webtoons.get_mut(&key) {
+                uwt.episode_progress.insert(number, progress);
+            }
+        }
+
+        Ok(webtoons)
+    }
+
+    /// adds or replaces the subscription along with its read progress
+    pub fn subscribe(&self, webtoon: &UserWebtoon) -> Result<(), String> {
+        let row = SubscriptionRow::new(webtoon)?;
+        self.in_transaction(|conn| row.write(conn))
+    }
+
+    /// the read progress goes with the subscription
+    pub fn unsubscribe(&self, id: WebtoonId) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "DELETE FROM subscriptions WHERE key = ?1",
+            params![id.store_key()],
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+
+    /// only writes the subscription itself, its read progress is left as is
+    pub fn update_subscription(&self, webtoon: &UserWebtoon) -> Result<(), String> {
+        self.save_episodes_progress(webtoon, &[])
+    }
+
+    /// only writes the progress of `ep_num` and the subscription last seen date
+    pub fn save_progress(&self, webtoon: &UserWebtoon, ep_num: usize) -> Result<(), String> {
+        self.save_episodes_progress(webtoon, &[ep_num])
+    }
+
+    /// writes the progress of `ep_nums` as in `webtoon.episode_progress`, and the subscription last seen date
+    ///
+    /// the saved progress of an episode missing from `episode_progress` is removed
+    pub fn save_episodes_progress(
+        &self,
+        webtoon: &UserWebtoon,
+        ep_nums: &[usize],
+    ) -> Result<(), String> {
+        let key = webtoon.id.store_key();
+        let raw_webtoon = to_json(&UserWebtoon {
+            episode_progress: Default::default(),
+            ..webtoon.clone()
+        })?;
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE subscriptions SET webtoon = ?2 WHERE key = ?1",
+            params![key, raw_webtoon],
+        )
+        .map_err(|e| e.to_string())?;
+        for &ep_num in ep_nums {
+            match webtoon.episode_progress.get(&ep_num) {
+                Some(progress) => tx.execute(
+                    UPSERT_PROGRESS,
+                    params![
+                        key,
+                        ep_num,
+                        progress.percent,
+                        progress.panel,
+                        progress.offset
+                    ],
+                ),
+                None => tx.execute(
+                    "DELETE FROM read_progress WHERE series_key = ?1 AND number = ?2",
+                    params![key, ep_num],
+                ),
+            }
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+}
+
+/* CACHES */
+
+impl Database {
+    pub fn get_cached_comments<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, String> {
+        let raw_posts = self
+            .conn()?
+            .query_row(
+                "SELECT posts FROM comments_cache WHERE key = ?1",
+                params![key],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        // a cache entry of an older format is just refetched
+        Ok(raw_posts.and_then(|raw| from_json::<T>(&raw).ok()))
+    }
+
+    pub fn set_cached_comments<T: Serialize>(&self, key: &str, posts: &T) -> Result<(), String> {
+        let posts = to_json(posts)?;
+        let conn = self.conn()?;
+        write_cached_comments(&conn, key, &posts)
+    }
+
+    /// remembers which webtoon the images cached on disk belong to
+    pub fn index_images(&self, id: WebtoonId, paths: &[String]) -> Result<(), String> {
+        let cached_at = unix_now()?;
+        let key = id.store_key();
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        {
+            let mut upsert_image = tx
+                .prepare_cached(
+                    "INSERT INTO image_cache (path, series_key, cached_at) VALUES (?1, ?2, ?3)
+                     ON CONFLICT (path) DO UPDATE SET cached_at = excluded.cached_at",
+                )
+                .map_err(|e| e.to_string())?;
+            for path in paths {
+                upsert_image
+                    .execute(params![path, key, cached_at])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// indexes the thumbnails of the webtoon and of its episodes
+    pub fn index_series_images(&self, webtoon: &WebtoonInfo) -> Result<(), String> {
+        let thumbnails = std::iter::once(webtoon.thumbnail.clone())
+            .chain(
+                webtoon
+                    .episodes
+                    .iter()
+                    .flat_map(|episodes| episodes.values().map(|ep| ep.thumbnail.clone())),
+            )
+            .collect::<Vec<_>>();
+        self.index_images(webtoon.id, &thumbnails)
+    }
+
+    /// forgets the images of a webtoon, returns their paths so that they can be removed from the disk
+    pub fn take_indexed_images(&self, id: WebtoonId) -> Result<Vec<String>, String> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let paths = {
+            let mut stmt = tx
+                .prepare("DELETE FROM image_cache WHERE series_key = ?1 RETURNING path")
+                .map_err(|e| e.to_string())?;
+            let paths = stmt
+                .query_map(params![id.store_key()], |row| row.get::<_, String>(0))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            paths
+        };
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(paths)
+    }
+
+    pub fn get_meta(&self, key: &str) -> Result<Option<String>, String> {
+        let conn = self.conn()?;
+        conn.query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            params![key],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn set_meta(&self, key: &str, value: &str) -> Result<(), String> {
+        let conn = self.conn()?;
+        write_meta(&conn, key, value)
+    }
+}
+
+/* HISTORY */
+
+/// `clauses` follow the `SELECT ... FROM history`
+fn query_history(
+    conn: &Connection,
+    clauses: &str,
+    params: impl Params,
+) -> Result<Vec<HistoryEntry>, String> {
+    let mut stmt = conn
+        .prepare_cached(&format!(
+            "SELECT id, webtoon_id, title, episode, episode_title, opened_at, finished_at, duration
+             FROM history {clauses}"
+        ))
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map(params, |row| {
+            let raw_wt_id = row.get::<_, String>(1)?;
+            Ok(HistoryEntry {
+                id: row.get(0)?,
+                wt_id: serde_json::from_str(&raw_wt_id).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(1, Type::Text, Box::new(e))
+                })?,
+                title: row.get(2)?,
+                episode: row.get(3)?,
+                episode_title: row.get(4)?,
+                opened_at: row.get(5)?,
+                finished_at: row.get(6)?,
+                duration: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    entries
+}
+
+impl Database {
+    /// appends an entry for the episode just opened, unless the latest entry is this same episode, unfinished
+    /// and recently active: it's then just being reopened
+    pub fn open_history_entry(
+        &self,
+        id: WebtoonId,
+        title: &str,
+        episode: usize,
+        episode_title: &str,
+    ) -> Result<(), String> {
+        let key = id.store_key();
+        let raw_id = to_json(&id)?;
+        let now = unix_now()?;
+
+        let conn = self.conn()?;
+        let reopened = conn
+            .query_row(
+                "SELECT series_key = ?1 AND episode = ?2 AND finished_at IS NULL
+                 AND last_active_at + ?4 >= ?3
+                 FROM history ORDER BY id DESC LIMIT 1",
+                params![key, episode, now, HISTORY_RESUME_WINDOW],
+                |row| row.get::<_, bool>(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default();
+        if reopened {
+            return Ok(());
+        }
+
+        conn.execute(
+            "INSERT INTO history
+             (series_key, webtoon_id, title, episode, episode_title, opened_at, last_active_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            params![key, raw_id, title, episode, episode_title, now],
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+
+    /// adds the time since the last activity to the duration of the latest entry of the episode, up to
+    /// `HISTORY_MAX_PAUSE`, and ends it if `finished`
+    ///
+    /// a finished entry isn't updated anymore
+    pub fn touch_history_entry(
+        &self,
+        id: WebtoonId,
+        episode: usize,
+        finished: bool,
+    ) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE history SET duration = duration + min(max(?3 - last_active_at, 0), ?5),
+             last_active_at = ?3, finished_at = CASE WHEN ?4 THEN ?3 ELSE NULL END
+             WHERE id = (SELECT max(id) FROM history WHERE series_key = ?1 AND episode = ?2)
+             AND finished_at IS NULL",
+            params![
+                id.store_key(),
+                episode,
+                unix_now()?,
+                finished,
+                HISTORY_MAX_PAUSE
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+
+    /// most recent first, returns whether there are more entries after this page
+    pub fn history(
+        &self,
+        page: usize,
+        per_page: usize,
+    ) -> Result<(Vec<HistoryEntry>, bool), String> {
+        let conn = self.conn()?;
+        let mut entries = query_history(
+            &conn,
+            "ORDER BY opened_at DESC, id DESC LIMIT ?1 OFFSET ?2",
+            params![per_page + 1, page.saturating_sub(1) * per_page],
+        )?;
+
+        let has_more = entries.len() > per_page;
+        entries.truncate(per_page);
+        Ok((entries, has_more))
+    }
+
+    /// every entry, oldest first
+    pub fn full_history(&self) -> Result<Vec<HistoryEntry>, String> {
+        let conn = self.conn()?;
+        query_history(&conn, "ORDER BY opened_at, id", [])
+    }
+
+    /// returns whether the entry existed
+    pub fn delete_history_entry(&self, entry_id: i64) -> Result<bool, String> {
+        let conn = self.conn()?;
+        conn.execute("DELETE FROM history WHERE id = ?1", params![entry_id])
+            .map(|deleted| deleted > 0)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn clear_history(&self) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute("DELETE FROM history", [])
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,70 @@
+//! Reading history
+//!
+//! Every episode opened in the reader appends an entry, which `save_read_progress` and `mark_as_read` keep
+//! up to date until the episode is finished. Unlike `last_seen`, entries are never overwritten and outlive
+//! the subscription: they are only removed by the user.
+
+use serde::{Deserialize, Serialize};
+use webtoon_sdk::WebtoonId;
+
+use crate::db::Database;
+
+/// number of entries sent to the frontend at once
+const HISTORY_PAGE_LEN: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub wt_id: WebtoonId,
+    /// webtoon title when the episode was opened
+    pub title: String,
+    pub episode: usize,
+    pub episode_title: String,
+    /// unix timestamp in seconds
+    pub opened_at: u64,
+    /// unix timestamp in seconds, `None` if the episode wasn't read until its end
+    pub finished_at: Option<u64>,
+    /// seconds spent in the episode
+    pub duration: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryPage {
+    /// most recent first
+    pub entries: Vec<HistoryEntry>,
+    /// starts at `1`
+    pub page: usize,
+    pub has_more: bool,
+}
+
+/* Commands */
+
+#[tauri::command]
+pub async fn get_history(
+    db: tauri::State<'_, Database>,
+    page: usize,
+) -> Result<HistoryPage, String> {
+    let page = page.max(1);
+    let (entries, has_more) = db.history(page, HISTORY_PAGE_LEN)?;
+    Ok(HistoryPage {
+        entries,
+        page,
+        has_more,
+    })
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn delete_history_entry(
+    db: tauri::State<'_, Database>,
+    entry_id: i64,
+) -> Result<(), String> {
+    if !db.delete_history_entry(entry_id)? {
+        return Err("History entry not found".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn clear_history(db: tauri::State<'_, Database>) -> Result<(), String> {
+    db.clear_history()
+}
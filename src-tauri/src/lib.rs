@@ -1,27 +1,55 @@
+mod auto_refresh;
+mod backup;
+mod bookmarks;
 mod constants;
+mod db;
+mod history;
+mod migrations;
+mod recommender;
+mod stats;
 mod store;
 mod webtoon_handler;
 
 use crate::{
-    constants::{USER_LANG_KEY, USER_STORE, USER_WEBTOONS_KEY},
+    auto_refresh::{get_new_episodes, run_scheduler, NewEpisodes},
+    backup::export_backup,
+    bookmarks::{add_bookmark, delete_bookmark, set_bookmark_note, Bookmark},
+    constants::{
+        DATABASE_FILE, LOCAL_LIBRARY_KEY, USER_BOOKMARKS_KEY, USER_LANG_KEY, USER_MUTE_RULES_KEY,
+        USER_READER_SETTINGS_KEY, USER_SHELVES_KEY, USER_STORE,
+    },
+    db::{import_json_stores, Database},
+    history::{clear_history, delete_history_entry, get_history},
+    migrations::run_migrations,
+    recommender::get_personal_recommandations,
+    stats::get_reading_stats,
     store::{
-        change_language, get_user_data, mark_as_read, subscribe_to_webtoon,
-        unsubscribe_from_webtoon, UserData, UserWebtoons,
+        change_language, get_library_overview, get_read_progress, get_user_data, load_key,
+        mark_as_read, mark_episodes_read, mark_episodes_unread, save_read_progress, set_mute_rules,
+        set_reader_settings, set_shelves, set_webtoon_shelf, set_webtoon_tags,
+        subscribe_to_webtoon, unsubscribe_from_webtoon, MuteRules, ReaderSettings, Shelves,
+        UserData,
     },
     webtoon_handler::{
         creator::get_author_info,
-        episodes::{force_refresh_episodes, get_episode_data, get_episode_post},
-        webtoon::{
-            delete_episodes, delete_webtoon, get_homepage_recommandations, get_webtoon_info,
-            search_webtoon,
+        episodes::{
+            force_refresh_episodes, get_episode_data, get_episode_post, get_post_replies,
         },
+        genre::get_genre_webtoons,
+        local::{get_local_library, set_local_library},
+        ranking::get_ranking,
+        recommandations::{get_homepage_recommandations, SessionSeed},
+        schedule::get_updating_today,
+        webtoon::{delete_episodes, delete_webtoon, get_webtoon_info, search_webtoon},
     },
 };
 
+use std::path::PathBuf;
+
 use tauri::Manager;
 use tauri_plugin_store::StoreExt;
 use tokio::sync::Mutex;
-use webtoon::platform::webtoons::Language;
+use webtoon_sdk::{local::set_library_root, Language};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -29,39 +57,102 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
+            // bring the stores to the current schema before reading them,
+            // the app can't run on stores it can't read
+            run_migrations(app.handle())
+                .map_err(|e| format!("Failed to migrate the stores: {e}"))?;
+
+            // open the library database, importing the json stores of the previous versions
+            let db = Database::open(&app.path().app_data_dir()?.join(DATABASE_FILE))?;
+            import_json_stores(app.handle(), &db)?;
+
             // load user store
             let user_store = app.store(USER_STORE)?;
 
-            let user_language = serde_json::from_value::<Language>(
-                user_store.get(USER_LANG_KEY).unwrap_or_default(),
-            )
-            .unwrap_or_default();
-            let user_webtoons = serde_json::from_value::<UserWebtoons>(
-                user_store.get(USER_WEBTOONS_KEY).unwrap_or_default(),
-            )
-            .unwrap_or_default();
+            let user_language = load_key::<Language>(&user_store, USER_STORE, USER_LANG_KEY)?;
+            let user_mute_rules =
+                load_key::<MuteRules>(&user_store, USER_STORE, USER_MUTE_RULES_KEY)?;
+            let user_reader_settings =
+                load_key::<ReaderSettings>(&user_store, USER_STORE, USER_READER_SETTINGS_KEY)?;
+            let user_bookmarks =
+                load_key::<Vec<Bookmark>>(&user_store, USER_STORE, USER_BOOKMARKS_KEY)?;
+            let user_shelves = load_key::<Shelves>(&user_store, USER_STORE, USER_SHELVES_KEY)?;
+            let user_webtoons = db.subscriptions()?;
+
+            // local series are read from the folder chosen by the user
+            let local_library = user_store
+                .get(LOCAL_LIBRARY_KEY)
+                .and_then(|path| path.as_str().map(PathBuf::from));
+            set_library_root(local_library);
 
-            let user_data: UserData = UserData::new(user_language, user_webtoons);
+            let user_data: UserData = UserData::new(
+                user_language,
+                user_webtoons,
+                user_mute_rules,
+                user_reader_settings,
+                user_bookmarks,
+                user_shelves,
+            );
 
             // inject user store
             app.manage(Mutex::new(user_data));
+            app.manage(db);
+            app.manage(Mutex::new(NewEpisodes::default()));
+
+            // look for new episodes of the subscriptions in the background
+            tauri::async_runtime::spawn(run_scheduler(app.handle().clone()));
+            app.manage(SessionSeed::default());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // stores
             get_user_data,
+            get_library_overview,
             subscribe_to_webtoon,
             unsubscribe_from_webtoon,
             mark_as_read,
+            mark_episodes_read,
+            mark_episodes_unread,
+            save_read_progress,
+            get_read_progress,
             change_language,
+            set_mute_rules,
+            set_reader_settings,
+            set_shelves,
+            set_webtoon_shelf,
+            set_webtoon_tags,
             // webtoons
             search_webtoon,
             get_webtoon_info,
             get_homepage_recommandations,
+            get_personal_recommandations,
             delete_episodes,
             delete_webtoon,
+            get_new_episodes,
+            // history
+            get_history,
+            delete_history_entry,
+            clear_history,
+            // bookmarks
+            add_bookmark,
+            set_bookmark_note,
+            delete_bookmark,
+            // backup
+            export_backup,
+            // statistics
+            get_reading_stats,
+            // genres
+            get_genre_webtoons,
+            // local library
+            get_local_library,
+            set_local_library,
+            // rankings
+            get_ranking,
+            // schedule
+            get_updating_today,
             // episodes
             get_episode_post,
+            get_post_replies,
             get_episode_data,
             force_refresh_episodes,
             // author
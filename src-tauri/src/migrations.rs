@@ -0,0 +1,219 @@
+//! Schema versions of the json stores
+//!
+//! Each store keeps its version under `SCHEMA_VERSION_KEY`, a store without it predates versioning (version `0`).
+//! At startup the pending steps of each store are run in order, after copying the store file aside.
+
+use serde_json::{json, Map, Value};
+use tauri::{Manager, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+use webtoon_sdk::WebtoonId;
+
+use crate::constants::{SCHEMA_VERSION_KEY, USER_STORE, USER_WEBTOONS_KEY, WEBTOONS_STORE};
+
+/// One step bringing `store` to `version`
+struct Migration {
+    store: &'static str,
+    version: u32,
+    description: &'static str,
+    migrate: fn(&Store<Wry>) -> Result<(), String>,
+}
+
+/// every migration ever written, never edit a released step: add a new one instead
+const MIGRATIONS: [Migration; 3] = [
+    Migration {
+        store: USER_STORE,
+        version: 1,
+        description: "key the user webtoons by `WebtoonId::store_key`",
+        migrate: rekey_user_webtoons,
+    },
+    Migration {
+        store: USER_STORE,
+        version: 2,
+        description: "store the read state of the episodes as a `ReadProgress`",
+        migrate: user_webtoons_read_progress,
+    },
+    Migration {
+        store: WEBTOONS_STORE,
+        version: 1,
+        description: "key the webtoons by `WebtoonId::store_key`",
+        migrate: rekey_webtoons,
+    },
+];
+
+/// latest schema version of `store`
+fn latest_version(store: &str) -> u32 {
+    MIGRATIONS
+        .iter()
+        .filter(|m| m.store == store)
+        .map(|m| m.version)
+        .max()
+        .unwrap_or_default()
+}
+
+/// brings every store to its latest version, must run before anything reads them
+///
+/// once a store was backed up, the errors tell where the backup is
+pub fn run_migrations(app: &tauri::AppHandle) -> Result<(), String> {
+    for store_name in [USER_STORE, WEBTOONS_STORE] {
+        migrate_store(app, store_name)?;
+    }
+    Ok(())
+}
+
+fn migrate_store(app: &tauri::AppHandle, store_name: &str) -> Result<(), String> {
+    let store = app
+        .store(store_name)
+        .map_err(|e| format!("Failed to open {store_name}: {e}"))?;
+
+    let latest = latest_version(store_name);
+    let current = match store.get(SCHEMA_VERSION_KEY) {
+        Some(raw_version) => serde_json::from_value::<u32>(raw_version)
+            .map_err(|e| format!("{store_name} has an invalid schema version: {e}"))?,
+        None => 0,
+    };
+    if current > latest {
+        return Err(format!(
+            "{store_name} is at schema version {current} but this app only knows up to {latest}, it was written by a newer version of the app"
+        ));
+    }
+    if current == latest {
+        return Ok(());
+    }
+
+    // a new store has nothing to migrate
+    if store.is_empty() {
+        store.set(SCHEMA_VERSION_KEY, latest);
+        return store.save().map_err(|e| e.to_string());
+    }
+
+    let backup_path = backup_store(app, store_name, current)?;
+    let mut pending = MIGRATIONS
+        .iter()
+        .filter(|m| m.store == store_name && m.version > current)
+        .collect::<Vec<_>>();
+    pending.sort_by_key(|m| m.version);
+
+    for migration in pending {
+        (migration.migrate)(&store).map_err(|e| {
+            format!(
+                "Failed to migrate {store_name} to version {} ({}): {e}. The previous data was kept in {}",
+                migration.version,
+                migration.description,
+                backup_path.to_string_lossy()
+            )
+        })?;
+        store.set(SCHEMA_VERSION_KEY, migration.version);
+    }
+
+    store.save().map_err(|e| {
+        format!(
+            "Failed to save the migrated {store_name}: {e}. The previous data was kept in {}",
+            backup_path.to_string_lossy()
+        )
+    })
+}
+
+/// copies the store file aside as `<store>.v<version>.bak`
+fn backup_store(
+    app: &tauri::AppHandle,
+    store_name: &str,
+    version: u32,
+) -> Result<std::path::PathBuf, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let store_path = data_dir.join(store_name);
+    let backup_path = data_dir.join(format!("{store_name}.v{version}.bak"));
+
+    std::fs::copy(&store_path, &backup_path).map_err(|e| {
+        format!(
+            "Failed to back up {} before migrating it: {e}",
+            store_path.to_string_lossy()
+        )
+    })?;
+    Ok(backup_path)
+}
+
+/* STEPS */
+
+/// the subscribed webtoons as raw json, the steps can't rely on the current `UserWebtoon`
+fn raw_user_webtoons(store: &Store<Wry>) -> Result<Option<Map<String, Value>>, String> {
+    match store.get(USER_WEBTOONS_KEY) {
+        Some(Value::Object(raw_webtoons)) => Ok(Some(raw_webtoons)),
+        Some(_) => Err("The subscribed webtoons aren't a json object".to_string()),
+        None => Ok(None),
+    }
+}
+
+/// v1: `UserWebtoons` were keyed by the bare `wt_id`
+fn rekey_user_webtoons(store: &Store<Wry>) -> Result<(), String> {
+    let Some(raw_webtoons) = raw_user_webtoons(store)? else {
+        return Ok(());
+    };
+
+    let mut migrated_webtoons = Map::new();
+    for raw_webtoon in raw_webtoons.into_values() {
+        let id = serde_json::from_value::<WebtoonId>(raw_webtoon["id"].clone())
+            .map_err(|e| format!("Failed to read the subscribed webtoons: {e}"))?;
+        migrated_webtoons.insert(id.store_key(), raw_webtoon);
+    }
+    store.set(USER_WEBTOONS_KEY, Value::Object(migrated_webtoons));
+    Ok(())
+}
+
+/// v2: the read state was a bool per episode in `episode_seen`
+///
+/// the progress is written as it was laid out in v2, not through the current `ReadProgress`
+fn user_webtoons_read_progress(store: &Store<Wry>) -> Result<(), String> {
+    let Some(mut raw_webtoons) = raw_user_webtoons(store)? else {
+        return Ok(());
+    };
+
+    for raw_webtoon in raw_webtoons.values_mut() {
+        let Value::Object(raw_webtoon) = raw_webtoon else {
+            return Err("A subscribed webtoon isn't a json object".to_string());
+        };
+        let episode_seen = match raw_webtoon.remove("episode_seen") {
+            Some(Value::Object(episode_seen)) => episode_seen,
+            _ => Map::new(),
+        };
+
+        let episode_progress = episode_seen
+            .into_iter()
+            .map(|(number, seen)| {
+                let percent = match seen.as_bool().unwrap_or_default() {
+                    true => 100,
+                    false => 0,
+                };
+                (
+                    number,
+                    json!({ "percent": percent, "panel": 0, "offset": 0.0 }),
+                )
+            })
+            .collect::<Map<_, _>>();
+        raw_webtoon.insert(
+            "episode_progress".to_string(),
+            Value::Object(episode_progress),
+        );
+    }
+    store.set(USER_WEBTOONS_KEY, Value::Object(raw_webtoons));
+    Ok(())
+}
+
+/// v1: webtoons were keyed by the bare `wt_id`
+fn rekey_webtoons(store: &Store<Wry>) -> Result<(), String> {
+    // every entry is read before the store is changed, so that a failure leaves it untouched
+    let mut rekeyed_webtoons = vec![];
+    for (old_key, raw_webtoon) in store.entries() {
+        if old_key.parse::<usize>().is_err() {
+            continue;
+        }
+        let id = serde_json::from_value::<WebtoonId>(raw_webtoon["id"].clone())
+            .map_err(|e| format!("Failed to read the stored webtoon {old_key}: {e}"))?;
+        rekeyed_webtoons.push((old_key, id.store_key(), raw_webtoon));
+    }
+
+    for (old_key, new_key, raw_webtoon) in rekeyed_webtoons {
+        store.delete(&old_key);
+        store.set(new_key, raw_webtoon);
+    }
+    Ok(())
+}
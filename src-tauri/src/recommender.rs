@@ -0,0 +1,283 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tokio::sync::Mutex;
+use webtoon_sdk::{
+    genres::{fetch_genre, GenreSortOrder},
+    image_dl::download_images,
+    recommandations::{fetch_canvas, fetch_original},
+    search::WebtoonSearchInfo,
+    Genre, Language, WebtoonId, WtType,
+};
+
+use crate::{
+    db::Database,
+    store::{UserData, UserWebtoon},
+};
+
+/// how many of the profile's top genres are used to gather candidates
+const CANDIDATE_GENRES: usize = 3;
+/// after this delay without reading, a webtoon weights half as much in the profile
+const RECENCY_HALF_LIFE: Duration = Duration::from_secs(30 * 24 * 3600);
+/// weight of a subscription that was never opened
+const NEVER_READ_WEIGHT: f64 = 0.25;
+
+const GENRE_SCORE: f64 = 1.0;
+const CREATOR_SCORE: f64 = 1.5;
+/// small bonus given to the webtoons ranked high in the site listings
+const POPULARITY_SCORE: f64 = 0.3;
+
+/// A candidate webtoon, ranked against the user's reading profile
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PersonalRecommendation {
+    pub webtoon: WebtoonSearchInfo,
+    pub score: f64,
+    /// short explanation, e.g. "Because you read Lore Olympus"
+    pub reason: String,
+}
+
+/// A feature of the profile (genre or creator) and which subscription weights the most in it
+#[derive(Default, Debug)]
+struct Affinity {
+    weight: f64,
+    top_title: String,
+    top_weight: f64,
+}
+
+impl Affinity {
+    fn add(&mut self, title: &str, weight: f64) {
+        self.weight += weight;
+        if weight > self.top_weight {
+            self.top_weight = weight;
+            self.top_title = title.to_string();
+        }
+    }
+}
+
+/// What the user reads, built from the subscriptions only
+#[derive(Default, Debug)]
+struct ReadingProfile {
+    genres: HashMap<Genre, Affinity>,
+    creators: HashMap<String, Affinity>,
+    subscribed: HashSet<WebtoonId>,
+    /// the listings are fetched from the site edition of the user
+    language: Language,
+}
+
+impl ReadingProfile {
+    fn build(app: &tauri::AppHandle, user_data: &UserData) -> Result<Self, String> {
+        let db = app.state::<Database>();
+        let now = SystemTime::now();
+
+        let mut profile = Self {
+            language: user_data.language,
+            ..Default::default()
+        };
+        for uwt in user_data.webtoons.values() {
+            profile.subscribed.insert(uwt.id);
+
+            let weight = Self::webtoon_weight(uwt, now);
+            let wt_info = db.get_series_info(uwt.id).ok().flatten();
+
+            let creators = match &wt_info {
+                Some(wt) if !wt.creators.is_empty() => wt.creators.clone(),
+                _ => vec![uwt.creator.clone()],
+            };
+            for creator in creators.iter().filter(|c| !c.trim().is_empty()) {
+                profile
+                    .creators
+                    .entry(normalize_creator(creator))
+                    .or_default()
+                    .add(&uwt.title, weight);
+            }
+            for genre in wt_info.map(|wt| wt.genres).unwrap_or_default() {
+                profile
+                    .genres
+                    .entry(genre)
+                    .or_default()
+                    .add(&uwt.title, weight);
+            }
+        }
+
+        Ok(profile)
+    }
+
+    /// the more episodes read and the more recently, the more a subscription counts
+    fn webtoon_weight(uwt: &UserWebtoon, now: SystemTime) -> f64 {
+        let read_count = uwt
+            .episode_progress
+            .values()
+            .filter(|progress| progress.is_read())
+            .count();
+        let Some(last_seen) = uwt.last_seen else {
+            return NEVER_READ_WEIGHT;
+        };
+
+        let idle = now.duration_since(last_seen).unwrap_or_default();
+        let recency = 0.5_f64.powf(idle.as_secs_f64() / RECENCY_HALF_LIFE.as_secs_f64());
+        (1.0 + (read_count as f64).ln_1p()) * recency
+    }
+
+    /// genres sorted by affinity, most read first
+    fn top_genres(&self, n: usize) -> Vec<Genre> {
+        let mut genres = self.genres.iter().collect::<Vec<_>>();
+        genres.sort_by(|(ga, a), (gb, b)| b.weight.total_cmp(&a.weight).then(ga.cmp(gb)));
+        genres
+            .into_iter()
+            .take(n)
+            .map(|(genre, _)| genre.clone())
+            .collect()
+    }
+
+    fn max_weight<K>(affinities: &HashMap<K, Affinity>) -> f64 {
+        affinities
+            .values()
+            .map(|a| a.weight)
+            .fold(0.0, f64::max)
+            .max(f64::EPSILON)
+    }
+
+    /// score a candidate, `listing_rank` being its position in the listing it was found in
+    fn score(&self, wt: &WebtoonSearchInfo, listing_rank: usize) -> PersonalRecommendation {
+        let mut score = POPULARITY_SCORE / (1.0 + listing_rank as f64).sqrt();
+        // the strongest link to what the user reads is used as explanation
+        let mut best_link = None::<(f64, &Affinity)>;
+
+        if let Some(affinity) = wt.genre.as_ref().and_then(|g| self.genres.get(g)) {
+            let genre_score = GENRE_SCORE * affinity.weight / Self::max_weight(&self.genres);
+            score += genre_score;
+            best_link = Some((genre_score, affinity));
+        }
+        if let Some(affinity) = wt
+            .creator
+            .as_ref()
+            .and_then(|c| self.creators.get(&normalize_creator(c)))
+        {
+            let creator_score =
+                CREATOR_SCORE * affinity.weight / Self::max_weight(&self.creators);
+            score += creator_score;
+            if !matches!(best_link, Some((s, _)) if s >= creator_score) {
+                best_link = Some((creator_score, affinity));
+            }
+        }
+
+        let reason = match best_link {
+            Some((_, affinity)) => format!("Because you read {}", affinity.top_title),
+            None => "Popular right now".to_string(),
+        };
+
+        PersonalRecommendation {
+            webtoon: wt.clone(),
+            score,
+            reason,
+        }
+    }
+}
+
+fn normalize_creator(creator: &str) -> String {
+    creator.trim().to_lowercase()
+}
+
+/// gather candidates from the originals, canvas and the profile's favourite genres listings
+///
+/// each candidate comes with its best position among the listings it appears in
+async fn fetch_candidates(
+    profile: &ReadingProfile,
+) -> Result<Vec<(WebtoonSearchInfo, usize)>, String> {
+    let top_genres = profile.top_genres(CANDIDATE_GENRES);
+    let (originals, canvas, genres_webtoons) = futures::future::join3(
+        fetch_original(profile.language),
+        fetch_canvas(1, profile.language),
+        futures::future::join_all(top_genres.iter().flat_map(|genre| {
+            [WtType::Original, WtType::Canvas].map(|wt_type| {
+                fetch_genre(
+                    genre,
+                    wt_type,
+                    GenreSortOrder::Popularity,
+                    1,
+                    profile.language,
+                )
+            })
+        })),
+    )
+    .await;
+
+    let mut listings = vec![originals?, canvas?];
+    // a genre listing failing shouldn't prevent recommendations
+    listings.extend(
+        genres_webtoons
+            .into_iter()
+            .filter_map(|res| res.ok().map(|page| page.webtoons)),
+    );
+
+    let mut candidates = HashMap::<WebtoonId, (WebtoonSearchInfo, usize)>::new();
+    for listing in listings {
+        for (rank, wt) in listing.into_iter().enumerate() {
+            if profile.subscribed.contains(&wt.id) {
+                continue;
+            }
+            candidates
+                .entry(wt.id)
+                .and_modify(|(known, best_rank)| {
+                    *best_rank = rank.min(*best_rank);
+                    // canvas listings don't always tell the genre
+                    if known.genre.is_none() {
+                        known.genre = wt.genre.clone();
+                    }
+                })
+                .or_insert((wt, rank));
+        }
+    }
+
+    Ok(candidates.into_values().collect())
+}
+
+/* Commands */
+
+#[tauri::command]
+/// webtoons ranked against the user's subscriptions and reading history, best match first
+///
+/// everything is computed on-device from the user store and the public listings
+pub async fn get_personal_recommandations(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    app: tauri::AppHandle,
+    limit: usize,
+) -> Result<Vec<PersonalRecommendation>, String> {
+    let profile = {
+        let user_data = user_state.lock().await;
+        ReadingProfile::build(&app, &user_data)?
+    };
+
+    let mut recommendations = fetch_candidates(&profile)
+        .await?
+        .iter()
+        .map(|(wt, rank)| profile.score(wt, *rank))
+        .collect::<Vec<_>>();
+    recommendations.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.webtoon.title.cmp(&b.webtoon.title))
+    });
+    recommendations.truncate(limit);
+
+    let cache_thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
+    let new_thumb_path = download_images(
+        &cache_thumb_path,
+        recommendations
+            .iter()
+            .map(|rec| rec.webtoon.thumbnail.clone())
+            .collect(),
+        "personal_recommandations".to_string(),
+        |_| {},
+    )
+    .await?;
+    for (rec, new_path) in recommendations.iter_mut().zip(new_thumb_path) {
+        rec.webtoon.thumbnail = new_path
+    }
+
+    Ok(recommendations)
+}
@@ -0,0 +1,252 @@
+//! Reading statistics
+//!
+//! Computed on demand from the reading history, the subscriptions and the series cached in the database:
+//! nothing is stored, clearing the history resets them. The backend doesn't know the user's timezone, so
+//! the frontend sends its UTC offset to split the history into days.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use webtoon_sdk::{Genre, WebtoonId};
+
+use crate::{db::Database, history::HistoryEntry, store::UserData};
+
+const DAY: i64 = 86400;
+/// number of days in `ReadingStats.days`, today included
+const STATS_DAYS: i64 = 30;
+/// number of weeks in `ReadingStats.weeks`, this week included
+const STATS_WEEKS: i64 = 12;
+/// max number of genres and creators ranked
+const TOP_LEN: usize = 5;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PeriodStats {
+    /// unix timestamp in seconds of the local midnight starting the period
+    pub start: i64,
+    /// episodes finished during the period
+    pub episodes: usize,
+    /// seconds spent reading during the period
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GenreStats {
+    pub genre: Genre,
+    pub episodes: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreatorStats {
+    pub creator: String,
+    pub episodes: usize,
+}
+
+/// Read episodes of a subscription, out of the episodes cached in the database
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SeriesCompletion {
+    pub id: WebtoonId,
+    pub title: String,
+    pub read: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReadingStats {
+    /// oldest first
+    pub days: Vec<PeriodStats>,
+    /// starting on mondays, oldest first
+    pub weeks: Vec<PeriodStats>,
+    pub total_episodes: usize,
+    pub total_time: u64,
+    /// consecutive days with a finished episode, up to today or yesterday as today isn't over
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    /// by finished episodes, most read first
+    pub genres: Vec<GenreStats>,
+    pub creators: Vec<CreatorStats>,
+    /// most completed first
+    pub completion: Vec<SeriesCompletion>,
+}
+
+/// days since 1970-01-01 in local time
+fn local_day(unix_secs: u64, utc_offset: i64) -> i64 {
+    (unix_secs as i64 + utc_offset).div_euclid(DAY)
+}
+
+fn day_start(day: i64, utc_offset: i64) -> i64 {
+    day * DAY - utc_offset
+}
+
+/// monday of the week of `day`
+fn week_start(day: i64) -> i64 {
+    // 1970-01-01 was a thursday
+    day - (day + 3).rem_euclid(7)
+}
+
+/// returns the current and the longest streaks
+fn streaks(read_days: &BTreeSet<i64>, today: i64) -> (usize, usize) {
+    let mut longest = 0;
+    let mut streak = 0;
+    let mut previous = None;
+    for &day in read_days {
+        streak = match previous {
+            Some(previous) if previous + 1 == day => streak + 1,
+            _ => 1,
+        };
+        longest = longest.max(streak);
+        previous = Some(day);
+    }
+
+    let last_day = match read_days.contains(&today) {
+        true => today,
+        false => today - 1,
+    };
+    let current = (0..)
+        .take_while(|&i| read_days.contains(&(last_day - i)))
+        .count();
+    (current, longest)
+}
+
+/// the `TOP_LEN` most read, ties sorted by key
+fn top<K: Ord>(counts: HashMap<K, usize>) -> Vec<(K, usize)> {
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|(a_key, a), (b_key, b)| b.cmp(a).then(a_key.cmp(b_key)));
+    counts.truncate(TOP_LEN);
+    counts
+}
+
+fn reading_stats(
+    history: &[HistoryEntry],
+    user_data: &UserData,
+    db: &Database,
+    utc_offset: i64,
+    now: u64,
+) -> Result<ReadingStats, String> {
+    // (finished episodes, time spent) by local day
+    let mut by_day = HashMap::<i64, (usize, u64)>::new();
+    let mut finished_by_series = HashMap::<WebtoonId, usize>::new();
+    for entry in history {
+        by_day
+            .entry(local_day(entry.opened_at, utc_offset))
+            .or_default()
+            .1 += entry.duration;
+        if let Some(finished_at) = entry.finished_at {
+            by_day
+                .entry(local_day(finished_at, utc_offset))
+                .or_default()
+                .0 += 1;
+            *finished_by_series.entry(entry.wt_id).or_default() += 1;
+        }
+    }
+
+    let period = |first_day: i64, len: i64| {
+        let (episodes, time) = (first_day..first_day + len)
+            .filter_map(|day| by_day.get(&day))
+            .fold((0, 0), |(episodes, time), (ep_count, ep_time)| {
+                (episodes + ep_count, time + ep_time)
+            });
+        PeriodStats {
+            start: day_start(first_day, utc_offset),
+            episodes,
+            time,
+        }
+    };
+    let today = local_day(now, utc_offset);
+    let days = (0..STATS_DAYS)
+        .rev()
+        .map(|i| period(today - i, 1))
+        .collect();
+    let weeks = (0..STATS_WEEKS)
+        .rev()
+        .map(|i| period(week_start(today) - 7 * i, 7))
+        .collect();
+
+    let read_days = by_day
+        .iter()
+        .filter(|(_, (episodes, _))| *episodes > 0)
+        .map(|(day, _)| *day)
+        .collect::<BTreeSet<_>>();
+    let (current_streak, longest_streak) = streaks(&read_days, today);
+
+    // series deleted since then aren't in the database anymore, their genres and creators are unknown
+    let mut genres = HashMap::<Genre, usize>::new();
+    let mut creators = HashMap::<String, usize>::new();
+    for (id, finished) in &finished_by_series {
+        let Some(webtoon) = db.get_series_info(*id)? else {
+            continue;
+        };
+        for genre in webtoon.genres {
+            *genres.entry(genre).or_default() += finished;
+        }
+        for creator in webtoon.creators {
+            *creators.entry(creator).or_default() += finished;
+        }
+    }
+
+    let mut completion = Vec::new();
+    for uwt in user_data.webtoons.values() {
+        let total = db
+            .get_series(uwt.id)?
+            .and_then(|wt| wt.episodes)
+            .map(|episodes| episodes.len())
+            .unwrap_or_default();
+        let read = uwt
+            .episode_progress
+            .values()
+            .filter(|progress| progress.is_read())
+            .count();
+        completion.push(SeriesCompletion {
+            id: uwt.id,
+            title: uwt.title.clone(),
+            read: read.min(total),
+            total,
+        });
+    }
+    // compares read / total without dividing by an empty series
+    completion.sort_by(|a, b| {
+        (b.read * a.total.max(1))
+            .cmp(&(a.read * b.total.max(1)))
+            .then(a.title.cmp(&b.title))
+    });
+
+    Ok(ReadingStats {
+        days,
+        weeks,
+        total_episodes: finished_by_series.values().sum(),
+        total_time: history.iter().map(|entry| entry.duration).sum(),
+        current_streak,
+        longest_streak,
+        genres: top(genres)
+            .into_iter()
+            .map(|(genre, episodes)| GenreStats { genre, episodes })
+            .collect(),
+        creators: top(creators)
+            .into_iter()
+            .map(|(creator, episodes)| CreatorStats { creator, episodes })
+            .collect(),
+        completion,
+    })
+}
+
+/* Commands */
+
+#[tauri::command(rename_all = "snake_case")]
+/// `utc_offset` is the number of minutes to add to UTC to get the user's local time
+pub async fn get_reading_stats(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    utc_offset: i64,
+) -> Result<ReadingStats, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_secs();
+    let history = db.full_history()?;
+
+    let user_data = user_state.lock().await;
+    reading_stats(&history, &user_data, &db, utc_offset * 60, now)
+}
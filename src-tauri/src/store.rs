@@ -1,11 +1,16 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, ops::Deref, time::SystemTime};
-use tauri_plugin_store::StoreExt;
+use tauri_plugin_store::{Store, StoreExt};
 use tokio::sync::Mutex;
-use webtoon::platform::webtoons::Language;
-use webtoon_sdk::{webtoon::WebtoonInfo, WebtoonId};
+use webtoon_sdk::{episodes::Episodes, webtoon::WebtoonInfo, Language, WebtoonId};
 
-use crate::constants::{USER_LANG_KEY, USER_STORE, USER_WEBTOONS_KEY, WEBTOONS_STORE};
+use crate::{
+    bookmarks::Bookmark,
+    constants::{
+        USER_LANG_KEY, USER_MUTE_RULES_KEY, USER_READER_SETTINGS_KEY, USER_SHELVES_KEY, USER_STORE,
+    },
+    db::Database,
+};
 
 /* TYPE DEF */
 
@@ -16,19 +21,124 @@ pub struct UserWebtoon {
     pub thumbnail: String,
     pub creator: String,
     pub last_seen: Option<SystemTime>,
-    pub episode_seen: HashMap<usize, bool>,
+    pub episode_progress: HashMap<usize, ReadProgress>,
+    /// one of `UserData.shelves`, on no shelf when `None`
+    #[serde(default)]
+    pub shelf: Option<String>,
+    /// free-form, in the order given by the user
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
-pub type UserWebtoons = HashMap<usize, UserWebtoon>;
+/// keyed by `WebtoonId::store_key`
+pub type UserWebtoons = HashMap<String, UserWebtoon>;
+
+/// How far the user got in an episode
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReadProgress {
+    /// from 0 to 100, an episode at 100 is read
+    pub percent: u8,
+    /// index of the panel at the top of the reader
+    pub panel: usize,
+    /// scrolled fraction of that panel, from 0 to 1
+    pub offset: f64,
+}
+
+/// Reading progress of a subscription, see `get_library_overview`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LibraryEntry {
+    pub id: WebtoonId,
+    pub unread_count: usize,
+    /// first unread episode after the last read one, or the first unread one
+    pub next_unread: Option<usize>,
+    /// as displayed by the source, only meant to be shown
+    pub latest_episode_date: Option<String>,
+    /// `latest_episode_date` as a unix timestamp in seconds, `None` when its format isn't known
+    pub latest_episode_at: Option<u64>,
+}
+
+/// keyed by `WebtoonId::store_key`
+pub type LibraryOverview = HashMap<String, LibraryEntry>;
+
+/// Episodes targeted by a bulk change of read state, only the episodes cached in the database are changed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum EpisodeSelection {
+    /// both ends included
+    Range {
+        from: usize,
+        to: usize,
+    },
+    Set(Vec<usize>),
+    All,
+}
+
+/// Comments matching one of these rules are hidden before being sent to the frontend
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+pub struct MuteRules {
+    /// matched case-insensitively anywhere in the comment
+    pub words: Vec<String>,
+    pub regexes: Vec<String>,
+    /// matched case-insensitively against the poster name
+    pub posters: Vec<String>,
+    pub hide_spoilers: bool,
+}
+
+/// How the episode reader behaves
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct ReaderSettings {
+    /// mark the episode as read once its end is reached, instead of only when going to the next one
+    pub auto_mark_read: bool,
+    /// percentage of the last panel to scroll past for the end to be reached
+    pub end_threshold: u8,
+}
+
+/// Names of the user's shelves, in the order of the library tabs
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct Shelves(pub Vec<String>);
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct UserData {
     pub language: Language,
     pub webtoons: UserWebtoons,
+    #[serde(default)]
+    pub mute_rules: MuteRules,
+    #[serde(default)]
+    pub reader_settings: ReaderSettings,
+    /// in creation order
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    pub shelves: Shelves,
 }
 
 /* IMPLEMENTATION */
 
+impl ReaderSettings {
+    /// below that, the last panel is barely on screen
+    const MIN_END_THRESHOLD: u8 = 10;
+}
+
+impl Default for ReaderSettings {
+    fn default() -> Self {
+        Self {
+            auto_mark_read: true,
+            end_threshold: 80,
+        }
+    }
+}
+
+impl Default for Shelves {
+    fn default() -> Self {
+        Self(
+            ["Reading", "On hold", "Plan to read", "Completed", "Dropped"]
+                .map(String::from)
+                .to_vec(),
+        )
+    }
+}
+
 impl From<WebtoonInfo> for UserWebtoon {
     fn from(
         WebtoonInfo {
@@ -45,14 +155,160 @@ impl From<WebtoonInfo> for UserWebtoon {
             thumbnail,
             creator: creators.first().cloned().unwrap_or_default(),
             last_seen: None,
-            episode_seen: HashMap::default(),
+            episode_progress: HashMap::default(),
+            shelf: None,
+            tags: vec![],
         }
     }
 }
 
+impl ReadProgress {
+    pub const READ: Self = Self {
+        percent: 100,
+        panel: 0,
+        offset: 0.0,
+    };
+
+    pub fn is_read(&self) -> bool {
+        self.percent >= 100
+    }
+}
+
+impl UserWebtoon {
+    pub fn is_read(&self, ep_num: usize) -> bool {
+        self.episode_progress
+            .get(&ep_num)
+            .is_some_and(ReadProgress::is_read)
+    }
+
+    /// `episodes` is the cached episodes list of this webtoon
+    fn library_entry(&self, episodes: &Episodes) -> LibraryEntry {
+        let unread = episodes
+            .keys()
+            .copied()
+            .filter(|&number| !self.is_read(number))
+            .collect::<Vec<_>>();
+        let last_read = episodes
+            .keys()
+            .copied()
+            .rev()
+            .find(|&number| self.is_read(number));
+        let latest = episodes.values().next_back();
+
+        LibraryEntry {
+            id: self.id,
+            unread_count: unread.len(),
+            next_unread: last_read
+                .and_then(|last_read| unread.iter().copied().find(|&number| number > last_read))
+                .or(unread.first().copied()),
+            latest_episode_date: latest.map(|ep| ep.posted_at.clone()),
+            latest_episode_at: latest.and_then(|ep| ep.posted_timestamp()),
+        }
+    }
+}
+
+impl EpisodeSelection {
+    /// the selected numbers among `episodes`
+    fn resolve(&self, episodes: &Episodes) -> Vec<usize> {
+        match self {
+            // a reversed range selects the same episodes, `BTreeMap::range` would panic
+            EpisodeSelection::Range { from, to } => episodes
+                .range(*from.min(to)..=*from.max(to))
+                .map(|(&number, _)| number)
+                .collect(),
+            EpisodeSelection::Set(numbers) => numbers
+                .iter()
+                .copied()
+                .filter(|number| episodes.contains_key(number))
+                .collect(),
+            EpisodeSelection::All => episodes.keys().copied().collect(),
+        }
+    }
+}
+
+/// marks the selected episodes of a subscription as read or unread, unread episodes lose their position
+///
+/// returns the whole progress of the subscription
+async fn set_episodes_read(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    wt_id: WebtoonId,
+    selection: EpisodeSelection,
+    read: bool,
+) -> Result<HashMap<usize, ReadProgress>, String> {
+    let episodes = db
+        .get_series(wt_id)?
+        .and_then(|wt| wt.episodes)
+        .unwrap_or_default();
+    let ep_nums = selection.resolve(&episodes);
+
+    let mut user_data = user_state.lock().await;
+    let user_webtoon = user_data
+        .webtoons
+        .get_mut(&wt_id.store_key())
+        .ok_or("Only the subscribed webtoons keep their read state")?;
+    for &ep_num in &ep_nums {
+        match read {
+            true => user_webtoon
+                .episode_progress
+                .insert(ep_num, ReadProgress::READ),
+            false => user_webtoon.episode_progress.remove(&ep_num),
+        };
+    }
+    if read && !ep_nums.is_empty() {
+        user_webtoon.last_seen = Some(SystemTime::now());
+    }
+
+    db.save_episodes_progress(user_webtoon, &ep_nums)?;
+    Ok(user_webtoon.episode_progress.clone())
+}
+
+/// trimmed, without the empty and the duplicated ones (ignoring case), in the given order
+fn normalize_labels(labels: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = vec![];
+    for label in labels {
+        let label = label.trim();
+        if !label.is_empty()
+            && !normalized
+                .iter()
+                .any(|other| other.to_lowercase() == label.to_lowercase())
+        {
+            normalized.push(label.to_string());
+        }
+    }
+    normalized
+}
+
+/// reads `key` from a store, a missing key gives the default value but an unreadable one is an error
+pub fn load_key<T: serde::de::DeserializeOwned + Default>(
+    store: &Store<tauri::Wry>,
+    store_name: &str,
+    key: &str,
+) -> Result<T, String> {
+    match store.get(key) {
+        Some(raw_value) => serde_json::from_value::<T>(raw_value)
+            .map_err(|e| format!("Failed to read '{key}' from {store_name}: {e}")),
+        None => Ok(T::default()),
+    }
+}
+
 impl UserData {
-    pub fn new(language: Language, webtoons: UserWebtoons) -> Self {
-        Self { webtoons, language }
+    pub fn new(
+        language: Language,
+        webtoons: UserWebtoons,
+        mute_rules: MuteRules,
+        reader_settings: ReaderSettings,
+        bookmarks: Vec<Bookmark>,
+        shelves: Shelves,
+    ) -> Self {
+        Self {
+            webtoons,
+            language,
+            mute_rules,
+            reader_settings,
+            bookmarks,
+            shelves,
+        }
     }
 }
 
@@ -90,86 +346,280 @@ pub async fn change_language(
 }
 
 #[tauri::command(rename_all = "snake_case")]
-pub async fn mark_as_read(
+pub async fn set_mute_rules(
     user_state: tauri::State<'_, Mutex<UserData>>,
     app: tauri::AppHandle,
-    wt_id: WebtoonId,
-    ep_num: usize,
+    mute_rules: MuteRules,
 ) -> Result<(), String> {
+    // refuse invalid regexes now rather than silently ignoring them when filtering
+    for pattern in &mute_rules.regexes {
+        regex::Regex::new(pattern).map_err(|e| format!("Invalid regex \"{pattern}\": {e}"))?;
+    }
+
     let user_store = app
         .store(USER_STORE)
         .map_err(|_| "Failed to open user store")?;
-
     let mut user_data = user_state.lock().await;
-    user_data.webtoons.entry(wt_id.wt_id).and_modify(|wt| {
-        wt.episode_seen.insert(ep_num, true);
-        wt.last_seen = Some(SystemTime::now());
-    });
 
     user_store.set(
-        USER_WEBTOONS_KEY,
-        serde_json::to_value(&user_data.webtoons)
-            .map_err(|_| "Couldn't serialize user new webtoons")?,
+        USER_MUTE_RULES_KEY,
+        serde_json::to_value(&mute_rules).map_err(|_| "Couldn't serialize mute rules")?,
     );
+    user_data.mute_rules = mute_rules;
 
     Ok(())
 }
 
 #[tauri::command(rename_all = "snake_case")]
-pub async fn subscribe_to_webtoon(
+/// returns the settings as saved, the end threshold is kept between 10 and 100
+pub async fn set_reader_settings(
     user_state: tauri::State<'_, Mutex<UserData>>,
     app: tauri::AppHandle,
-    webtoon_id: WebtoonId,
-) -> Result<(), String> {
-    // load ressources
+    reader_settings: ReaderSettings,
+) -> Result<ReaderSettings, String> {
+    let reader_settings = ReaderSettings {
+        end_threshold: reader_settings
+            .end_threshold
+            .clamp(ReaderSettings::MIN_END_THRESHOLD, 100),
+        ..reader_settings
+    };
+
     let user_store = app
         .store(USER_STORE)
         .map_err(|_| "Failed to open user store")?;
-    let webtoons_store = app
-        .store(WEBTOONS_STORE)
-        .map_err(|_| "Failed to open wt store")?;
     let mut user_data = user_state.lock().await;
 
-    // get webtoon data from storage
-    let webtoon2sub = serde_json::from_value::<WebtoonInfo>(
-        webtoons_store
-            .get(webtoon_id.wt_id.to_string())
-            .ok_or("No webtoons info found in storage")?,
-    )
-    .map_err(|e| e.to_string())?;
-
-    // update user_state, user_store and webtoon_store accordingly
-    user_data
-        .webtoons
-        .insert(webtoon_id.wt_id, webtoon2sub.clone().into());
     user_store.set(
-        USER_WEBTOONS_KEY,
-        serde_json::to_value(&user_data.webtoons)
-            .map_err(|_| "Couldn't serialize user new webtoons")?,
+        USER_READER_SETTINGS_KEY,
+        serde_json::to_value(reader_settings).map_err(|_| "Couldn't serialize reader settings")?,
     );
+    user_data.reader_settings = reader_settings;
 
-    Ok(())
+    Ok(reader_settings)
 }
 
 #[tauri::command(rename_all = "snake_case")]
-pub async fn unsubscribe_from_webtoon(
+/// creates, removes and reorders the shelves at once, returns them as saved
+///
+/// the subscriptions on a removed shelf are taken off it
+pub async fn set_shelves(
     user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
     app: tauri::AppHandle,
-    webtoon_id: WebtoonId,
-) -> Result<(), String> {
-    // load ressources
+    shelves: Vec<String>,
+) -> Result<Shelves, String> {
+    let shelves = Shelves(normalize_labels(shelves));
+
     let user_store = app
         .store(USER_STORE)
         .map_err(|_| "Failed to open user store")?;
     let mut user_data = user_state.lock().await;
 
-    // update user_state, user_store and webtoon_store accordingly
-    user_data.webtoons.remove(&webtoon_id.wt_id);
+    for user_webtoon in user_data.webtoons.values_mut() {
+        if user_webtoon
+            .shelf
+            .as_ref()
+            .is_some_and(|shelf| !shelves.0.contains(shelf))
+        {
+            user_webtoon.shelf = None;
+            db.update_subscription(user_webtoon)?;
+        }
+    }
     user_store.set(
-        USER_WEBTOONS_KEY,
-        serde_json::to_value(&user_data.webtoons)
-            .map_err(|_| "Couldn't serialize user new webtoons")?,
+        USER_SHELVES_KEY,
+        serde_json::to_value(&shelves).map_err(|_| "Couldn't serialize shelves")?,
     );
+    user_data.shelves = shelves.clone();
+
+    Ok(shelves)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+/// `None` takes the subscription off its shelf
+pub async fn set_webtoon_shelf(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    wt_id: WebtoonId,
+    shelf: Option<String>,
+) -> Result<(), String> {
+    let mut user_data = user_state.lock().await;
+    if let Some(shelf) = shelf.as_ref() {
+        if !user_data.shelves.0.contains(shelf) {
+            return Err(format!("There is no shelf named \"{shelf}\""));
+        }
+    }
+    let user_webtoon = user_data
+        .webtoons
+        .get_mut(&wt_id.store_key())
+        .ok_or("Only the subscribed webtoons can be put on a shelf")?;
+    user_webtoon.shelf = shelf;
+
+    db.update_subscription(user_webtoon)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+/// replaces the tags of the subscription, returns them as saved
+pub async fn set_webtoon_tags(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    wt_id: WebtoonId,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut user_data = user_state.lock().await;
+    let user_webtoon = user_data
+        .webtoons
+        .get_mut(&wt_id.store_key())
+        .ok_or("Only the subscribed webtoons can be tagged")?;
+    user_webtoon.tags = normalize_labels(tags);
+
+    db.update_subscription(user_webtoon)?;
+    Ok(user_webtoon.tags.clone())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn mark_as_read(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    wt_id: WebtoonId,
+    ep_num: usize,
+) -> Result<(), String> {
+    // the history is kept for every webtoon, subscribed or not
+    db.touch_history_entry(wt_id, ep_num, true)?;
+
+    let mut user_data = user_state.lock().await;
+    let Some(user_webtoon) = user_data.webtoons.get_mut(&wt_id.store_key()) else {
+        return Ok(());
+    };
+    user_webtoon
+        .episode_progress
+        .insert(ep_num, ReadProgress::READ);
+    user_webtoon.last_seen = Some(SystemTime::now());
+
+    db.save_progress(user_webtoon, ep_num)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+/// returns the whole progress of the subscription
+pub async fn mark_episodes_read(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    wt_id: WebtoonId,
+    selection: EpisodeSelection,
+) -> Result<HashMap<usize, ReadProgress>, String> {
+    set_episodes_read(user_state, db, wt_id, selection, true).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+/// returns the whole progress of the subscription
+pub async fn mark_episodes_unread(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    wt_id: WebtoonId,
+    selection: EpisodeSelection,
+) -> Result<HashMap<usize, ReadProgress>, String> {
+    set_episodes_read(user_state, db, wt_id, selection, false).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+/// saves where the reader is in the episode, only the subscriptions keep their progress
+///
+/// a saved position never marks the episode as read, see `mark_as_read`, and a read episode stays read.
+/// Returns the progress as saved, `None` if the webtoon isn't subscribed
+pub async fn save_read_progress(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    wt_id: WebtoonId,
+    ep_num: usize,
+    progress: ReadProgress,
+) -> Result<Option<ReadProgress>, String> {
+    db.touch_history_entry(wt_id, ep_num, false)?;
+
+    let mut user_data = user_state.lock().await;
+    let Some(user_webtoon) = user_data.webtoons.get_mut(&wt_id.store_key()) else {
+        return Ok(None);
+    };
+
+    let percent = match user_webtoon.is_read(ep_num) {
+        true => 100,
+        false => progress.percent.min(99),
+    };
+    let saved = ReadProgress {
+        percent,
+        panel: progress.panel,
+        offset: progress.offset.clamp(0.0, 1.0),
+    };
+    user_webtoon.episode_progress.insert(ep_num, saved);
+    user_webtoon.last_seen = Some(SystemTime::now());
+
+    db.save_progress(user_webtoon, ep_num)?;
+    Ok(Some(saved))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_read_progress(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    wt_id: WebtoonId,
+    ep_num: usize,
+) -> Result<Option<ReadProgress>, String> {
+    let user_data = user_state.lock().await;
+    Ok(user_data
+        .webtoons
+        .get(&wt_id.store_key())
+        .and_then(|uwt| uwt.episode_progress.get(&ep_num).copied()))
+}
+
+#[tauri::command]
+/// reading progress of every subscription, from the episodes cached in the database
+///
+/// a subscription whose episodes were never fetched has nothing unread
+pub async fn get_library_overview(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+) -> Result<LibraryOverview, String> {
+    let user_data = user_state.lock().await;
+
+    let mut overview = LibraryOverview::new();
+    for (key, uwt) in &user_data.webtoons {
+        let episodes = db
+            .get_series(uwt.id)?
+            .and_then(|wt| wt.episodes)
+            .unwrap_or_default();
+        overview.insert(key.clone(), uwt.library_entry(&episodes));
+    }
+    Ok(overview)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn subscribe_to_webtoon(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    webtoon_id: WebtoonId,
+) -> Result<(), String> {
+    // get webtoon data from storage
+    let webtoon2sub: UserWebtoon = db
+        .get_series_info(webtoon_id)?
+        .ok_or("No webtoons info found in storage")?
+        .into();
+
+    // update the database first, user_state only mirrors it
+    db.subscribe(&webtoon2sub)?;
+    let mut user_data = user_state.lock().await;
+    user_data
+        .webtoons
+        .insert(webtoon_id.store_key(), webtoon2sub);
+
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn unsubscribe_from_webtoon(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    db: tauri::State<'_, Database>,
+    webtoon_id: WebtoonId,
+) -> Result<(), String> {
+    db.unsubscribe(webtoon_id)?;
+    let mut user_data = user_state.lock().await;
+    user_data.webtoons.remove(&webtoon_id.store_key());
 
     Ok(())
 }
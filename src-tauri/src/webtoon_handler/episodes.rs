@@ -1,115 +1,362 @@
-use std::time::{Duration, SystemTime};
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime},
+};
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Manager};
-use tauri_plugin_store::StoreExt;
-use webtoon::platform::webtoons::{self};
-use webtoon_sdk::{episodes::EpisodeData, webtoon::WebtoonInfo, DownloadState, WebtoonId};
+use tokio::sync::Mutex;
+use webtoon_sdk::{
+    comments::{fetch_posts, fetch_replies, fetch_top_posts, Post, PostBatch, API_PAGE_SIZE},
+    episodes::EpisodeData,
+    webtoon::WebtoonInfo,
+    DownloadState, WebtoonId,
+};
 
-use crate::{constants::WEBTOONS_STORE, webtoon_handler::FromWtType};
+use crate::{
+    db::Database,
+    store::{MuteRules, UserData},
+};
+
+/// number of comments sent to the frontend at once, one api page per page of comments
+const POSTS_PER_PAGE: usize = API_PAGE_SIZE;
+/// api pages fetched for one page of comments at most, when the mute rules hide most of them
+const MAX_API_PAGES_PER_PAGE: usize = 5;
+/// how long cached comments are considered fresh
+const POSTS_TTL: Duration = Duration::from_secs(3600);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PostSort {
+    /// the comments pinned by the site first, then the most upvoted of the loaded ones
+    #[default]
+    Top,
+    Newest,
+}
 
-/// for the app simplicity sake, no replies will be fetch in this app
 #[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct Post {
-    pub wt_id: WebtoonId,
-    pub ep_num: usize,
-
-    pub id: String,
-    pub content: String,
-    pub is_spoiler: bool,
-    pub upvotes: u32,
-    pub downvotes: u32,
-    pub posted_at: u64,
-    pub poster_name: String,
+pub struct PostPage {
+    pub posts: Vec<Post>,
+    /// starts at `1`
+    pub page: usize,
+    pub has_more: bool,
+    /// the comments come from the cache and couldn't be refreshed (yet)
+    pub is_stale: bool,
+    /// number of the loaded comments hidden by the user's mute rules
+    pub hidden_count: usize,
+}
+
+/// The user's `MuteRules`, ready to be matched against comments
+struct PostMuter {
+    words: Vec<String>,
+    regexes: Vec<Regex>,
+    posters: HashSet<String>,
+    hide_spoilers: bool,
 }
 
-pub trait PostExtension {
-    async fn fetch_posts(wt_id: WebtoonId, ep_num: usize) -> Result<Vec<Post>, String>;
+/// The comments of an episode loaded so far, as saved in the comments cache of the database
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedPosts {
+    /// newest first, one api page after another
+    posts: Vec<Post>,
+    /// cursor of the next api page, `None` once every comment was loaded
+    #[serde(default)]
+    next_cursor: Option<String>,
+    /// pinned by the site
+    #[serde(default)]
+    top: Vec<Post>,
+    expired_at: SystemTime,
 }
 
-impl PostExtension for EpisodeData {
-    async fn fetch_posts(wt_id: WebtoonId, ep_num: usize) -> Result<Vec<Post>, String> {
-        let wtclient = webtoons::Client::new();
-
-        let webtoon = wtclient
-            .webtoon(wt_id.wt_id as u32, wt_id.wt_type.to_local_type())
-            .await
-            .map_err(|err| err.to_string())?
-            .ok_or("Webtoon not found")?;
-        let episode = webtoon
-            .episode(ep_num as u16)
-            .await
-            .map_err(|err| err.to_string())?
-            .ok_or("Episode not found")?;
-
-        let top_posts: Vec<Post> = episode
-            .posts()
-            .await
-            .map_err(|err| err.to_string())?
-            .filter(|p| p.is_top() && p.is_comment())
-            .map(|p| Post {
-                wt_id,
-                ep_num,
-                id: p.id().to_string(),
-                content: p.body().contents().to_string(),
-                is_spoiler: p.body().is_spoiler(),
-                upvotes: p.upvotes(),
-                downvotes: p.downvotes(),
-                posted_at: p.posted() as u64,
-                poster_name: p.poster().username().to_string(),
-            })
+/// Payload of the `posts_refreshed` event, sent once the comments of an episode were revalidated
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct PostsRefreshed {
+    wt_id: WebtoonId,
+    ep_num: usize,
+}
+
+impl PostPage {
+    /// keeps the requested page of the sorted comments, `more_upstream` tells if some weren't loaded yet
+    fn new(
+        posts: Vec<Post>,
+        page: usize,
+        more_upstream: bool,
+        is_stale: bool,
+        hidden_count: usize,
+    ) -> Self {
+        let has_more = more_upstream || posts.len() > page * POSTS_PER_PAGE;
+        let posts = posts
+            .into_iter()
+            .skip((page - 1) * POSTS_PER_PAGE)
+            .take(POSTS_PER_PAGE)
             .collect();
 
-        Ok(top_posts)
+        Self {
+            posts,
+            page,
+            has_more,
+            is_stale,
+            hidden_count,
+        }
+    }
+}
+
+impl PostMuter {
+    fn new(rules: &MuteRules) -> Result<Self, String> {
+        Ok(Self {
+            words: rules
+                .words
+                .iter()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty())
+                .collect(),
+            regexes: rules
+                .regexes
+                .iter()
+                .map(|r| Regex::new(r).map_err(|e| e.to_string()))
+                .collect::<Result<_, _>>()?,
+            posters: rules
+                .posters
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .collect(),
+            hide_spoilers: rules.hide_spoilers,
+        })
+    }
+
+    fn is_muted(&self, post: &Post) -> bool {
+        if self.hide_spoilers && post.is_spoiler {
+            return true;
+        }
+        if self.posters.contains(&post.poster_name.trim().to_lowercase()) {
+            return true;
+        }
+
+        let content = post.content.to_lowercase();
+        self.words.iter().any(|w| content.contains(w))
+            || self.regexes.iter().any(|r| r.is_match(&post.content))
+    }
+
+    /// remove the muted comments, returns how many were removed
+    fn apply(&self, posts: &mut Vec<Post>) -> usize {
+        let total = posts.len();
+        posts.retain(|p| !self.is_muted(p));
+        total - posts.len()
+    }
+}
+
+impl CachedPosts {
+    fn key(wt_id: WebtoonId, ep_num: usize) -> String {
+        format!("{}_{ep_num}", wt_id.store_key())
+    }
+
+    fn load(
+        app: &tauri::AppHandle,
+        wt_id: WebtoonId,
+        ep_num: usize,
+    ) -> Result<Option<Self>, String> {
+        app.state::<Database>()
+            .get_cached_comments::<Self>(&Self::key(wt_id, ep_num))
+    }
+
+    /// fetch the first page of comments from the site and replace the cached ones
+    async fn refresh(
+        app: &tauri::AppHandle,
+        wt_id: WebtoonId,
+        ep_num: usize,
+    ) -> Result<Self, String> {
+        let PostBatch { posts, next_cursor } = fetch_posts(wt_id, ep_num, None).await?;
+        // not being able to get the pinned comments isn't fatal
+        let top = fetch_top_posts(wt_id, ep_num).await.unwrap_or_default();
+        let cached = Self {
+            posts,
+            next_cursor,
+            top,
+            expired_at: SystemTime::now()
+                .checked_add(POSTS_TTL)
+                .ok_or("are we near 2038?")?,
+        };
+
+        app.state::<Database>()
+            .set_cached_comments(&Self::key(wt_id, ep_num), &cached)?;
+
+        Ok(cached)
+    }
+
+    /// fetch the next api page, if any, and add it to the cached comments
+    async fn load_more(
+        &mut self,
+        app: &tauri::AppHandle,
+        wt_id: WebtoonId,
+        ep_num: usize,
+    ) -> Result<(), String> {
+        let Some(cursor) = self.next_cursor.as_deref() else {
+            return Ok(());
+        };
+        let PostBatch { posts, next_cursor } = fetch_posts(wt_id, ep_num, Some(cursor)).await?;
+        self.posts.extend(posts);
+        self.next_cursor = next_cursor;
+
+        app.state::<Database>()
+            .set_cached_comments(&Self::key(wt_id, ep_num), self)
+    }
+
+    /// the loaded comments in the order of `sort`, the pinned ones are flagged as such
+    fn sorted(&self, sort: PostSort) -> Vec<Post> {
+        let is_top = |post: &Post| self.top.iter().any(|top| top.id == post.id);
+        match sort {
+            PostSort::Top => {
+                let mut posts = self
+                    .posts
+                    .iter()
+                    .filter(|&post| !is_top(post))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                // the newest first between comments with as many upvotes
+                posts.sort_by(|a, b| {
+                    b.upvotes
+                        .cmp(&a.upvotes)
+                        .then_with(|| b.posted_at.cmp(&a.posted_at))
+                });
+                self.top.iter().cloned().chain(posts).collect()
+            }
+            PostSort::Newest => self
+                .posts
+                .iter()
+                .map(|post| Post {
+                    is_top: is_top(post),
+                    ..post.clone()
+                })
+                .collect(),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expired_at <= SystemTime::now()
     }
 }
 
 /* Commands */
 
 #[tauri::command(rename_all = "snake_case")]
-pub async fn get_episode_post(wt_id: WebtoonId, ep_num: usize) -> Result<Vec<Post>, String> {
-    webtoon_sdk::episodes::EpisodeData::fetch_posts(wt_id, ep_num).await
+/// comments are served from the cache first: expired ones are still returned while they are
+/// refreshed in the background, and a `posts_refreshed` event is emitted once it's done
+///
+/// when the site can't be reached, the cached comments are returned as stale
+pub async fn get_episode_post(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    app: tauri::AppHandle,
+    wt_id: WebtoonId,
+    ep_num: usize,
+    sort: PostSort,
+    page: usize,
+    force_refresh: bool,
+) -> Result<PostPage, String> {
+    let muter = PostMuter::new(&user_state.lock().await.mute_rules)?;
+    let cached = CachedPosts::load(&app, wt_id, ep_num)?;
+
+    let (mut cached, mut is_stale) = match cached {
+        Some(cached) if !force_refresh => {
+            let is_stale = cached.is_expired();
+            // only the first page triggers a revalidation, so that the following ones stay consistent
+            if is_stale && page <= 1 {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if CachedPosts::refresh(&app, wt_id, ep_num).await.is_ok() {
+                        let _ = app.emit("posts_refreshed", PostsRefreshed { wt_id, ep_num });
+                    }
+                });
+            }
+            (cached, is_stale)
+        }
+        cached => match CachedPosts::refresh(&app, wt_id, ep_num).await {
+            Ok(fresh) => (fresh, false),
+            // offline, fallback on what we have
+            Err(e) => match cached {
+                Some(cached) => (cached, true),
+                None => return Err(e),
+            },
+        },
+    };
+
+    // only the api pages needed to fill the requested page are fetched
+    let page = page.max(1);
+    for _ in 0..MAX_API_PAGES_PER_PAGE {
+        let shown_count = cached
+            .sorted(sort)
+            .iter()
+            .filter(|post| !muter.is_muted(post))
+            .count();
+        if cached.next_cursor.is_none() || shown_count >= page * POSTS_PER_PAGE {
+            break;
+        }
+        if cached.load_more(&app, wt_id, ep_num).await.is_err() {
+            is_stale = true;
+            break;
+        }
+    }
+
+    let mut posts = cached.sorted(sort);
+    let hidden_count = muter.apply(&mut posts);
+    Ok(PostPage::new(
+        posts,
+        page,
+        cached.next_cursor.is_some(),
+        is_stale,
+        hidden_count,
+    ))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+/// one page of replies, `cursor` is the `next_cursor` of the previous one
+pub async fn get_post_replies(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    wt_id: WebtoonId,
+    ep_num: usize,
+    post_id: String,
+    cursor: Option<String>,
+) -> Result<PostBatch, String> {
+    let muter = PostMuter::new(&user_state.lock().await.mute_rules)?;
+
+    let mut replies = fetch_replies(wt_id, ep_num, &post_id, cursor.as_deref()).await?;
+    muter.apply(&mut replies.posts);
+    Ok(replies)
 }
 
 #[tauri::command]
 pub async fn force_refresh_episodes(
     app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
     id: WebtoonId,
 ) -> Result<WebtoonInfo, String> {
-    let webtoons_store = app
-        .store(WEBTOONS_STORE)
-        .map_err(|_| "Failed to open wt store")?;
-
-    let mut updated_wt = match webtoons_store
-        .get(id.wt_id.to_string())
-        .map(serde_json::from_value::<WebtoonInfo>)
-    {
-        Some(Ok(mut wt)) => {
+    let _series_lock = db.lock_series(id).await?;
+    let mut updated_wt = match db.get_series(id) {
+        Ok(Some(mut wt)) => {
             let thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
-            wt.update_episodes(&thumb_path, |_| {}).await?;
+            // the whole list is fetched again so that removed or renumbered episodes are caught
+            wt.fetch_episodes(&thumb_path, |_| {}).await?;
             wt
         }
-        Some(Err(_)) | None => return Err("webtoon not found".to_string()),
+        Err(e) => return Err(format!("Stored webtoon is unreadable: {e}")),
+        Ok(None) => return Err("webtoon not found".to_string()),
     };
 
     updated_wt.refresh_eps_at = SystemTime::now()
         .checked_add(Duration::from_secs(86400)) // add 1 days before refresh
         .ok_or("are we near 2038?")?;
 
-    // set updated webtoon to storage
-    webtoons_store.set(
-        id.wt_id.to_string(),
-        serde_json::to_value(&updated_wt).map_err(|_| "Couldn't serialize updated_wt")?,
-    );
+    // set updated webtoon to the database
+    db.save_series(&updated_wt)?;
     Ok(updated_wt)
 }
 
 #[tauri::command(rename_all = "snake_case")]
 pub async fn get_episode_data(
     app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
     wt_id: WebtoonId,
     ep_num: usize,
-) -> Result<(EpisodeData, bool), String> {
+) -> Result<(EpisodeData, Option<usize>), String> {
     if ep_num == 0 {
         return Err("episode number cannot be 0".to_string());
     }
@@ -118,28 +365,24 @@ pub async fn get_episode_data(
         let _ = app.emit("ep_dl_channel", news);
     };
 
-    let webtoons_store = app
-        .store(WEBTOONS_STORE)
-        .map_err(|_| "Failed to open wt store")?;
-
-    let webtoon = webtoons_store
-        .get(wt_id.wt_id.to_string())
-        .map(serde_json::from_value::<WebtoonInfo>)
-        .ok_or("No webtoon found in store")?
-        .map_err(|e| e.to_string())?;
-    let episodes = webtoon.episodes.ok_or("No episode found in store")?;
+    let webtoon = db.get_series(wt_id)?.ok_or("No webtoon found in store")?;
+    if webtoon.episodes.is_none() {
+        return Err("No episode found in store".to_string());
+    }
 
-    let episode = episodes
-        .get(ep_num - 1)
+    let episode = webtoon
+        .episode(ep_num)
         .cloned()
         .ok_or("Requested episode not found in store")?;
-    let has_next_ep = ep_num != episodes.len();
+    let next_ep = webtoon.next_episode(ep_num);
 
     let mut ep_data = episode.get_episode_data(dl_progress_cb).await?;
 
     // episodes panels are stored temporarily in cache
     let cache_dir = app.path().app_cache_dir().map_err(|e| e.to_string())?;
     ep_data.dl_panels(&cache_dir, dl_progress_cb).await?;
+    db.index_images(wt_id, &ep_data.panels)?;
+    db.open_history_entry(wt_id, &webtoon.title, ep_num, &episode.title)?;
 
-    Ok((ep_data, has_next_ep))
+    Ok((ep_data, next_ep))
 }
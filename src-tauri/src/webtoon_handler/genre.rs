@@ -0,0 +1,43 @@
+use tauri::Manager;
+use tokio::sync::Mutex;
+use webtoon_sdk::{
+    genres::{fetch_genre, GenreSortOrder},
+    image_dl::download_images,
+    search::SearchResult,
+    Genre, WtType,
+};
+
+use crate::store::UserData;
+
+/* Commands */
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_genre_webtoons(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    app: tauri::AppHandle,
+    genre: Genre,
+    wt_type: WtType,
+    sort_order: GenreSortOrder,
+    page: usize,
+) -> Result<SearchResult, String> {
+    let language = user_state.lock().await.language;
+    let mut genre_webtoons = fetch_genre(&genre, wt_type, sort_order, page, language).await?;
+
+    let cache_thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
+    let new_thumb_path = download_images(
+        &cache_thumb_path,
+        genre_webtoons
+            .webtoons
+            .iter()
+            .map(|wt| wt.thumbnail.clone())
+            .collect(),
+        format!("genre_{}", genre.slug()),
+        |_| {},
+    )
+    .await?;
+    for (wt, new_path) in genre_webtoons.webtoons.iter_mut().zip(new_thumb_path) {
+        wt.thumbnail = new_path
+    }
+
+    Ok(genre_webtoons)
+}
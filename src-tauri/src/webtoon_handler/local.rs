@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use tauri::Manager;
+use tauri_plugin_store::StoreExt;
+use webtoon_sdk::{
+    image_dl::download_images,
+    local::{library_root, list_local_series, set_library_root},
+    search::WebtoonSearchInfo,
+};
+
+use crate::constants::{LOCAL_LIBRARY_KEY, USER_STORE};
+
+/* Commands */
+
+/// series of the local library folder, empty when no folder is set
+#[tauri::command]
+pub async fn get_local_library(app: tauri::AppHandle) -> Result<Vec<WebtoonSearchInfo>, String> {
+    if library_root().is_none() {
+        return Ok(vec![]);
+    }
+
+    let mut local_series = list_local_series().await?;
+
+    // covers are copied next to the other thumbnails, the webview can't read outside of the app folders
+    let thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
+    let new_thumb_path = download_images(
+        &thumb_path,
+        local_series.iter().map(|wt| wt.thumbnail.clone()).collect(),
+        "local_library".to_string(),
+        |_| {},
+    )
+    .await?;
+    for (wt, new_path) in local_series.iter_mut().zip(new_thumb_path) {
+        wt.thumbnail = new_path
+    }
+
+    Ok(local_series)
+}
+
+/// changes the folder scanned for local series, `None` removes it
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_local_library(
+    app: tauri::AppHandle,
+    library_path: Option<String>,
+) -> Result<Vec<WebtoonSearchInfo>, String> {
+    let library_path = library_path
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from);
+    if let Some(path) = library_path.as_ref().filter(|path| !path.is_dir()) {
+        return Err(format!("'{}' is not a folder", path.to_string_lossy()));
+    }
+
+    let user_store = app
+        .store(USER_STORE)
+        .map_err(|_| "Failed to open user store")?;
+    match &library_path {
+        Some(path) => user_store.set(LOCAL_LIBRARY_KEY, path.to_string_lossy().to_string()),
+        None => {
+            user_store.delete(LOCAL_LIBRARY_KEY);
+        }
+    }
+    set_library_root(library_path);
+
+    get_local_library(app).await
+}
@@ -0,0 +1,39 @@
+use tauri::Manager;
+use tokio::sync::Mutex;
+use webtoon_sdk::{
+    image_dl::download_images,
+    ranking::{fetch_ranking, RankedWebtoon, RankingKind},
+    Genre,
+};
+
+use crate::store::UserData;
+
+/* Commands */
+
+#[tauri::command]
+pub async fn get_ranking(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    app: tauri::AppHandle,
+    kind: RankingKind,
+    genre: Option<Genre>,
+) -> Result<Vec<RankedWebtoon>, String> {
+    let language = user_state.lock().await.language;
+    let mut ranking = fetch_ranking(kind, genre.as_ref(), language).await?;
+
+    let cache_thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
+    let new_thumb_path = download_images(
+        &cache_thumb_path,
+        ranking
+            .iter()
+            .map(|rwt| rwt.webtoon.thumbnail.clone())
+            .collect(),
+        "ranking".to_string(),
+        |_| {},
+    )
+    .await?;
+    for (rwt, new_path) in ranking.iter_mut().zip(new_thumb_path) {
+        rwt.webtoon.thumbnail = new_path
+    }
+
+    Ok(ranking)
+}
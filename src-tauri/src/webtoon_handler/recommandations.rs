@@ -0,0 +1,226 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, SystemTime},
+};
+
+use nanorand::{Rng, WyRand};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+use webtoon_sdk::{
+    genres::{fetch_genre, GenreSortOrder},
+    image_dl::download_images,
+    recommandations::fetch_canvas,
+    schedule::fetch_originals_schedule,
+    search::WebtoonSearchInfo,
+    Genre, Language, WebtoonId, Weekday, WtType,
+};
+
+use crate::{
+    constants::{CACHE_STORE, RECOMMENDATIONS_KEY},
+    db::Database,
+    store::UserData,
+};
+
+/// max number of webtoons in a section
+const SECTION_LEN: usize = 20;
+/// how many of the most followed genres get their own section
+const FOLLOWED_GENRES_SECTIONS: usize = 2;
+
+/// Random seed drawn once per app launch, so that the recommendations order stays the same during a session
+pub struct SessionSeed(pub u64);
+
+impl Default for SessionSeed {
+    fn default() -> Self {
+        Self(WyRand::new().generate::<u64>())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum SectionKind {
+    OriginalsToday,
+    PopularCanvas,
+    FollowedGenre(Genre),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecommendationSection {
+    pub kind: SectionKind,
+    pub webtoons: Vec<WebtoonSearchInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedRecommendations {
+    sections: Vec<RecommendationSection>,
+    /// "Originals today" is only valid for the day it was built
+    built_for: Weekday,
+    /// the sections are fetched from the site edition of the user
+    #[serde(default)]
+    language: Language,
+    expired_at: SystemTime,
+}
+
+impl CachedRecommendations {
+    async fn build(
+        app: &tauri::AppHandle,
+        subscribed: &HashSet<WebtoonId>,
+        followed_genres: Vec<Genre>,
+        today: Weekday,
+        language: Language,
+    ) -> Result<Self, String> {
+        let (schedule, canvas, genres_webtoons) = futures::future::join3(
+            fetch_originals_schedule(language),
+            fetch_canvas(1, language),
+            futures::future::join_all(followed_genres.iter().map(|genre| {
+                fetch_genre(
+                    genre,
+                    WtType::Original,
+                    GenreSortOrder::Popularity,
+                    1,
+                    language,
+                )
+            })),
+        )
+        .await;
+
+        let mut sections = vec![
+            RecommendationSection {
+                kind: SectionKind::OriginalsToday,
+                webtoons: schedule?.releasing_on(today).to_vec(),
+            },
+            RecommendationSection {
+                kind: SectionKind::PopularCanvas,
+                webtoons: canvas?,
+            },
+        ];
+        for (genre, genre_webtoons) in followed_genres.into_iter().zip(genres_webtoons) {
+            sections.push(RecommendationSection {
+                kind: SectionKind::FollowedGenre(genre),
+                webtoons: genre_webtoons?.webtoons,
+            });
+        }
+
+        // no need to recommend what the user already reads
+        for section in sections.iter_mut() {
+            section.webtoons.retain(|wt| !subscribed.contains(&wt.id));
+            section.webtoons.truncate(SECTION_LEN);
+        }
+        sections.retain(|section| !section.webtoons.is_empty());
+
+        let cache_thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
+        let mut new_thumb_paths = download_images(
+            &cache_thumb_path,
+            sections
+                .iter()
+                .flat_map(|section| section.webtoons.iter().map(|wt| wt.thumbnail.clone()))
+                .collect(),
+            "homepage_recommandations".to_string(),
+            |_| {},
+        )
+        .await?
+        .into_iter();
+        for wt in sections
+            .iter_mut()
+            .flat_map(|section| section.webtoons.iter_mut())
+        {
+            if let Some(new_path) = new_thumb_paths.next() {
+                wt.thumbnail = new_path
+            }
+        }
+
+        Ok(Self {
+            sections,
+            built_for: today,
+            language,
+            expired_at: SystemTime::now()
+                .checked_add(Duration::from_secs(21600)) // add 6 hours before refresh
+                .ok_or("are we near 2038?")?,
+        })
+    }
+}
+
+/// genres of the user's subscriptions, most followed first
+fn followed_genres(app: &tauri::AppHandle, user_data: &UserData) -> Result<Vec<Genre>, String> {
+    let db = app.state::<Database>();
+
+    let mut genres_count = HashMap::<Genre, usize>::new();
+    for uwt in user_data.webtoons.values() {
+        if let Ok(Some(wt)) = db.get_series_info(uwt.id) {
+            for genre in wt.genres {
+                *genres_count.entry(genre).or_default() += 1;
+            }
+        }
+    }
+
+    let mut followed_genres = genres_count.into_iter().collect::<Vec<_>>();
+    followed_genres.sort_by(|(ga, ca), (gb, cb)| cb.cmp(ca).then(ga.cmp(gb)));
+    Ok(followed_genres
+        .into_iter()
+        .map(|(genre, _)| genre)
+        .take(FOLLOWED_GENRES_SECTIONS)
+        .collect())
+}
+
+/* Commands */
+
+#[tauri::command(rename_all = "snake_case")]
+/// recommendations split by sections, cached for a few hours and shuffled once per app launch
+///
+/// `today` is given by the frontend so that it matches the user's local time
+pub async fn get_homepage_recommandations(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    session_seed: tauri::State<'_, SessionSeed>,
+    app: tauri::AppHandle,
+    today: Weekday,
+    force_refresh: bool,
+) -> Result<Vec<RecommendationSection>, String> {
+    let cache_store = app
+        .store(CACHE_STORE)
+        .map_err(|_| "Failed to open cache store")?;
+    let language = user_state.lock().await.language;
+
+    let recommendations = match cache_store
+        .get(RECOMMENDATIONS_KEY)
+        .map(serde_json::from_value::<CachedRecommendations>)
+    {
+        Some(Ok(rec))
+            if !force_refresh
+                && rec.built_for == today
+                && rec.language == language
+                && rec.expired_at > SystemTime::now() =>
+        {
+            rec
+        }
+        Some(_) | None => {
+            let (subscribed, followed_genres) = {
+                let user_data = user_state.lock().await;
+                (
+                    user_data
+                        .webtoons
+                        .values()
+                        .map(|uwt| uwt.id)
+                        .collect::<HashSet<_>>(),
+                    followed_genres(&app, &user_data)?,
+                )
+            };
+
+            let rec =
+                CachedRecommendations::build(&app, &subscribed, followed_genres, today, language)
+                    .await?;
+            cache_store.set(
+                RECOMMENDATIONS_KEY,
+                serde_json::to_value(&rec).map_err(|_| "Couldn't serialize recommendations")?,
+            );
+            rec
+        }
+    };
+
+    let mut sections = recommendations.sections;
+    let mut rng = WyRand::new_seed(session_seed.0);
+    for section in sections.iter_mut() {
+        rng.shuffle(&mut section.webtoons);
+    }
+
+    Ok(sections)
+}
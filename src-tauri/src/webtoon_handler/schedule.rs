@@ -0,0 +1,71 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tokio::sync::Mutex;
+use webtoon_sdk::{
+    image_dl::download_images, schedule::fetch_originals_schedule, search::WebtoonSearchInfo,
+    Weekday,
+};
+
+use crate::store::UserData;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TodayWebtoon {
+    pub webtoon: WebtoonSearchInfo,
+    pub subscribed: bool,
+}
+
+/* Commands */
+
+#[tauri::command]
+/// originals releasing `today`, the ones the user is subscribed to first
+///
+/// `today` is given by the frontend so that it matches the user's local time
+pub async fn get_updating_today(
+    user_state: tauri::State<'_, Mutex<UserData>>,
+    app: tauri::AppHandle,
+    today: Weekday,
+) -> Result<Vec<TodayWebtoon>, String> {
+    let (language, subscribed_ids) = {
+        let user_data = user_state.lock().await;
+        (
+            user_data.language,
+            user_data
+                .webtoons
+                .values()
+                .map(|uwt| uwt.id)
+                .collect::<HashSet<_>>(),
+        )
+    };
+    let schedule = fetch_originals_schedule(language).await?;
+
+    let mut today_webtoons = schedule
+        .releasing_on(today)
+        .iter()
+        .cloned()
+        .map(|webtoon| TodayWebtoon {
+            subscribed: subscribed_ids.contains(&webtoon.id),
+            webtoon,
+        })
+        .collect::<Vec<_>>();
+    // stable sort: the site order is kept within each group
+    today_webtoons.sort_by_key(|twt| !twt.subscribed);
+
+    let cache_thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
+    let new_thumb_path = download_images(
+        &cache_thumb_path,
+        today_webtoons
+            .iter()
+            .map(|twt| twt.webtoon.thumbnail.clone())
+            .collect(),
+        "updating_today".to_string(),
+        |_| {},
+    )
+    .await?;
+    for (twt, new_path) in today_webtoons.iter_mut().zip(new_thumb_path) {
+        twt.webtoon.thumbnail = new_path
+    }
+
+    Ok(today_webtoons)
+}
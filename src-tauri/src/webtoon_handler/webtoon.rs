@@ -1,68 +1,62 @@
 use std::time::SystemTime;
 
-use async_trait::async_trait;
-use nanorand::{Rng, WyRand};
 use tauri::{Emitter, Manager};
-use tauri_plugin_store::StoreExt;
-use webtoon::platform::webtoons::Webtoon;
+use tokio::sync::Mutex;
 use webtoon_sdk::{
     image_dl::download_images,
-    recommandations::{fetch_canvas, fetch_original},
-    search::WebtoonSearchInfo,
+    local::library_root,
+    search::{SearchFilter, SearchResult},
+    source::{ContentSource, SourceId},
     webtoon::WebtoonInfo,
-    DownloadState, WebtoonId, WtType,
+    DownloadState, Genre, WebtoonId,
 };
 
-use crate::{constants::WEBTOONS_STORE, webtoon_handler::FromWtType};
-/* Implementations */
-
-#[async_trait]
-pub trait FromWebtoon {
-    async fn from_webtoon(webtoon: &Webtoon) -> Result<Self, String>
-    where
-        Self: Sized;
-}
-
-#[async_trait]
-impl FromWebtoon for WebtoonSearchInfo {
-    async fn from_webtoon(webtoon: &Webtoon) -> Result<Self, String> {
-        Ok(WebtoonSearchInfo {
-            id: WebtoonId::new(
-                webtoon.id() as usize,
-                WtType::from_wt_type(webtoon.r#type()),
-            ),
-            title: webtoon.title().await.map_err(|err| err.to_string())?,
-            thumbnail: webtoon
-                .thumbnail()
-                .await
-                .map_err(|err| err.to_string())?
-                .unwrap_or_default(),
-            creator: Some(
-                webtoon
-                    .creators()
-                    .await
-                    .map_err(|err| err.to_string())?
-                    .first()
-                    .map(|c| c.username().to_string())
-                    .unwrap_or_default(),
-            ),
-        })
-    }
-}
+use crate::{auto_refresh::NewEpisodes, db::Database, store::UserData};
 
 /* Commands */
 
 #[tauri::command]
 pub async fn search_webtoon(
+    user_state: tauri::State<'_, Mutex<UserData>>,
     app: tauri::AppHandle,
     query: &str,
-) -> Result<Vec<WebtoonSearchInfo>, String> {
-    let mut search_result = WebtoonSearchInfo::from_query(query).await?;
+    filter: SearchFilter,
+    page: usize,
+    genre: Option<Genre>,
+) -> Result<SearchResult, String> {
+    let language = user_state.lock().await.language;
+
+    // the local series come first, when a library folder is set
+    let mut sources = vec![SourceId::Webtoons];
+    if library_root().is_some() {
+        sources.insert(0, SourceId::Local);
+    }
+    let mut search_result = SearchResult {
+        webtoons: vec![],
+        page,
+        has_more: false,
+    };
+    for source in sources {
+        let result = source
+            .source()
+            .search(query, filter, page, language)
+            .await?;
+        // a source without pages, like the local one, returns everything on the first page
+        if result.page < page {
+            continue;
+        }
+        search_result.webtoons.extend(result.webtoons);
+        search_result.has_more |= result.has_more;
+    }
+    if let Some(genre) = genre {
+        search_result = search_result.filter_genre(&genre);
+    }
 
     let cache_thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
     let new_thumb_path = download_images(
         &cache_thumb_path,
         search_result
+            .webtoons
             .iter()
             .map(|wt| wt.thumbnail.clone())
             .collect(),
@@ -70,7 +64,7 @@ pub async fn search_webtoon(
         |_| {},
     )
     .await?;
-    for (wt, new_path) in search_result.iter_mut().zip(new_thumb_path) {
+    for (wt, new_path) in search_result.webtoons.iter_mut().zip(new_thumb_path) {
         wt.thumbnail = new_path
     }
 
@@ -79,69 +73,59 @@ pub async fn search_webtoon(
 
 #[tauri::command]
 pub async fn delete_episodes(
-    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
     id: WebtoonId,
     eps2delete: Vec<usize>,
 ) -> Result<WebtoonInfo, String> {
-    let webtoons_store = app
-        .store(WEBTOONS_STORE)
-        .map_err(|_| "Failed to open wt store")?;
-
-    let mut webtoon = webtoons_store
-        .get(id.wt_id.to_string())
-        .map(serde_json::from_value::<WebtoonInfo>)
-        .ok_or("Webtoon is not found")?
-        .map_err(|e| e.to_string())?;
-
-    let episodes = webtoon.episodes.ok_or("No episodes found")?;
-    let filtered_eps = episodes
-        .into_iter()
-        .filter(|ep| !eps2delete.contains(&ep.number))
-        .collect::<Vec<_>>();
-
-    webtoon.episodes = Some(filtered_eps);
-
-    // set updated/new webtoon to storage
-    webtoons_store.set(
-        id.wt_id.to_string(),
-        serde_json::to_value(&webtoon).map_err(|_| "Couldn't serialize webtoon_info")?,
-    );
+    let _series_lock = db.lock_series(id).await?;
+    let mut webtoon = db.get_series(id)?.ok_or("Webtoon is not found")?;
+
+    let episodes = webtoon.episodes.as_mut().ok_or("No episodes found")?;
+    episodes.retain(|number, _| !eps2delete.contains(number));
+
+    db.delete_episodes(id, &eps2delete)?;
     Ok(webtoon)
 }
 
 #[tauri::command]
-pub async fn delete_webtoon(app: tauri::AppHandle, id: WebtoonId) -> Result<(), String> {
-    let webtoons_store = app
-        .store(WEBTOONS_STORE)
-        .map_err(|_| "Failed to open wt store")?;
-
-    match webtoons_store.delete(id.wt_id.to_string()) {
-        true => Ok(()),
-        false => Err("Failed to delete webtoon from the store".to_string()),
+pub async fn delete_webtoon(db: tauri::State<'_, Database>, id: WebtoonId) -> Result<(), String> {
+    // a refresh in progress would save the series back
+    let _series_lock = db.lock_series(id).await?;
+    if !db.delete_series(id)? {
+        return Err("Failed to delete webtoon from the store".to_string());
+    }
+
+    // the cached images are only removed best effort, they're not referenced anymore anyway
+    for path in db.take_indexed_images(id)? {
+        let _ = tokio::fs::remove_file(path).await;
     }
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn get_webtoon_info(app: tauri::AppHandle, id: WebtoonId) -> Result<WebtoonInfo, String> {
+pub async fn get_webtoon_info(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+    new_episodes_state: tauri::State<'_, Mutex<NewEpisodes>>,
+    id: WebtoonId,
+) -> Result<WebtoonInfo, String> {
+    // the user has now seen the new episodes found in the background
+    new_episodes_state.lock().await.remove(&id.store_key());
+
     let wt_dl_progress_cb = |news: DownloadState| {
         let _ = app.emit("wt_dl_channel", news);
     };
 
-    let webtoons_store = app
-        .store(WEBTOONS_STORE)
-        .map_err(|_| "Failed to open wt store")?;
-
+    // held until the updated webtoon is saved
+    let _series_lock = db.lock_series(id).await?;
     // check if already cached and not expired
-    let webtoon_info = match webtoons_store
-        .get(id.wt_id.to_string())
-        .map(serde_json::from_value::<WebtoonInfo>)
-    {
-        Some(Ok(wt))
+    let webtoon_info = match db.get_series(id) {
+        Ok(Some(wt))
             if wt.expired_at > SystemTime::now() && wt.refresh_eps_at > SystemTime::now() =>
         {
-            return Ok(wt); // no need to re-write the same value to the store
+            return Ok(wt); // no need to re-write the same value to the database
         }
-        Some(Ok(mut wt))
+        Ok(Some(mut wt))
             if wt.expired_at <= SystemTime::now() && wt.refresh_eps_at > SystemTime::now() =>
         {
             // refresh expired webtoon
@@ -149,7 +133,7 @@ pub async fn get_webtoon_info(app: tauri::AppHandle, id: WebtoonId) -> Result<We
             wt.refresh(&thumb_path, wt_dl_progress_cb).await?;
             wt
         }
-        Some(Ok(mut wt))
+        Ok(Some(mut wt))
             if wt.refresh_eps_at <= SystemTime::now() && wt.expired_at > SystemTime::now() =>
         {
             // get missing eps
@@ -157,17 +141,19 @@ pub async fn get_webtoon_info(app: tauri::AppHandle, id: WebtoonId) -> Result<We
             wt.update_episodes(&thumb_path, wt_dl_progress_cb).await?;
             wt
         }
-        Some(Ok(mut wt)) => {
+        Ok(Some(mut wt)) => {
             // refresh expired webtoon
             let thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
             wt.refresh(&thumb_path, wt_dl_progress_cb).await?;
 
-            // get missing eps
+            // `refresh` keeps the expired `refresh_eps_at`, so the whole list is reconciled
             wt.update_episodes(&thumb_path, wt_dl_progress_cb).await?;
             wt
         }
-        Some(Err(_)) | None => {
-            // if not existing or type migration, fetch data
+        // the stores are migrated at startup, so this isn't an old format but a broken entry
+        Err(e) => return Err(format!("Stored webtoon is unreadable: {e}")),
+        Ok(None) => {
+            // if not existing, fetch data
             let thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
             let mut webtoon = WebtoonInfo::new_from_id(id, wt_dl_progress_cb).await?;
             webtoon
@@ -180,39 +166,8 @@ pub async fn get_webtoon_info(app: tauri::AppHandle, id: WebtoonId) -> Result<We
         }
     };
 
-    // set updated/new webtoon to storage
-    webtoons_store.set(
-        id.wt_id.to_string(),
-        serde_json::to_value(&webtoon_info).map_err(|_| "Couldn't serialize webtoon_info")?,
-    );
+    // set updated/new webtoon to the database, along with the thumbnails it cached
+    db.save_series(&webtoon_info)?;
+    db.index_series_images(&webtoon_info)?;
     Ok(webtoon_info)
 }
-
-#[tauri::command]
-/// get canvas and original (check exemple)
-pub async fn get_homepage_recommandations(
-    app: tauri::AppHandle,
-) -> Result<Vec<WebtoonSearchInfo>, String> {
-    let mut canvas = fetch_canvas().await?;
-    let original = fetch_original().await?;
-
-    let mut merged = original;
-    merged.append(&mut canvas);
-
-    let mut rng = WyRand::new();
-    rng.shuffle(&mut merged);
-
-    let cache_thumb_path = app.path().app_local_data_dir().map_err(|e| e.to_string())?;
-    let new_thumb_path = download_images(
-        &cache_thumb_path,
-        merged.iter().map(|wt| wt.thumbnail.clone()).collect(),
-        "homepage_recommandations".to_string(),
-        |_| {},
-    )
-    .await?;
-    for (wt, new_path) in merged.iter_mut().zip(new_thumb_path) {
-        wt.thumbnail = new_path
-    }
-
-    Ok(merged)
-}
@@ -1,12 +1,16 @@
 use crate::{
     components::alert::Alert,
-    routes::{creator::CreatorPage, episode::EpisodePage, home::Home, webtoon::WebtoonPage},
+    routes::{
+        bookmarks::BookmarksPage, creator::CreatorPage, episode::EpisodePage, genre::GenrePage,
+        history::HistoryPage, home::Home, stats::StatsPage, webtoon::WebtoonPage,
+    },
     utility::{
-        store::{LoadingState, UserData, UserRecommendations},
-        types::{Alert, WebtoonSearchInfo},
+        store::{LoadingState, UpdatingToday, UserData, UserRecommendations},
+        types::{Alert, RecommendationSection, TodayWebtoon, Weekday},
     },
 };
 use reactive_stores::Store;
+use serde::Serialize;
 
 use leptos::prelude::*;
 use leptos_router::{
@@ -20,6 +24,20 @@ use wasm_bindgen::prelude::*;
 extern "C" {
     #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
     async fn invoke_without_args(cmd: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct UpdatingTodayArgs {
+    today: Weekday,
+}
+
+#[derive(Serialize)]
+struct RecommendationsArgs {
+    today: Weekday,
+    force_refresh: bool,
 }
 
 #[component]
@@ -30,11 +48,32 @@ pub fn App() -> impl IntoView {
 
     let user_rec_state = Store::new(UserRecommendations::default());
     provide_context(user_rec_state);
+
+    let updating_today_state = Store::new(UpdatingToday::default());
+    provide_context(updating_today_state);
     // fetch ressources
     let user_data_resp = LocalResource::new(move || invoke_without_args("get_user_data"));
 
-    let recommendations_resp =
-        LocalResource::new(move || invoke_without_args("get_homepage_recommandations"));
+    let recommendations_resp = LocalResource::new(move || {
+        invoke(
+            "get_homepage_recommandations",
+            serde_wasm_bindgen::to_value(&RecommendationsArgs {
+                today: Weekday::today(),
+                force_refresh: false,
+            })
+            .unwrap(),
+        )
+    });
+
+    let updating_today_resp = LocalResource::new(move || {
+        invoke(
+            "get_updating_today",
+            serde_wasm_bindgen::to_value(&UpdatingTodayArgs {
+                today: Weekday::today(),
+            })
+            .unwrap(),
+        )
+    });
 
     // load ressources
     Effect::new(move |_| {
@@ -67,7 +106,7 @@ pub fn App() -> impl IntoView {
     Effect::new(move |_| {
         let user_rec = match recommendations_resp.get().map(|opv| {
             opv.map(|v| {
-                serde_wasm_bindgen::from_value::<Vec<WebtoonSearchInfo>>(v)
+                serde_wasm_bindgen::from_value::<Vec<RecommendationSection>>(v)
                     .map_err(|_| "Failed to parse data as the right struct".to_string())
             })
             .map_err(|e| {
@@ -76,8 +115,8 @@ pub fn App() -> impl IntoView {
                 )
             })
         }) {
-            Some(Ok(Ok(wt))) => UserRecommendations {
-                webtoons: wt,
+            Some(Ok(Ok(sections))) => UserRecommendations {
+                sections,
                 loading_state: LoadingState::Completed,
             },
             None => return,
@@ -91,6 +130,32 @@ pub fn App() -> impl IntoView {
         user_rec_state.set(user_rec);
     });
 
+    Effect::new(move |_| {
+        let updating_today = match updating_today_resp.get().map(|opv| {
+            opv.map(|v| {
+                serde_wasm_bindgen::from_value::<Vec<TodayWebtoon>>(v)
+                    .map_err(|_| "Failed to parse data as the right struct".to_string())
+            })
+            .map_err(|e| {
+                e.as_string().unwrap_or(
+                    "An error happened, but we can't provide more information".to_string(),
+                )
+            })
+        }) {
+            Some(Ok(Ok(wt))) => UpdatingToday {
+                webtoons: wt,
+                loading_state: LoadingState::Completed,
+            },
+            None => return,
+            Some(Err(e)) | Some(Ok(Err(e))) => UpdatingToday {
+                loading_state: LoadingState::Error(e),
+                ..Default::default()
+            },
+        };
+
+        updating_today_state.set(updating_today);
+    });
+
     /* Alert system */
     let (alerts, set_alerts) = signal::<Vec<Alert>>(vec![]);
     let push_toast = move |mut alert: Alert| {
@@ -114,6 +179,10 @@ pub fn App() -> impl IntoView {
                 <Route path=path!("/webtoon") view=WebtoonPage />
                 <Route path=path!("/webtoon/episode/:num") view=EpisodePage />
                 <Route path=path!("/creator/:id") view=CreatorPage />
+                <Route path=path!("/genre/:name") view=GenrePage />
+                <Route path=path!("/history") view=HistoryPage />
+                <Route path=path!("/stats") view=StatsPage />
+                <Route path=path!("/bookmarks") view=BookmarksPage />
             </Routes>
 
             <div id="alerts">
@@ -0,0 +1,135 @@
+use leptos::prelude::*;
+use leptos_meta::Style;
+
+/// One bar of a chart, `value_label` is how its value is displayed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bar {
+    pub label: String,
+    pub value: f64,
+    pub value_label: String,
+}
+
+/* sizes in svg units, the charts are scaled to the width of their container */
+const COLUMN_WIDTH: f64 = 10.0;
+const COLUMNS_HEIGHT: f64 = 60.0;
+const AXIS_HEIGHT: f64 = 10.0;
+const ROW_WIDTH: f64 = 200.0;
+const ROW_HEIGHT: f64 = 18.0;
+
+/// Vertical bars, the value of the highest one is shown in the corner
+///
+/// a bar with an empty label leaves its column unlabeled
+#[component]
+pub fn BarChart(#[prop(into)] bars: Signal<Vec<Bar>>) -> impl IntoView {
+    let highest = Memo::new(move |_| {
+        bars.with(|bars| {
+            bars.iter()
+                .filter(|bar| bar.value > 0.0)
+                .max_by(|a, b| a.value.total_cmp(&b.value))
+                .cloned()
+        })
+    });
+
+    view! {
+        <Style>{include_str!("chart.css")}</Style>
+        <svg
+            class="bar_chart"
+            viewBox=move || {
+                format!(
+                    "0 0 {} {}",
+                    bars.with(Vec::len) as f64 * COLUMN_WIDTH,
+                    COLUMNS_HEIGHT + AXIS_HEIGHT,
+                )
+            }
+        >
+            {move || {
+                let scale = highest.get().map(|bar| bar.value).unwrap_or(1.0);
+                bars.get()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, bar)| {
+                        let x = i as f64 * COLUMN_WIDTH;
+                        let height = bar.value / scale * COLUMNS_HEIGHT;
+                        view! {
+                            <rect
+                                x={x + 1.0}
+                                y={COLUMNS_HEIGHT - height}
+                                width={COLUMN_WIDTH - 2.0}
+                                height=height
+                                rx=1.0
+                            />
+                            <text
+                                class="label"
+                                x={x + COLUMN_WIDTH / 2.0}
+                                y={COLUMNS_HEIGHT + AXIS_HEIGHT - 2.0}
+                            >
+                                {bar.label}
+                            </text>
+                        }
+                    })
+                    .collect_view()
+            }}
+            {move || {
+                highest
+                    .get()
+                    .map(|bar| {
+                        view! {
+                            <text class="highest" x=0.0 y=6.0>
+                                {bar.value_label}
+                            </text>
+                        }
+                    })
+            }}
+        </svg>
+    }
+}
+
+/// Horizontal bars with their label above them, ranked as given
+///
+/// `max` is the value of a full bar, the highest value by default
+#[component]
+pub fn RankChart(
+    #[prop(into)] bars: Signal<Vec<Bar>>,
+    #[prop(optional)] max: Option<f64>,
+) -> impl IntoView {
+    view! {
+        <Style>{include_str!("chart.css")}</Style>
+        <svg
+            class="rank_chart"
+            viewBox=move || {
+                format!("0 0 {ROW_WIDTH} {}", bars.with(Vec::len) as f64 * ROW_HEIGHT)
+            }
+        >
+            {move || {
+                let bars = bars.get();
+                let scale = max
+                    .unwrap_or_else(|| bars.iter().map(|bar| bar.value).fold(0.0, f64::max));
+                let scale = if scale > 0.0 { scale } else { 1.0 };
+                bars.into_iter()
+                    .enumerate()
+                    .map(|(i, bar)| {
+                        let y = i as f64 * ROW_HEIGHT;
+                        let width = (bar.value / scale).clamp(0.0, 1.0) * ROW_WIDTH;
+                        view! {
+                            <text class="label" x=0.0 y={y + 8.0}>
+                                {bar.label}
+                            </text>
+                            <text class="value" x=ROW_WIDTH y={y + 8.0}>
+                                {bar.value_label}
+                            </text>
+                            <rect
+                                class="track"
+                                x=0.0
+                                y={y + 10.0}
+                                width=ROW_WIDTH
+                                height=5.0
+                                rx=2.0
+                            />
+                            <rect x=0.0 y={y + 10.0} width=width height=5.0 rx=2.0 />
+                        }
+                    })
+                    .collect_view()
+            }}
+        </svg>
+    }
+}
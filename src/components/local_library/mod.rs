@@ -0,0 +1,107 @@
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Style;
+
+use icondata as i;
+use leptos_icons::Icon;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    components::webtoon::Webtoon,
+    parse_or_toast,
+    utility::types::{Alert, AlertLevel, WebtoonSearchInfo},
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct LocalLibraryArgs {
+    library_path: Option<String>,
+}
+
+/// Series found in the user's comics folder, with the form to choose that folder
+#[component]
+pub fn LocalLibrary() -> impl IntoView {
+    /* context */
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    let (local_series, set_local_series) = signal(Vec::<WebtoonSearchInfo>::new());
+    let (show_settings, set_show_settings) = signal(false);
+    let library_path = RwSignal::new(String::new());
+
+    /* Handlers */
+    let load_library = move || {
+        spawn_local(async move {
+            let series = parse_or_toast!(
+                invoke("get_local_library", JsValue::null()).await,
+                Ty = Vec<WebtoonSearchInfo>,
+                push_toast
+            );
+            set_local_series.set(series);
+        });
+    };
+
+    let save_library_path = move |_| {
+        let path = library_path.get_untracked();
+        spawn_local(async move {
+            let series = parse_or_toast!(
+                invoke(
+                    "set_local_library",
+                    serde_wasm_bindgen::to_value(&LocalLibraryArgs {
+                        library_path: Some(path).filter(|p| !p.trim().is_empty()),
+                    })
+                    .unwrap()
+                )
+                .await,
+                Ty = Vec<WebtoonSearchInfo>,
+                push_toast
+            );
+            set_local_series.set(series);
+            set_show_settings.set(false);
+        });
+    };
+
+    load_library();
+
+    view! {
+        <Style>{include_str!("local_library.css")}</Style>
+        <div id="local_library">
+            <div class="header">
+                <h3>"On this device"</h3>
+                <button on:click=move |_| set_show_settings.update(|s| *s = !*s)>
+                    <Icon icon=i::BiFolderOpenRegular />
+                </button>
+            </div>
+            <Show when=move || show_settings.get()>
+                <div class="settings">
+                    <input
+                        type="text"
+                        placeholder="Comics folder, e.g. /home/me/Comics"
+                        bind:value=library_path
+                    />
+                    <button on:click=save_library_path>"Scan"</button>
+                </div>
+            </Show>
+            <Show when=move || !local_series.get().is_empty()>
+                <div class="shelf">
+                    <For
+                        each=move || local_series.get()
+                        key=|wt| (wt.id.store_key(), wt.thumbnail.clone())
+                        let(wt: WebtoonSearchInfo)
+                    >
+                        <div class="shelf_item">
+                            <Webtoon wt_info=wt is_local=true />
+                        </div>
+                    </For>
+                </div>
+            </Show>
+        </div>
+    }
+}
@@ -1,4 +1,9 @@
 pub mod alert;
+pub mod chart;
+pub mod local_library;
+pub mod mute_rules;
+pub mod reader_settings;
+pub mod shelves;
 pub mod spinner;
 pub mod waiting_screen;
 pub mod webtoon;
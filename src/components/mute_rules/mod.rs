@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Style;
+use reactive_stores::Store;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    parse_or_toast,
+    utility::{
+        store::{MuteRules, UserData, UserDataStoreFields},
+        types::{Alert, AlertLevel},
+    },
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct MuteRulesArgs<'a> {
+    mute_rules: &'a MuteRules,
+}
+
+/// one rule per line, blank lines are ignored
+fn parse_lines(raw: &str) -> Vec<String> {
+    raw.lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+/// Form editing the user's comment mute rules, `on_saved` is called once the new rules are stored
+#[component]
+pub fn MuteRulesEditor(on_saved: Callback<()>) -> impl IntoView {
+    /* context */
+    let user_state = expect_context::<Store<UserData>>();
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    let current_rules = user_state.mute_rules().get_untracked();
+    let words = RwSignal::new(current_rules.words.join("\n"));
+    let regexes = RwSignal::new(current_rules.regexes.join("\n"));
+    let posters = RwSignal::new(current_rules.posters.join("\n"));
+    let hide_spoilers = RwSignal::new(current_rules.hide_spoilers);
+
+    /* Handlers */
+    let save_rules = move |_| {
+        let mute_rules = MuteRules {
+            words: parse_lines(&words.get_untracked()),
+            regexes: parse_lines(&regexes.get_untracked()),
+            posters: parse_lines(&posters.get_untracked()),
+            hide_spoilers: hide_spoilers.get_untracked(),
+        };
+
+        spawn_local(async move {
+            parse_or_toast!(
+                invoke(
+                    "set_mute_rules",
+                    serde_wasm_bindgen::to_value(&MuteRulesArgs {
+                        mute_rules: &mute_rules
+                    })
+                    .unwrap()
+                )
+                .await,
+                Ty = (),
+                push_toast
+            );
+
+            user_state.mute_rules().set(mute_rules);
+            push_toast.run(Alert::new(
+                "Filters saved",
+                AlertLevel::Success,
+                Some(Duration::from_secs(1)),
+            ));
+            on_saved.run(());
+        });
+    };
+
+    view! {
+        <Style>{include_str!("mute_rules.css")}</Style>
+        <div class="mute_rules">
+            <label>
+                "Muted words (one per line)"
+                <textarea bind:value=words rows="3"></textarea>
+            </label>
+            <label>
+                "Muted patterns (regexes, one per line)"
+                <textarea bind:value=regexes rows="2"></textarea>
+            </label>
+            <label>
+                "Muted users (one per line)"
+                <textarea bind:value=posters rows="2"></textarea>
+            </label>
+            <label class="checkbox">
+                <input type="checkbox" bind:checked=hide_spoilers />
+                "Hide spoilers entirely"
+            </label>
+            <button on:click=save_rules>"Save filters"</button>
+        </div>
+    }
+}
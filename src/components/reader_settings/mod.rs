@@ -0,0 +1,96 @@
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Style;
+use reactive_stores::Store;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    parse_or_toast,
+    utility::{
+        store::{ReaderSettings, UserData, UserDataStoreFields},
+        types::{Alert, AlertLevel},
+    },
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct ReaderSettingsArgs {
+    reader_settings: ReaderSettings,
+}
+
+/// Settings of the episode reader, saved as soon as they change
+#[component]
+pub fn ReaderSettingsEditor() -> impl IntoView {
+    /* context */
+    let user_state = expect_context::<Store<UserData>>();
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* Handlers */
+    let save_settings = move |reader_settings: ReaderSettings| {
+        spawn_local(async move {
+            let saved = parse_or_toast!(
+                invoke(
+                    "set_reader_settings",
+                    serde_wasm_bindgen::to_value(&ReaderSettingsArgs { reader_settings }).unwrap()
+                )
+                .await,
+                Ty = ReaderSettings,
+                push_toast
+            );
+            user_state.reader_settings().set(saved);
+        });
+    };
+
+    view! {
+        <Style>{include_str!("reader_settings.css")}</Style>
+        <div class="reader_settings">
+            <label class="checkbox">
+                <input
+                    type="checkbox"
+                    prop:checked=move || user_state.reader_settings().get().auto_mark_read
+                    on:change:target=move |ev| {
+                        save_settings(ReaderSettings {
+                            auto_mark_read: ev.target().checked(),
+                            ..user_state.reader_settings().get_untracked()
+                        })
+                    }
+                />
+                "Mark as read when reaching the end"
+            </label>
+            <Show when=move || user_state.reader_settings().get().auto_mark_read>
+                <label>
+                    {move || {
+                        format!(
+                            "Once {}% of the last panel is on screen",
+                            user_state.reader_settings().get().end_threshold,
+                        )
+                    }}
+                    <input
+                        type="range"
+                        min="10"
+                        max="100"
+                        step="5"
+                        prop:value=move || {
+                            user_state.reader_settings().get().end_threshold.to_string()
+                        }
+                        on:change:target=move |ev| {
+                            if let Ok(end_threshold) = ev.target().value().parse::<u8>() {
+                                save_settings(ReaderSettings {
+                                    end_threshold,
+                                    ..user_state.reader_settings().get_untracked()
+                                })
+                            }
+                        }
+                    />
+                </label>
+            </Show>
+        </div>
+    }
+}
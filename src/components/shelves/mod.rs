@@ -0,0 +1,259 @@
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Style;
+use reactive_stores::Store;
+
+use icondata as i;
+use leptos_icons::Icon;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    parse_or_toast,
+    utility::{
+        store::{UserData, UserDataStoreFields},
+        types::{Alert, AlertLevel, WebtoonId},
+    },
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct ShelvesArgs {
+    shelves: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct WebtoonShelfArgs {
+    wt_id: WebtoonId,
+    shelf: Option<String>,
+}
+
+#[derive(Serialize)]
+struct WebtoonTagsArgs {
+    wt_id: WebtoonId,
+    tags: Vec<String>,
+}
+
+/// Shelf and tags of a subscription, saved as soon as they change
+#[component]
+pub fn ShelfPicker(wt_id: WebtoonId) -> impl IntoView {
+    /* context */
+    let user_state = expect_context::<Store<UserData>>();
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    // only the shelf and the tags, the read progress changes too often
+    let shelf_and_tags = Memo::new(move |_| {
+        user_state.webtoons().with(|webtoons| {
+            webtoons
+                .get(&wt_id.store_key())
+                .map(|uwt| (uwt.shelf.clone(), uwt.tags.clone()))
+        })
+    });
+
+    /* Handlers */
+    let save_shelf = move |shelf: Option<String>| {
+        spawn_local(async move {
+            parse_or_toast!(
+                invoke(
+                    "set_webtoon_shelf",
+                    serde_wasm_bindgen::to_value(&WebtoonShelfArgs {
+                        wt_id,
+                        shelf: shelf.clone()
+                    })
+                    .unwrap()
+                )
+                .await,
+                Ty = (),
+                push_toast
+            );
+            user_state.webtoons().update(|webtoons| {
+                if let Some(uwt) = webtoons.get_mut(&wt_id.store_key()) {
+                    uwt.shelf = shelf;
+                }
+            });
+        });
+    };
+
+    // comma separated, the backend trims and deduplicates them
+    let save_tags = move |raw_tags: String| {
+        let tags = raw_tags.split(',').map(str::to_string).collect();
+        spawn_local(async move {
+            let saved = parse_or_toast!(
+                invoke(
+                    "set_webtoon_tags",
+                    serde_wasm_bindgen::to_value(&WebtoonTagsArgs { wt_id, tags }).unwrap()
+                )
+                .await,
+                Ty = Vec<String>,
+                push_toast
+            );
+            user_state.webtoons().update(|webtoons| {
+                if let Some(uwt) = webtoons.get_mut(&wt_id.store_key()) {
+                    uwt.tags = saved;
+                }
+            });
+        });
+    };
+
+    view! {
+        <Style>{include_str!("shelves.css")}</Style>
+        <div class="shelf_picker">
+            <select on:change:target=move |ev| {
+                let shelf = ev.target().value();
+                save_shelf((!shelf.is_empty()).then_some(shelf));
+            }>
+                <option
+                    value=""
+                    selected=move || {
+                        shelf_and_tags.get().is_some_and(|(shelf, _)| shelf.is_none())
+                    }
+                >
+                    "No shelf"
+                </option>
+                <For
+                    each=move || user_state.shelves().get()
+                    key=|shelf| shelf.clone()
+                    let(shelf)
+                >
+                    <option
+                        value=shelf.clone()
+                        selected={
+                            let shelf = shelf.clone();
+                            move || {
+                                shelf_and_tags
+                                    .get()
+                                    .is_some_and(|(current, _)| current.as_ref() == Some(&shelf))
+                            }
+                        }
+                    >
+                        {shelf.clone()}
+                    </option>
+                </For>
+            </select>
+            <input
+                type="text"
+                placeholder="Tags, separated by commas"
+                prop:value=move || {
+                    shelf_and_tags.get().map(|(_, tags)| tags.join(", ")).unwrap_or_default()
+                }
+                on:change:target=move |ev| save_tags(ev.target().value())
+            />
+        </div>
+    }
+}
+
+/// Adds, removes and reorders the user's shelves, each change is saved right away
+#[component]
+pub fn ShelvesEditor() -> impl IntoView {
+    /* context */
+    let user_state = expect_context::<Store<UserData>>();
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    let new_shelf = RwSignal::new(String::new());
+
+    /* Handlers */
+    let save_shelves = move |shelves: Vec<String>| {
+        spawn_local(async move {
+            let saved = parse_or_toast!(
+                invoke(
+                    "set_shelves",
+                    serde_wasm_bindgen::to_value(&ShelvesArgs { shelves }).unwrap()
+                )
+                .await,
+                Ty = Vec<String>,
+                push_toast
+            );
+            // the backend takes the subscriptions off the removed shelves
+            user_state.webtoons().update(|webtoons| {
+                for uwt in webtoons.values_mut() {
+                    if uwt
+                        .shelf
+                        .as_ref()
+                        .is_some_and(|shelf| !saved.contains(shelf))
+                    {
+                        uwt.shelf = None;
+                    }
+                }
+            });
+            user_state.shelves().set(saved);
+        });
+    };
+
+    let move_shelf = move |from: usize, to: usize| {
+        let mut shelves = user_state.shelves().get_untracked();
+        if to < shelves.len() {
+            shelves.swap(from, to);
+            save_shelves(shelves);
+        }
+    };
+
+    let remove_shelf = move |index: usize| {
+        let mut shelves = user_state.shelves().get_untracked();
+        shelves.remove(index);
+        save_shelves(shelves);
+    };
+
+    let add_shelf = move |_| {
+        let name = new_shelf.get_untracked();
+        if name.trim().is_empty() {
+            return;
+        }
+        let mut shelves = user_state.shelves().get_untracked();
+        shelves.push(name);
+        new_shelf.set(String::new());
+        save_shelves(shelves);
+    };
+
+    view! {
+        <Style>{include_str!("shelves.css")}</Style>
+        <div class="shelves_editor">
+            {move || {
+                let shelves = user_state.shelves().get();
+                let last = shelves.len().saturating_sub(1);
+                shelves
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, shelf)| {
+                        view! {
+                            <div class="shelf">
+                                <span>{shelf}</span>
+                                <button
+                                    title="Move up"
+                                    disabled={index == 0}
+                                    on:click=move |_| move_shelf(index, index.saturating_sub(1))
+                                >
+                                    <Icon icon=i::BiUpArrowAltRegular />
+                                </button>
+                                <button
+                                    title="Move down"
+                                    disabled={index == last}
+                                    on:click=move |_| move_shelf(index, index + 1)
+                                >
+                                    <Icon icon=i::BiDownArrowAltRegular />
+                                </button>
+                                <button title="Remove shelf" on:click=move |_| remove_shelf(index)>
+                                    <Icon icon=i::BiXRegular />
+                                </button>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+            <div class="new_shelf">
+                <input type="text" placeholder="New shelf" bind:value=new_shelf />
+                <button class="btn" on:click=add_shelf>
+                    "Add"
+                </button>
+            </div>
+        </div>
+    }
+}
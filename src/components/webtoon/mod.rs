@@ -10,14 +10,18 @@ pub fn Webtoon(
         thumbnail,
         creator,
         id,
+        ..
     }: WebtoonSearchInfo,
     is_local: bool,
+    /// number shown over the thumbnail, hidden when `0`
+    #[prop(optional)]
+    badge: Option<Signal<usize>>,
 ) -> impl IntoView {
     view! {
         <Style>{include_str!("webtoon.css")}</Style>
         <a
             class="webtoon"
-            href=move || format!("/webtoon?wt_id={}&wt_type={}", id.wt_id, id.wt_type)
+            href=move || format!("/webtoon?{}", id.to_query())
         >
             <div class="thumbnail">
                 <img
@@ -26,6 +30,14 @@ pub fn Webtoon(
                     }
                     alt="Webtoon poster"
                 />
+                {badge
+                    .map(|count| {
+                        view! {
+                            <Show when=move || { count.get() > 0 }>
+                                <span class="badge">{move || count.get()}</span>
+                            </Show>
+                        }
+                    })}
             </div>
             <div class="title">
                 <span>{title}</span>
@@ -0,0 +1,173 @@
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Style;
+use reactive_stores::Store;
+
+use icondata as i;
+use leptos_icons::Icon;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    parse_or_toast,
+    utility::{
+        store::{Bookmark, UserData, UserDataStoreFields},
+        types::{Alert, AlertLevel},
+    },
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct BookmarkIdArgs {
+    bookmark_id: u64,
+}
+
+#[derive(Serialize)]
+struct NoteArgs {
+    bookmark_id: u64,
+    note: String,
+}
+
+/// opens the episode scrolled to the bookmarked panel, or to the top of its cropped part
+fn bookmark_href(bookmark: &Bookmark) -> String {
+    format!(
+        "/webtoon/episode/{}?{}&panel={}&offset={}",
+        bookmark.episode,
+        bookmark.wt_id.to_query(),
+        bookmark.panel,
+        bookmark.crop.map(|crop| crop.y).unwrap_or_default(),
+    )
+}
+
+fn date_label(unix_secs: u64) -> String {
+    // js_sys bindings calls JavaScript's standard lib, which knows the user's timezone
+    js_sys::Date::new(&JsValue::from_f64(unix_secs as f64 * 1000.0))
+        .to_date_string()
+        .as_string()
+        .unwrap_or_default()
+}
+
+#[component]
+pub fn BookmarksPage() -> impl IntoView {
+    /* context */
+    let user_state = expect_context::<Store<UserData>>();
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    // most recent first
+    let bookmarks = Memo::new(move |_| {
+        let mut bookmarks = user_state.bookmarks().get();
+        bookmarks.reverse();
+        bookmarks
+    });
+
+    /* Handlers */
+    let save_note = move |bookmark_id: u64, note: String| {
+        spawn_local(async move {
+            parse_or_toast!(
+                invoke(
+                    "set_bookmark_note",
+                    serde_wasm_bindgen::to_value(&NoteArgs {
+                        bookmark_id,
+                        note: note.clone()
+                    })
+                    .unwrap()
+                )
+                .await,
+                Ty = (),
+                push_toast
+            );
+            user_state.bookmarks().update(|bookmarks| {
+                if let Some(bookmark) = bookmarks.iter_mut().find(|b| b.id == bookmark_id) {
+                    bookmark.note = note;
+                }
+            });
+        });
+    };
+
+    let delete_bookmark = move |bookmark_id: u64| {
+        spawn_local(async move {
+            parse_or_toast!(
+                invoke(
+                    "delete_bookmark",
+                    serde_wasm_bindgen::to_value(&BookmarkIdArgs { bookmark_id }).unwrap()
+                )
+                .await,
+                Ty = (),
+                push_toast
+            );
+            user_state
+                .bookmarks()
+                .update(|bookmarks| bookmarks.retain(|b| b.id != bookmark_id));
+        });
+    };
+
+    view! {
+        <Style>{include_str!("bookmarks.css")}</Style>
+        <div id="bookmarks_page">
+            <header>
+                <a href="/">
+                    <Icon icon=i::IoCaretBackOutline />
+                </a>
+                <h1>"Bookmarks"</h1>
+            </header>
+            <div id="bookmarks">
+                <Show
+                    when=move || !bookmarks.get().is_empty()
+                    fallback=|| {
+                        view! { <p>"No bookmark yet, save panels from the reader!"</p> }
+                    }
+                >
+                    <For
+                        each=move || bookmarks.get()
+                        key=|bookmark| bookmark.id
+                        let(bookmark)
+                    >
+                        {
+                            let bookmark_id = bookmark.id;
+                            let href = bookmark_href(&bookmark);
+                            let position = format!(
+                                "#{} {} · panel {}{}",
+                                bookmark.episode,
+                                bookmark.episode_title,
+                                bookmark.panel + 1,
+                                if bookmark.crop.is_some() { " (part)" } else { "" },
+                            );
+                            view! {
+                                <div class="bookmark">
+                                    <div class="heading">
+                                        <a href=href title="Open the panel">
+                                            <span class="title">{bookmark.title}</span>
+                                            <span class="episode">{position}</span>
+                                            <span class="date">{date_label(bookmark.created_at)}</span>
+                                        </a>
+                                        <button
+                                            title="Delete bookmark"
+                                            on:click=move |_| delete_bookmark(bookmark_id)
+                                        >
+                                            <Icon icon=i::BiTrashAltRegular />
+                                        </button>
+                                    </div>
+                                    <textarea
+                                        rows="2"
+                                        placeholder="Add a note"
+                                        prop:value=bookmark.note
+                                        on:change:target=move |ev| {
+                                            save_note(bookmark_id, ev.target().value())
+                                        }
+                                    ></textarea>
+                                </div>
+                            }
+                        }
+                    </For>
+                </Show>
+            </div>
+        </div>
+    }
+}
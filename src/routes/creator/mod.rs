@@ -14,7 +14,9 @@ use wasm_bindgen::prelude::*;
 use crate::{
     components::{waiting_screen::WaitingScreen, webtoon::Webtoon},
     parse_or_navigate,
-    utility::types::{Alert, AlertLevel, CreatorInfo, DownloadState, Language, WebtoonSearchInfo},
+    utility::types::{
+        Alert, AlertLevel, CreatorInfo, DownloadState, Language, SocialLink, WebtoonSearchInfo,
+    },
 };
 
 #[wasm_bindgen]
@@ -113,11 +115,31 @@ pub fn CreatorPage() -> impl IntoView {
                 <p class="followers">
                     {move || creator_data.get().unwrap().followers.unwrap_or_default()} " followers"
                 </p>
+                {move || {
+                    creator_data
+                        .get()
+                        .unwrap()
+                        .bio
+                        .map(|bio| {
+                            view! { <p class="bio">{bio}</p> }
+                        })
+                }}
+                <div class="socials">
+                    <For
+                        each=move || creator_data.get().unwrap().socials
+                        key=|social| social.url.clone()
+                        let(social: SocialLink)
+                    >
+                        <a href=social.url target="_blank">
+                            {social.platform}
+                        </a>
+                    </For>
+                </div>
 
                 <div id="webtoons">
                     <For
                         each=move || creator_data.get().unwrap().webtoons
-                        key=|wt| wt.id.wt_id
+                        key=|wt| wt.id.store_key()
                         let(wt: WebtoonSearchInfo)
                     >
                         <Webtoon wt_info=wt.clone() is_local=false />
@@ -18,12 +18,18 @@ use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 use crate::{
-    components::waiting_screen::WaitingScreen,
+    components::{
+        mute_rules::MuteRulesEditor, reader_settings::ReaderSettingsEditor,
+        waiting_screen::WaitingScreen,
+    },
     parse_or_navigate, parse_or_toast,
     utility::{
-        convert_file_src,
-        store::UserData,
-        types::{Alert, AlertLevel, DownloadState, EpisodeData, Post, WebtoonId, WtType},
+        convert_file_src, listen_while_mounted,
+        store::{Bookmark, CropRect, ReadProgress, UserData, UserDataStoreFields},
+        types::{
+            Alert, AlertLevel, DownloadState, EpisodeData, Language, Post, PostBatch, PostPage,
+            PostSort, SourceId, WebtoonId, WtType,
+        },
     },
 };
 
@@ -42,11 +48,55 @@ struct EpIdArgs {
     ep_num: usize,
 }
 
+#[derive(Serialize, Deserialize)]
+struct ProgressArgs {
+    wt_id: WebtoonId,
+    ep_num: usize,
+    progress: ReadProgress,
+}
+
+#[derive(Serialize)]
+struct BookmarkTarget {
+    wt_id: WebtoonId,
+    ep_num: usize,
+    panel: usize,
+    crop: Option<CropRect>,
+}
+
+#[derive(Serialize)]
+struct BookmarkArgs {
+    target: BookmarkTarget,
+    note: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PostsArgs {
+    wt_id: WebtoonId,
+    ep_num: usize,
+    sort: PostSort,
+    page: usize,
+    force_refresh: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RepliesArgs<'a> {
+    wt_id: WebtoonId,
+    ep_num: usize,
+    post_id: &'a str,
+    cursor: Option<&'a str>,
+}
+
 #[derive(Params, PartialEq, Debug, Clone)]
 struct WebtoonQueryArgs {
     wt_id: Option<usize>,
     wt_type: Option<WtType>,
-    prev_ep_read: Option<bool>,
+    source: Option<SourceId>,
+    lang: Option<Language>,
+    /// episode finished just before opening this one
+    read_ep: Option<usize>,
+    /// panel to scroll to instead of the saved position, see `ReadProgress`
+    panel: Option<usize>,
+    offset: Option<f64>,
 }
 
 #[derive(Params, PartialEq, Debug, Clone)]
@@ -54,6 +104,114 @@ struct EpisodeParams {
     num: Option<usize>,
 }
 
+/// delay without scrolling before the position in the episode is saved
+const SAVE_PROGRESS_DELAY: Duration = Duration::from_secs(1);
+
+/// panels of the episode, in reading order
+fn panel_elements(
+    panels: &leptos::web_sys::HtmlDivElement,
+) -> Vec<leptos::web_sys::HtmlImageElement> {
+    let mut elements = vec![];
+    let mut next = panels.first_element_child();
+    while let Some(element) = next {
+        next = element.next_element_sibling();
+        if let Ok(image) = element.dyn_into::<leptos::web_sys::HtmlImageElement>() {
+            elements.push(image);
+        }
+    }
+    elements
+}
+
+/// where the reader is in the episode: the panel at the top of the screen for the position, and the
+/// bottom of the screen for the percentage
+fn reading_progress(panels: &leptos::web_sys::HtmlDivElement) -> Option<ReadProgress> {
+    let elements = panel_elements(panels);
+    let (first, last) = (elements.first()?, elements.last()?);
+    let top = first.offset_top() as f64;
+    let height = (last.offset_top() + last.offset_height()) as f64 - top;
+    if height <= 0.0 {
+        return None;
+    }
+
+    let scroll_y = window().scroll_y().ok()?;
+    let viewport_height = window().inner_height().ok()?.as_f64()?;
+    let panel = elements
+        .iter()
+        .rposition(|el| el.offset_top() as f64 <= scroll_y)
+        .unwrap_or_default();
+    let element = &elements[panel];
+
+    Some(ReadProgress {
+        percent: ((scroll_y + viewport_height - top) / height * 100.0).clamp(0.0, 100.0) as u8,
+        panel,
+        offset: ((scroll_y - element.offset_top() as f64) / element.offset_height().max(1) as f64)
+            .clamp(0.0, 1.0),
+    })
+}
+
+/// whether the bottom of the screen went past `threshold` percent of the last panel
+///
+/// always `false` while some panels are loading, their height isn't known yet
+fn end_reached(panels: &leptos::web_sys::HtmlDivElement, threshold: u8) -> bool {
+    let elements = panel_elements(panels);
+    let Some(last) = elements.last() else {
+        return false;
+    };
+    if !elements.iter().all(|el| el.complete()) {
+        return false;
+    }
+    let (Ok(scroll_y), Some(viewport_height)) = (
+        window().scroll_y(),
+        window().inner_height().ok().and_then(|h| h.as_f64()),
+    ) else {
+        return false;
+    };
+
+    scroll_y + viewport_height
+        >= last.offset_top() as f64 + last.offset_height() as f64 * threshold as f64 / 100.0
+}
+
+/// the panel in the middle of the screen, cropped to its part on screen when it doesn't fit
+fn focused_panel(panels: &leptos::web_sys::HtmlDivElement) -> Option<(usize, Option<CropRect>)> {
+    let elements = panel_elements(panels);
+    let scroll_y = window().scroll_y().ok()?;
+    let viewport_height = window().inner_height().ok()?.as_f64()?;
+    let panel = elements
+        .iter()
+        .rposition(|el| el.offset_top() as f64 <= scroll_y + viewport_height / 2.0)?;
+    let element = &elements[panel];
+
+    let top = element.offset_top() as f64;
+    let height = element.offset_height().max(1) as f64;
+    let visible_top = ((scroll_y - top) / height).clamp(0.0, 1.0);
+    let visible_bottom = ((scroll_y + viewport_height - top) / height).clamp(0.0, 1.0);
+    let crop = (visible_top > 0.0 || visible_bottom < 1.0).then_some(CropRect {
+        x: 0.0,
+        y: visible_top,
+        width: 1.0,
+        height: visible_bottom - visible_top,
+    });
+    Some((panel, crop))
+}
+
+/// scrolls back to `progress`, returns `false` while the panels above it aren't loaded yet
+fn restore_progress(panels: &leptos::web_sys::HtmlDivElement, progress: ReadProgress) -> bool {
+    let elements = panel_elements(panels);
+    // the episode has less panels than when it was saved, nothing to restore
+    let Some(target) = elements.get(progress.panel) else {
+        return true;
+    };
+    if !elements[..=progress.panel].iter().all(|el| el.complete()) {
+        return false;
+    }
+
+    window().scroll_to_with_x_and_y(
+        0.0,
+        target.offset_top() as f64 + progress.offset * target.offset_height() as f64,
+    );
+    true
+}
+
 #[component]
 pub fn EpisodePage() -> impl IntoView {
     /* url params */
@@ -67,39 +225,157 @@ pub fn EpisodePage() -> impl IntoView {
     /* states */
     let user_state = expect_context::<Store<UserData>>();
 
-    let (episode_data, set_episode_data) = signal(None::<(EpisodeData, bool)>);
+    let (episode_data, set_episode_data) = signal(None::<(EpisodeData, Option<usize>)>);
     let (ep_comments, set_ep_comments) = signal(None::<Vec<Post>>);
+    let (post_sort, set_post_sort) = signal(PostSort::default());
+    let (posts_has_more, set_posts_has_more) = signal(false);
+    let (posts_stale, set_posts_stale) = signal(false);
+    let (posts_hidden, set_posts_hidden) = signal(0_usize);
+    let show_mute_rules = RwSignal::new(false);
+    let posts_page = StoredValue::new(1_usize);
+    let posts_loading = StoredValue::new(false);
     let (dl_state, set_dl_state) = signal(DownloadState::Idle);
     let (see_back_btn, set_see_back_btn) = signal(false);
+    let panels_ref = NodeRef::<leptos::html::Div>::new();
+    // position to go back to once the panels are loaded
+    let pending_restore = StoredValue::new(None::<ReadProgress>);
+    let save_timeout = StoredValue::new(None::<TimeoutHandle>);
+    // the episode is only marked as read once when its end is reached
+    let auto_marked = StoredValue::new(false);
+    // panel being bookmarked, waiting for its note
+    let (bookmark_draft, set_bookmark_draft) = signal(None::<(usize, Option<CropRect>)>);
+    let (bookmark_note, set_bookmark_note) = signal(String::new());
 
     /* Handlers */
-    let fetch_post = move || {
-        if let Some(ep_data) = episode_data.get_untracked() {
+    let fetch_post = move |page: usize, force_refresh: bool| {
+        let Some(ep_data) = episode_data.get_untracked() else {
+            return;
+        };
+        if posts_loading.get_value() {
+            return;
+        }
+        posts_loading.set_value(true);
+
+        if page == 1 && ep_comments.get_untracked().is_none() {
             push_toast.run(Alert::new(
                 "Posts are loading, it may take a while",
                 AlertLevel::Info,
                 None,
             ));
-            spawn_local(async move {
-                let posts = parse_or_toast!(
-                    invoke(
-                        "get_episode_post",
-                        serde_wasm_bindgen::to_value(&EpIdArgs {
-                            wt_id: ep_data.0.parent_wt_id,
-                            ep_num: ep_data.0.number
-                        })
-                        .unwrap()
-                    )
-                    .await,
-                    Ty = Vec<Post>,
+        }
+        let args = serde_wasm_bindgen::to_value(&PostsArgs {
+            wt_id: ep_data.0.parent_wt_id,
+            ep_num: ep_data.0.number,
+            sort: post_sort.get_untracked(),
+            page,
+            force_refresh,
+        })
+        .unwrap();
+        spawn_local(async move {
+            async {
+                let post_page = parse_or_toast!(
+                    invoke("get_episode_post", args).await,
+                    Ty = PostPage,
                     push_toast
                 );
-                set_ep_comments.set(Some(posts));
-            });
+                posts_page.set_value(post_page.page);
+                set_posts_has_more.set(post_page.has_more);
+                set_posts_stale.set(post_page.is_stale);
+                set_posts_hidden.set(post_page.hidden_count);
+                match page {
+                    1 => set_ep_comments.set(Some(post_page.posts)),
+                    // the top order moves comments between pages as more of them are loaded
+                    _ => set_ep_comments.update(|posts| {
+                        let posts = posts.get_or_insert_default();
+                        let new_posts = post_page
+                            .posts
+                            .into_iter()
+                            .filter(|post| posts.iter().all(|shown| shown.id != post.id))
+                            .collect::<Vec<_>>();
+                        posts.extend(new_posts);
+                    }),
+                }
+            }
+            .await;
+            posts_loading.set_value(false);
+        });
+    };
+
+    let change_post_sort = move |sort: PostSort| {
+        if post_sort.get_untracked() == sort {
+            return;
+        }
+        set_post_sort.set(sort);
+        if ep_comments.get_untracked().is_some() {
+            fetch_post(1, false);
         }
     };
 
-    let fetch_ep_data = move |wt_id: WebtoonId, ep_num: usize| {
+    let try_restore = move || {
+        if let (Some(progress), Some(panels)) =
+            (pending_restore.get_value(), panels_ref.get_untracked())
+        {
+            if restore_progress(&panels, progress) {
+                pending_restore.set_value(None);
+            }
+        }
+    };
+
+    let save_progress = move || {
+        // the page starts at the top, don't overwrite the saved position before going back to it
+        if pending_restore.get_value().is_some() {
+            return;
+        }
+        let (Some(panels), Some((wt_id, ep_num))) = (
+            panels_ref.get_untracked(),
+            episode_data.with_untracked(|ep_data| {
+                ep_data
+                    .as_ref()
+                    .map(|(ep_data, _)| (ep_data.parent_wt_id, ep_data.number))
+            }),
+        ) else {
+            return;
+        };
+        let Some(progress) = reading_progress(&panels) else {
+            return;
+        };
+
+        if let Some(timeout_handle) = save_timeout.get_value() {
+            timeout_handle.clear();
+        }
+        let timeout_handle = set_timeout_with_handle(
+            move || {
+                spawn_local(async move {
+                    let saved = parse_or_toast!(
+                        invoke(
+                            "save_read_progress",
+                            serde_wasm_bindgen::to_value(&ProgressArgs {
+                                wt_id,
+                                ep_num,
+                                progress
+                            })
+                            .unwrap()
+                        )
+                        .await,
+                        Ty = Option<ReadProgress>,
+                        push_toast
+                    );
+                    if let Some(saved) = saved {
+                        user_state.update(|us| {
+                            us.webtoons.entry(wt_id.store_key()).and_modify(|wt| {
+                                wt.episode_progress.insert(ep_num.to_string(), saved);
+                            });
+                        });
+                    }
+                });
+            },
+            SAVE_PROGRESS_DELAY,
+        )
+        .ok();
+        save_timeout.set_value(timeout_handle);
+    };
+
+    let fetch_ep_data = move |wt_id: WebtoonId, ep_num: usize, target: Option<ReadProgress>| {
         let navigate = use_navigate();
         spawn_local(async move {
             // open data stream to get info of webtoon download progression
@@ -123,32 +399,43 @@ pub fn EpisodePage() -> impl IntoView {
                     serde_wasm_bindgen::to_value(&EpIdArgs { wt_id, ep_num }).unwrap()
                 )
                 .await,
-                Ty = (EpisodeData, bool),
+                Ty = (EpisodeData, Option<usize>),
                 push_toast,
                 navigate,
-                &format!("/webtoon?wt_id={}&wt_type={}", wt_id.wt_id, wt_id.wt_type)
+                &format!("/webtoon?{}", wt_id.to_query())
             );
             set_episode_data.set(Some(ep_data));
-            fetch_post();
+            fetch_post(1, false);
+
+            // go to the requested panel, or resume where the user stopped
+            let saved = match target {
+                Some(target) => Some(target),
+                None => parse_or_toast!(
+                    invoke(
+                        "get_read_progress",
+                        serde_wasm_bindgen::to_value(&EpIdArgs { wt_id, ep_num }).unwrap()
+                    )
+                    .await,
+                    Ty = Option<ReadProgress>,
+                    push_toast
+                ),
+            };
+            if let Some(saved) = saved.filter(|p| p.panel > 0 || p.offset > 0.0) {
+                pending_restore.set_value(Some(saved));
+                try_restore();
+            }
 
             // close data gathering
             closure.forget();
         });
     };
 
-    let mark_prev_ep_as_read = move |wt_id: WebtoonId, current_ep: usize| {
-        if current_ep <= 1 {
-            return;
-        }
+    let mark_ep_as_read = move |wt_id: WebtoonId, ep_num: usize| {
         spawn_local(async move {
             parse_or_toast!(
                 invoke(
                     "mark_as_read",
-                    serde_wasm_bindgen::to_value(&EpIdArgs {
-                        wt_id,
-                        ep_num: current_ep - 1
-                    })
-                    .unwrap()
+                    serde_wasm_bindgen::to_value(&EpIdArgs { wt_id, ep_num }).unwrap()
                 )
                 .await,
                 Ty = (),
@@ -156,8 +443,9 @@ pub fn EpisodePage() -> impl IntoView {
             );
 
             user_state.update(|us| {
-                us.webtoons.entry(wt_id.wt_id.to_string()).and_modify(|wt| {
-                    wt.episode_seen.insert((current_ep - 1).to_string(), true);
+                us.webtoons.entry(wt_id.store_key()).and_modify(|wt| {
+                    wt.episode_progress
+                        .insert(ep_num.to_string(), ReadProgress::READ);
                     // BECAREFUL! This could be summarized as `SystemTime::now()` but
                     // because of the bad implementation of SystemTime in leptos+tauri as of now
                     // it breaks the app in a weird way (impossible to navigate in the app after this function call).
@@ -172,7 +460,104 @@ pub fn EpisodePage() -> impl IntoView {
         });
     };
 
+    let auto_mark_read = move || {
+        let settings = user_state.reader_settings().get_untracked();
+        if !settings.auto_mark_read || auto_marked.get_value() {
+            return;
+        }
+        let (Some(panels), Some((wt_id, ep_num))) = (
+            panels_ref.get_untracked(),
+            episode_data.with_untracked(|ep_data| {
+                ep_data
+                    .as_ref()
+                    .map(|(ep_data, _)| (ep_data.parent_wt_id, ep_data.number))
+            }),
+        ) else {
+            return;
+        };
+        if end_reached(&panels, settings.end_threshold) {
+            auto_marked.set_value(true);
+            mark_ep_as_read(wt_id, ep_num);
+        }
+    };
+
+    let start_bookmark = move |_| {
+        let Some(focused) = panels_ref
+            .get_untracked()
+            .and_then(|panels| focused_panel(&panels))
+        else {
+            return;
+        };
+        set_bookmark_note.set(String::new());
+        set_bookmark_draft.set(Some(focused));
+    };
+
+    let save_bookmark = move |_| {
+        let (Some((panel, crop)), Some((wt_id, ep_num))) = (
+            bookmark_draft.get_untracked(),
+            episode_data.with_untracked(|ep_data| {
+                ep_data
+                    .as_ref()
+                    .map(|(ep_data, _)| (ep_data.parent_wt_id, ep_data.number))
+            }),
+        ) else {
+            return;
+        };
+        set_bookmark_draft.set(None);
+        let args = serde_wasm_bindgen::to_value(&BookmarkArgs {
+            target: BookmarkTarget {
+                wt_id,
+                ep_num,
+                panel,
+                crop,
+            },
+            note: bookmark_note.get_untracked(),
+        })
+        .unwrap();
+        spawn_local(async move {
+            let bookmark = parse_or_toast!(
+                invoke("add_bookmark", args).await,
+                Ty = Bookmark,
+                push_toast
+            );
+            user_state
+                .bookmarks()
+                .update(|bookmarks| bookmarks.push(bookmark));
+            push_toast.run(Alert::new(
+                "Panel bookmarked",
+                AlertLevel::Success,
+                Some(Duration::from_secs(1)),
+            ));
+        });
+    };
+
     /* Effects */
+    let scroll_handle = window_event_listener(leptos::ev::scroll, move |_| {
+        save_progress();
+        auto_mark_read();
+    });
+    on_cleanup(move || scroll_handle.remove());
+
+    // the backend revalidates outdated comments in the background, reload them once it's done
+    listen_while_mounted("posts_refreshed", move |jsv: JsValue| {
+        #[derive(Deserialize)]
+        struct Event {
+            payload: EpIdArgs,
+        }
+
+        if let (Ok(Event { payload }), Some(Some((ep_data, _)))) = (
+            serde_wasm_bindgen::from_value::<Event>(jsv),
+            episode_data.try_get_untracked(),
+        ) {
+            if payload.wt_id == ep_data.parent_wt_id
+                && payload.ep_num == ep_data.number
+                && posts_page.try_get_value() == Some(1)
+            {
+                fetch_post(1, false);
+            }
+        }
+    });
+
     Effect::new(move |_| {
         let navigate = use_navigate();
 
@@ -181,18 +566,32 @@ pub fn EpisodePage() -> impl IntoView {
                 Ok(WebtoonQueryArgs {
                     wt_id: Some(wt_id),
                     wt_type: Some(wt_type),
-                    prev_ep_read,
+                    source,
+                    lang,
+                    read_ep,
+                    panel,
+                    offset,
                 }),
                 Ok(EpisodeParams { num: Some(ep_num) }),
             ) => {
                 set_episode_data.set(None);
                 set_ep_comments.set(None);
+                set_posts_has_more.set(false);
+                auto_marked.set_value(false);
+                set_bookmark_draft.set(None);
 
-                let webtoon_id = WebtoonId::new(wt_id, wt_type);
-                if prev_ep_read.unwrap_or_default() {
-                    mark_prev_ep_as_read(webtoon_id, ep_num);
+                let webtoon_id =
+                    WebtoonId::from_source(source.unwrap_or_default(), wt_id, wt_type)
+                        .with_language(lang.unwrap_or_default());
+                if let Some(read_ep) = read_ep {
+                    mark_ep_as_read(webtoon_id, read_ep);
                 }
-                fetch_ep_data(webtoon_id, ep_num);
+                let target = panel.map(|panel| ReadProgress {
+                    panel,
+                    offset: offset.unwrap_or_default(),
+                    ..Default::default()
+                });
+                fetch_ep_data(webtoon_id, ep_num, target);
             }
             _ => {
                 push_toast.run(Alert::new(
@@ -223,24 +622,62 @@ pub fn EpisodePage() -> impl IntoView {
                 }>
                     <a href=move || match episode_data.get() {
                         Some(ep_data) => {
-                            format!(
-                                "/webtoon?wt_id={}&wt_type={}",
-                                ep_data.0.parent_wt_id.wt_id,
-                                ep_data.0.parent_wt_id.wt_type,
-                            )
+                            format!("/webtoon?{}", ep_data.0.parent_wt_id.to_query())
                         }
                         None => "/".to_string(),
                     }>
                         <Icon icon=i::IoCaretBackOutline />
                     </a>
+                    <button class="bookmark_btn" title="Bookmark this panel" on:click=start_bookmark>
+                        <Icon icon=i::BiBookmarkPlusRegular />
+                    </button>
                 </div>
-                <div id="panels" on:click=move |_| set_see_back_btn.update(|sbb| *sbb = sbb.not())>
+                <Show when=move || bookmark_draft.get().is_some()>
+                    <div class="bookmark_form">
+                        <p>
+                            {move || {
+                                bookmark_draft
+                                    .get()
+                                    .map(|(panel, crop)| {
+                                        format!(
+                                            "Bookmark panel {}{}",
+                                            panel + 1,
+                                            if crop.is_some() { " (part on screen)" } else { "" },
+                                        )
+                                    })
+                            }}
+                        </p>
+                        <textarea
+                            rows="2"
+                            placeholder="Note (optional)"
+                            prop:value=bookmark_note
+                            on:input:target=move |ev| set_bookmark_note.set(ev.target().value())
+                        ></textarea>
+                        <div>
+                            <button class="btn" on:click=move |_| set_bookmark_draft.set(None)>
+                                "Cancel"
+                            </button>
+                            <button class="btn" on:click=save_bookmark>
+                                "Save"
+                            </button>
+                        </div>
+                    </div>
+                </Show>
+                <div
+                    id="panels"
+                    node_ref=panels_ref
+                    on:click=move |_| set_see_back_btn.update(|sbb| *sbb = sbb.not())
+                >
                     <For
                         each=move || episode_data.get().unwrap().0.panels
                         key=|panel| panel.to_owned()
                         let(panel_url)
                     >
-                        <img src=move || convert_file_src(&panel_url) alt="Episode panel" />
+                        <img
+                            src=move || convert_file_src(&panel_url)
+                            alt="Episode panel"
+                            on:load=move |_| try_restore()
+                        />
                     </For>
                 </div>
                 <div class="author_info">
@@ -279,17 +716,14 @@ pub fn EpisodePage() -> impl IntoView {
                 </div>
                 <div class="action">
                     <Show
-                        when=move || episode_data.get().unwrap().1
+                        when=move || episode_data.get().unwrap().1.is_some()
                         fallback=move || {
                             view! {
                                 <a
                                     href="javascript:void(0)"
                                     on:click=move |_| {
                                         let ep_data = episode_data.get().unwrap().0;
-                                        mark_prev_ep_as_read(
-                                            ep_data.parent_wt_id,
-                                            ep_data.number + 1,
-                                        );
+                                        mark_ep_as_read(ep_data.parent_wt_id, ep_data.number);
                                         push_toast
                                             .run(
                                                 Alert::new(
@@ -309,31 +743,80 @@ pub fn EpisodePage() -> impl IntoView {
                         }
                     >
                         <a href=move || {
-                            let ep_data = episode_data.get().unwrap().0;
+                            let (ep_data, next_ep) = episode_data.get().unwrap();
                             format!(
-                                "/webtoon/episode/{}?wt_id={}&wt_type={}&prev_ep_read=true",
-                                ep_data.number + 1,
-                                ep_data.parent_wt_id.wt_id,
-                                ep_data.parent_wt_id.wt_type,
+                                "/webtoon/episode/{}?{}&read_ep={}",
+                                next_ep.unwrap_or_default(),
+                                ep_data.parent_wt_id.to_query(),
+                                ep_data.number,
                             )
                         }>
                             <div>
                                 <p>"Next episode: "</p>
-                                <p>"Episode " {move || episode_data.get().unwrap().0.number + 1}</p>
+                                <p>
+                                    "Episode "
+                                    {move || episode_data.get().unwrap().1.unwrap_or_default()}
+                                </p>
                             </div>
                             <Icon icon=i::AiCaretRightOutlined />
                         </a>
                     </Show>
 
                 </div>
+                <ReaderSettingsEditor />
                 <div class="comments">
-                    <h3>"Top Comments" <Icon icon=i::BiCommentDetailRegular /></h3>
+                    <h3>"Comments" <Icon icon=i::BiCommentDetailRegular /></h3>
+                    <div class="sort">
+                        {[PostSort::Top, PostSort::Newest]
+                            .into_iter()
+                            .map(|sort| {
+                                view! {
+                                    <button
+                                        class=move || {
+                                            format!(
+                                                "chip {}",
+                                                if post_sort.get() == sort { "active" } else { "" },
+                                            )
+                                        }
+                                        on:click=move |_| change_post_sort(sort)
+                                    >
+                                        {sort.to_string()}
+                                    </button>
+                                }
+                            })
+                            .collect_view()}
+                        <button
+                            class="filters"
+                            on:click=move |_| show_mute_rules.update(|smr| *smr = smr.not())
+                        >
+                            <Icon icon=i::BiFilterAltRegular />
+                        </button>
+                        <button class="refresh" on:click=move |_| fetch_post(1, true)>
+                            <Icon icon=i::MdiReload />
+                        </button>
+                    </div>
+                    <Show when=move || show_mute_rules.get()>
+                        <MuteRulesEditor on_saved=Callback::new(move |_| {
+                            show_mute_rules.set(false);
+                            if ep_comments.get_untracked().is_some() {
+                                fetch_post(1, false);
+                            }
+                        }) />
+                    </Show>
+                    <Show when=move || { posts_hidden.get() > 0 }>
+                        <p class="hidden_count">
+                            {move || format!("{} comments hidden by your filters", posts_hidden.get())}
+                        </p>
+                    </Show>
+                    <Show when=move || posts_stale.get()>
+                        <p class="stale">"Saved comments, they may be outdated"</p>
+                    </Show>
 
                     <Show
                         when=move || ep_comments.get().is_some()
                         fallback=move || {
                             view! {
-                                <button on:click=move |_| fetch_post()>"Click to fetch"</button>
+                                <button on:click=move |_| fetch_post(1, false)>"Click to fetch"</button>
                             }
                         }
                     >
@@ -344,6 +827,11 @@ pub fn EpisodePage() -> impl IntoView {
                         >
                             <PostComponent post_data />
                         </For>
+                        <Show when=move || posts_has_more.get()>
+                            <button on:click=move |_| fetch_post(posts_page.get_value() + 1, false)>
+                                "Load more comments"
+                            </button>
+                        </Show>
                     </Show>
                 </div>
             </div>
@@ -353,6 +841,93 @@ pub fn EpisodePage() -> impl IntoView {
 
 #[component]
 fn PostComponent(post_data: Post) -> impl IntoView {
+    /* context */
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    let (replies, set_replies) = signal(None::<Vec<Post>>);
+    let (replies_cursor, set_replies_cursor) = signal(None::<String>);
+    let show_replies = RwSignal::new(false);
+    let reply_count = post_data.reply_count;
+
+    /* Handlers */
+    // fetch the replies following the ones already loaded
+    let fetch_replies = Callback::new({
+        let post_data = post_data.clone();
+        move |()| {
+            let cursor = replies_cursor.get_untracked();
+            let args = serde_wasm_bindgen::to_value(&RepliesArgs {
+                wt_id: post_data.wt_id,
+                ep_num: post_data.ep_num,
+                post_id: &post_data.id,
+                cursor: cursor.as_deref(),
+            })
+            .unwrap();
+            spawn_local(async move {
+                let batch = parse_or_toast!(
+                    invoke("get_post_replies", args).await,
+                    Ty = PostBatch,
+                    push_toast
+                );
+                set_replies_cursor.set(batch.next_cursor);
+                set_replies.update(|replies| {
+                    replies.get_or_insert_with(Vec::new).extend(batch.posts);
+                });
+            });
+        }
+    });
+
+    let toggle_replies = move |_| {
+        show_replies.update(|sr| *sr = sr.not());
+        if show_replies.get_untracked() && replies.get_untracked().is_none() {
+            fetch_replies.run(());
+        }
+    };
+
+    view! {
+        <div class="comment">
+            <PostContent post_data />
+            <Show when=move || { reply_count > 0 }>
+                <button class="replies_toggle" on:click=toggle_replies>
+                    {move || match show_replies.get() {
+                        true => "Hide replies".to_string(),
+                        false => format!("{reply_count} replies"),
+                    }}
+                </button>
+            </Show>
+            <Show when=move || show_replies.get()>
+                <div class="replies">
+                    <Show
+                        when=move || replies.get().is_some()
+                        fallback=|| view! { <p class="loading">"Loading replies..."</p> }
+                    >
+                        <For
+                            each=move || replies.get().unwrap()
+                            key=|post| post.id.clone()
+                            let(post_data)
+                        >
+                            <div class="reply">
+                                <PostContent post_data />
+                            </div>
+                        </For>
+                        <Show when=move || replies_cursor.get().is_some()>
+                            <button
+                                class="replies_toggle"
+                                on:click=move |_| fetch_replies.run(())
+                            >
+                                "Load more replies"
+                            </button>
+                        </Show>
+                    </Show>
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn PostContent(post_data: Post) -> impl IntoView {
     let show_spoiler = RwSignal::new(false);
     let date = move || {
         // js_sys bindings calls JavaScript's standard lib
@@ -362,25 +937,23 @@ fn PostComponent(post_data: Post) -> impl IntoView {
     };
 
     view! {
-        <div class="comment">
-            <p class="poster">{post_data.poster_name}</p>
-            <p class="date">{date}</p>
-            <p class="content" on:click=move |_| show_spoiler.update(|s_sp| *s_sp = s_sp.not())>
-                {move || match (post_data.is_spoiler, show_spoiler.get()) {
-                    (true, true) | (false, _) => Some(post_data.content.to_owned()),
-                    (true, false) => Some("**Click to show spoiler**".to_string()),
-                }}
+        <p class="poster">{post_data.poster_name}</p>
+        <p class="date">{date}</p>
+        <p class="content" on:click=move |_| show_spoiler.update(|s_sp| *s_sp = s_sp.not())>
+            {move || match (post_data.is_spoiler, show_spoiler.get()) {
+                (true, true) | (false, _) => Some(post_data.content.to_owned()),
+                (true, false) => Some("**Click to show spoiler**".to_string()),
+            }}
+        </p>
+        <div class="votes">
+            <p>
+                <Icon icon=i::AiLikeFilled />
+                {post_data.upvotes}
+            </p>
+            <p>
+                <Icon icon=i::AiDislikeFilled />
+                {post_data.downvotes}
             </p>
-            <div class="votes">
-                <p>
-                    <Icon icon=i::AiLikeFilled />
-                    {post_data.upvotes}
-                </p>
-                <p>
-                    <Icon icon=i::AiDislikeFilled />
-                    {post_data.downvotes}
-                </p>
-            </div>
         </div>
     }
 }
@@ -0,0 +1,236 @@
+use std::str::FromStr;
+
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Style;
+use leptos_router::{hooks::use_params, params::Params};
+
+use icondata as i;
+use leptos_icons::Icon;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    components::{
+        spinner::Spinner,
+        webtoon::{StandaloneWebtoon, Webtoon},
+    },
+    parse_or_toast,
+    utility::types::{
+        Alert, AlertLevel, Genre, GenreSortOrder, SearchResult, WebtoonSearchInfo, WtType,
+    },
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Params, PartialEq, Debug, Clone)]
+struct GenreParams {
+    name: Option<String>,
+}
+
+#[derive(Serialize)]
+struct FetchGenreArgs {
+    genre: Genre,
+    wt_type: WtType,
+    sort_order: GenreSortOrder,
+    page: usize,
+}
+
+#[component]
+pub fn GenrePage() -> impl IntoView {
+    /* url params */
+    let params_args = use_params::<GenreParams>();
+
+    /* context */
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    let genre = Memo::new(move |_| {
+        params_args.get().ok().and_then(|p| p.name).map(|name| {
+            let name = urlencoding::decode(&name)
+                .map(|n| n.to_string())
+                .unwrap_or(name);
+            Genre::from_str(&name).unwrap()
+        })
+    });
+    let (wt_type, set_wt_type) = signal(WtType::Original);
+    let (sort_order, set_sort_order) = signal(GenreSortOrder::default());
+
+    let (webtoons, set_webtoons) = signal(None::<Vec<WebtoonSearchInfo>>);
+    let (has_more, set_has_more) = signal(false);
+    let current_page = StoredValue::new(1_usize);
+    let loading = StoredValue::new(false);
+    // incremented each time the listing is reset, so that pages of an outdated listing are dropped
+    let generation = StoredValue::new(0_usize);
+
+    /* Handlers */
+    let fetch_genre_page = move |page: usize| {
+        let Some(genre) = genre.get_untracked() else {
+            return;
+        };
+        match page {
+            1 => generation.update_value(|g| *g += 1),
+            _ if loading.get_value() => return,
+            _ => (),
+        }
+        loading.set_value(true);
+
+        let fetch_generation = generation.get_value();
+        let args = serde_wasm_bindgen::to_value(&FetchGenreArgs {
+            genre,
+            wt_type: wt_type.get_untracked(),
+            sort_order: sort_order.get_untracked(),
+            page,
+        })
+        .unwrap();
+        spawn_local(async move {
+            async {
+                let genre_page = parse_or_toast!(
+                    invoke("get_genre_webtoons", args).await,
+                    Ty = SearchResult,
+                    push_toast
+                );
+                if generation.get_value() != fetch_generation {
+                    return;
+                }
+
+                current_page.set_value(page);
+                set_has_more.set(genre_page.has_more);
+                match page {
+                    1 => set_webtoons.set(Some(genre_page.webtoons)),
+                    _ => set_webtoons.update(|wts| {
+                        wts.get_or_insert_default().extend(genre_page.webtoons)
+                    }),
+                }
+            }
+            .await;
+
+            if generation.get_value() == fetch_generation {
+                loading.set_value(false);
+            }
+        });
+    };
+
+    let load_next_page = move |ev: leptos::ev::Event| {
+        let container = event_target::<leptos::web_sys::Element>(&ev);
+        let near_bottom = container.scroll_top() + container.client_height()
+            >= container.scroll_height() - 300;
+        if near_bottom && has_more.get_untracked() {
+            fetch_genre_page(current_page.get_value() + 1);
+        }
+    };
+
+    /* Effects */
+    Effect::new(move |_| {
+        // refetch the listing from the start whenever one of its parameters changes
+        if genre.get().is_none() {
+            push_toast.run(Alert::new(
+                "Failed to parse genre, please return home",
+                AlertLevel::Warning,
+                None,
+            ));
+            return;
+        }
+        wt_type.track();
+        sort_order.track();
+
+        set_webtoons.set(None);
+        set_has_more.set(false);
+        fetch_genre_page(1);
+    });
+
+    view! {
+        <Style>{include_str!("genre.css")}</Style>
+        <div id="genre_page">
+            <header>
+                <a href="/">
+                    <Icon icon=i::IoCaretBackOutline />
+                </a>
+                <h1>{move || genre.get().map(|g| g.to_string()).unwrap_or_default()}</h1>
+            </header>
+            <div class="filters">
+                <div class="chips">
+                    {[WtType::Original, WtType::Canvas]
+                        .into_iter()
+                        .map(|t| {
+                            view! {
+                                <button
+                                    class=move || {
+                                        format!(
+                                            "chip {}",
+                                            if wt_type.get() == t { "active" } else { "" },
+                                        )
+                                    }
+                                    on:click=move |_| set_wt_type.set(t)
+                                >
+                                    {t.to_string()}
+                                </button>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+                <select on:change:target=move |ev| {
+                    let order = match ev.target().value().as_str() {
+                        "Likes" => GenreSortOrder::Likes,
+                        "Update" => GenreSortOrder::Update,
+                        _ => GenreSortOrder::Popularity,
+                    };
+                    set_sort_order.set(order);
+                }>
+                    {[GenreSortOrder::Popularity, GenreSortOrder::Likes, GenreSortOrder::Update]
+                        .into_iter()
+                        .map(|order| {
+                            view! {
+                                <option
+                                    value=format!("{order:?}")
+                                    selected=move || sort_order.get() == order
+                                >
+                                    {order.to_string()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+            </div>
+            <div id="webtoons" on:scroll=load_next_page>
+                <Show
+                    when=move || webtoons.get().is_some()
+                    fallback=|| {
+                        view! {
+                            {(1..=10)
+                                .map(|_| {
+                                    view! { <StandaloneWebtoon /> }
+                                })
+                                .collect::<Vec<_>>()}
+                        }
+                    }
+                >
+                    <Show
+                        when=move || !webtoons.get().unwrap_or_default().is_empty()
+                        fallback=|| {
+                            view! { <p>"Nothing to show!"</p> }
+                        }
+                    >
+                        <For
+                            each=move || webtoons.get().unwrap_or_default()
+                            key=|wt| (wt.id.store_key(), wt.thumbnail.clone())
+                            let(wt: WebtoonSearchInfo)
+                        >
+                            <Webtoon wt_info=wt.clone() is_local=true />
+                        </For>
+                        <Show when=move || has_more.get()>
+                            <div class="load_more">
+                                <Spinner />
+                            </div>
+                        </Show>
+                    </Show>
+                </Show>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,256 @@
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Style;
+
+use icondata as i;
+use leptos_icons::Icon;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    components::spinner::Spinner,
+    parse_or_toast,
+    utility::{
+        duration_label,
+        types::{self, Alert, AlertLevel, HistoryEntry},
+    },
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+    async fn invoke_without_args(cmd: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct HistoryArgs {
+    page: usize,
+}
+
+#[derive(Serialize)]
+struct EntryIdArgs {
+    entry_id: i64,
+}
+
+fn local_date(unix_secs: u64) -> js_sys::Date {
+    // js_sys bindings calls JavaScript's standard lib, which knows the user's timezone
+    js_sys::Date::new(&JsValue::from_f64(unix_secs as f64 * 1000.0))
+}
+
+fn day_label(unix_secs: u64) -> String {
+    local_date(unix_secs)
+        .to_date_string()
+        .as_string()
+        .unwrap_or_default()
+}
+
+fn time_label(unix_secs: u64) -> String {
+    let date = local_date(unix_secs);
+    format!("{:02}:{:02}", date.get_hours(), date.get_minutes())
+}
+
+/// entries are sorted from the most recent, so a day's entries are contiguous
+fn group_by_day(entries: Vec<HistoryEntry>) -> Vec<(String, Vec<HistoryEntry>)> {
+    let mut days: Vec<(String, Vec<HistoryEntry>)> = vec![];
+    for entry in entries {
+        let day = day_label(entry.opened_at);
+        match days.last_mut() {
+            Some((last_day, day_entries)) if *last_day == day => day_entries.push(entry),
+            _ => days.push((day, vec![entry])),
+        }
+    }
+    days
+}
+
+#[component]
+pub fn HistoryPage() -> impl IntoView {
+    /* context */
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    let (entries, set_entries) = signal(None::<Vec<HistoryEntry>>);
+    let (has_more, set_has_more) = signal(false);
+    let current_page = StoredValue::new(1_usize);
+    let loading = StoredValue::new(false);
+    // clearing the whole history takes two clicks
+    let (clear_armed, set_clear_armed) = signal(false);
+
+    let days = Memo::new(move |_| group_by_day(entries.get().unwrap_or_default()));
+
+    /* Handlers */
+    let fetch_history_page = move |page: usize| {
+        if loading.get_value() {
+            return;
+        }
+        loading.set_value(true);
+
+        let args = serde_wasm_bindgen::to_value(&HistoryArgs { page }).unwrap();
+        spawn_local(async move {
+            async {
+                let history_page = parse_or_toast!(
+                    invoke("get_history", args).await,
+                    Ty = types::HistoryPage,
+                    push_toast
+                );
+
+                current_page.set_value(history_page.page);
+                set_has_more.set(history_page.has_more);
+                match page {
+                    1 => set_entries.set(Some(history_page.entries)),
+                    _ => set_entries.update(|entries| {
+                        entries.get_or_insert_default().extend(history_page.entries)
+                    }),
+                }
+            }
+            .await;
+
+            loading.set_value(false);
+        });
+    };
+
+    let load_next_page = move |ev: leptos::ev::Event| {
+        let container = event_target::<leptos::web_sys::Element>(&ev);
+        let near_bottom =
+            container.scroll_top() + container.client_height() >= container.scroll_height() - 300;
+        if near_bottom && has_more.get_untracked() {
+            fetch_history_page(current_page.get_value() + 1);
+        }
+    };
+
+    let delete_entry = move |entry_id: i64| {
+        spawn_local(async move {
+            parse_or_toast!(
+                invoke(
+                    "delete_history_entry",
+                    serde_wasm_bindgen::to_value(&EntryIdArgs { entry_id }).unwrap()
+                )
+                .await,
+                Ty = (),
+                push_toast
+            );
+            set_entries.update(|entries| {
+                if let Some(entries) = entries {
+                    entries.retain(|entry| entry.id != entry_id);
+                }
+            });
+        });
+    };
+
+    let clear_history = move |_| {
+        if !clear_armed.get_untracked() {
+            set_clear_armed.set(true);
+            return;
+        }
+        set_clear_armed.set(false);
+        spawn_local(async move {
+            parse_or_toast!(
+                invoke_without_args("clear_history").await,
+                Ty = (),
+                push_toast
+            );
+            set_entries.set(Some(vec![]));
+            set_has_more.set(false);
+            push_toast.run(Alert::new(
+                "Reading history cleared",
+                AlertLevel::Success,
+                None,
+            ));
+        });
+    };
+
+    /* Effects */
+    Effect::new(move |_| fetch_history_page(1));
+
+    view! {
+        <Style>{include_str!("history.css")}</Style>
+        <div id="history_page">
+            <header>
+                <a href="/">
+                    <Icon icon=i::IoCaretBackOutline />
+                </a>
+                <h1>"History"</h1>
+                <button
+                    class=move || format!("btn {}", if clear_armed.get() { "active" } else { "" })
+                    on:click=clear_history
+                    disabled=move || entries.get().unwrap_or_default().is_empty()
+                >
+                    {move || match clear_armed.get() {
+                        true => "Click again to clear",
+                        false => "Clear all",
+                    }}
+                </button>
+            </header>
+            <div id="history" on:scroll=load_next_page>
+                <Show
+                    when=move || entries.get().is_some()
+                    fallback=|| view! { <Spinner /> }
+                >
+                    <Show
+                        when=move || !days.get().is_empty()
+                        fallback=|| {
+                            view! { <p>"Nothing read yet!"</p> }
+                        }
+                    >
+                        <For
+                            each=move || days.get()
+                            key=|(day, day_entries)| {
+                                (day.clone(), day_entries.iter().map(|e| e.id).collect::<Vec<_>>())
+                            }
+                            let((day, day_entries))
+                        >
+                            <section>
+                                <h3>{day}</h3>
+                                {day_entries
+                                    .into_iter()
+                                    .map(|entry| {
+                                        let entry_id = entry.id;
+                                        view! {
+                                            <div class=format!(
+                                                "entry {}",
+                                                if entry.finished_at.is_some() { "finished" } else { "" },
+                                            )>
+                                                <a
+                                                    href=format!(
+                                                        "/webtoon/episode/{}?{}",
+                                                        entry.episode,
+                                                        entry.wt_id.to_query(),
+                                                    )
+                                                    title=format!("Read again episode {}", entry.episode)
+                                                >
+                                                    <span class="time">{time_label(entry.opened_at)}</span>
+                                                    <span class="title">{entry.title}</span>
+                                                    <span class="episode">
+                                                        {format!("#{} {}", entry.episode, entry.episode_title)}
+                                                    </span>
+                                                    <span class="duration">
+                                                        {duration_label(entry.duration)}
+                                                        {entry.finished_at.map(|_| " · finished")}
+                                                    </span>
+                                                </a>
+                                                <button
+                                                    title="Remove from history"
+                                                    on:click=move |_| delete_entry(entry_id)
+                                                >
+                                                    <Icon icon=i::BiTrashAltRegular />
+                                                </button>
+                                            </div>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </section>
+                        </For>
+                        <Show when=move || has_more.get()>
+                            <div class="load_more">
+                                <Spinner />
+                            </div>
+                        </Show>
+                    </Show>
+                </Show>
+            </div>
+        </div>
+    }
+}
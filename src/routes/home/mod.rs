@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::fmt::Display;
 use std::time::Duration;
 
@@ -12,13 +13,21 @@ use leptos_icons::Icon;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+use crate::components::local_library::LocalLibrary;
+use crate::components::shelves::ShelvesEditor;
+use crate::components::spinner::Spinner;
 use crate::components::webtoon::{StandaloneWebtoon, Webtoon};
 use crate::parse_or_toast;
+use crate::utility::{listen_while_mounted, touch_client_y};
 use crate::utility::store::{
-    LoadingState, UserData, UserDataStoreFields, UserRecommendations,
-    UserRecommendationsStoreFields, UserWebtoon,
+    LibraryOverview, LoadingState, NewEpisodes, UpdatingToday, UpdatingTodayStoreFields, UserData,
+    UserDataStoreFields, UserRecommendations, UserRecommendationsStoreFields, UserWebtoon,
+};
+use crate::utility::types::{
+    Alert, AlertLevel, Genre, PersonalRecommendation, RankMovement, RankedWebtoon, RankingKind,
+    RecommendationSection, SearchFilter, SearchResult, TodayWebtoon, WebtoonId, WebtoonSearchInfo,
+    Weekday,
 };
-use crate::utility::types::{Alert, AlertLevel, WebtoonId, WebtoonSearchInfo};
 
 #[wasm_bindgen]
 extern "C" {
@@ -29,6 +38,26 @@ extern "C" {
 #[derive(Serialize, Deserialize)]
 struct SearchWtArgs<'a> {
     query: &'a str,
+    filter: SearchFilter,
+    page: usize,
+    genre: Option<Genre>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecommendationsArgs {
+    today: Weekday,
+    force_refresh: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersonalRecommendationsArgs {
+    limit: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RankingArgs {
+    kind: RankingKind,
+    genre: Option<Genre>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -36,10 +65,18 @@ struct FetchWtInfoArgs {
     id: WebtoonId,
 }
 
+/// when filtering by genre, the number of empty pages fetched in a row before waiting for the user to scroll
+const MAX_EMPTY_SEARCH_PAGES: usize = 5;
+/// number of webtoons in the "Picked for you" section
+const PICKED_FOR_YOU_LEN: usize = 10;
+/// how far (in px) the recommendations must be pulled down to be refreshed
+const PULL_TO_REFRESH_DISTANCE: f64 = 120.0;
+
 #[derive(Debug, Clone, PartialEq)]
 enum AppMode {
     My,
     Recommandation,
+    Charts,
     Search(RwSignal<String>),
 }
 
@@ -51,79 +88,402 @@ impl Display for AppMode {
             match self {
                 AppMode::My => "my",
                 AppMode::Recommandation => "recommandation",
+                AppMode::Charts => "charts",
                 AppMode::Search(_) => "search",
             }
         )
     }
 }
 
+/// How the subscriptions of the "My" tab are sorted
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LibrarySort {
+    LastRead,
+    Title,
+    Unread,
+    LastUpdate,
+}
+
+impl LibrarySort {
+    const ALL: [Self; 4] = [
+        LibrarySort::LastRead,
+        LibrarySort::Title,
+        LibrarySort::Unread,
+        LibrarySort::LastUpdate,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            LibrarySort::LastRead => "Last read",
+            LibrarySort::Title => "Title",
+            LibrarySort::Unread => "Unread episodes",
+            LibrarySort::LastUpdate => "Last update",
+        }
+    }
+
+    fn sort(self, webtoons: &mut [UserWebtoon], library: &LibraryOverview) {
+        let unread_count = |uwt: &UserWebtoon| {
+            library
+                .get(&uwt.id.store_key())
+                .map_or(0, |entry| entry.unread_count)
+        };
+        // webtoons without a known date come last
+        let last_update = |uwt: &UserWebtoon| {
+            library
+                .get(&uwt.id.store_key())
+                .and_then(|entry| entry.latest_episode_at)
+        };
+        let title = |uwt: &UserWebtoon| uwt.title.to_lowercase();
+
+        match self {
+            LibrarySort::LastRead => webtoons.sort_by(|a, b| b.last_seen.cmp(&a.last_seen)),
+            LibrarySort::Title => webtoons.sort_by_key(title),
+            LibrarySort::Unread => webtoons.sort_by(|a, b| {
+                unread_count(b)
+                    .cmp(&unread_count(a))
+                    .then_with(|| title(a).cmp(&title(b)))
+            }),
+            LibrarySort::LastUpdate => webtoons.sort_by(|a, b| last_update(b).cmp(&last_update(a))),
+        }
+    }
+}
+
 #[component]
 pub fn Home() -> impl IntoView {
     /* Global state */
     let user_state = expect_context::<Store<UserData>>();
     let user_rec_state = expect_context::<Store<UserRecommendations>>();
+    let updating_today_state = expect_context::<Store<UpdatingToday>>();
     let push_toast =
         use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
 
+    /* states */
+    let (webtoons, set_webtoons) = signal::<Vec<WebtoonSearchInfo>>(vec![]);
+    let (picked_for_you, set_picked_for_you) = signal(None::<Vec<PersonalRecommendation>>);
+    let (app_mode, set_app_mode) = signal(AppMode::My);
+    let (new_episodes, set_new_episodes) = signal(NewEpisodes::default());
+    let (library, set_library) = signal(LibraryOverview::default());
+
+    // `None` shows every subscription
+    let library_shelf = RwSignal::new(None::<String>);
+    let library_tag = RwSignal::new(None::<String>);
+    let library_sort = RwSignal::new(LibrarySort::LastRead);
+    let show_shelves_editor = RwSignal::new(false);
+
+    let library_tags = Memo::new(move |_| {
+        user_state.webtoons().with(|webtoons| {
+            webtoons
+                .values()
+                .flat_map(|uwt| uwt.tags.iter().cloned())
+                .collect::<BTreeSet<String>>()
+        })
+    });
+
     let user_webtoons = Memo::new(move |_| {
         let mut uwt = user_state
             .webtoons()
             .get()
             .into_values()
+            .filter(|uwt| library_shelf.with(|shelf| shelf.is_none() || uwt.shelf == *shelf))
+            .filter(|uwt| {
+                library_tag.with(|tag| tag.as_ref().is_none_or(|tag| uwt.tags.contains(tag)))
+            })
             .collect::<Vec<UserWebtoon>>();
-        uwt.sort_by(|uwta, uwtb| uwtb.last_seen.cmp(&uwta.last_seen));
+        library.with(|library| library_sort.get().sort(&mut uwt, library));
         uwt.into_iter()
             .map(|uwt| uwt.into())
             .collect::<Vec<WebtoonSearchInfo>>()
     });
 
-    /* states */
-    let (webtoons, set_webtoons) = signal::<Vec<WebtoonSearchInfo>>(vec![]);
-    let (app_mode, set_app_mode) = signal(AppMode::My);
+    let search_filter = RwSignal::new(SearchFilter::All);
+    let search_genre = RwSignal::new(None::<Genre>);
+    let search_genres = RwSignal::new(BTreeSet::<Genre>::new());
+    let (search_has_more, set_search_has_more) = signal(false);
+    let search_page = StoredValue::new(1_usize);
+    let search_loading = StoredValue::new(false);
+    // incremented on each new search, so that pages of an outdated search are dropped
+    let search_generation = StoredValue::new(0_usize);
+
+    let chart_kind = RwSignal::new(RankingKind::default());
+    let chart_genre = RwSignal::new(None::<Genre>);
+    let (charts, set_charts) = signal(None::<Vec<RankedWebtoon>>);
+    let charts_loaded_for = StoredValue::new(None::<(RankingKind, Option<Genre>)>);
 
     /* handlers */
+    let load_library = move || {
+        spawn_local(async move {
+            let overview = parse_or_toast!(
+                invoke("get_library_overview", JsValue::null()).await,
+                Ty = LibraryOverview,
+                push_toast
+            );
+            set_library.set(overview);
+        });
+    };
+
+    let export_backup = move || {
+        spawn_local(async move {
+            let path = parse_or_toast!(
+                invoke("export_backup", JsValue::null()).await,
+                Ty = String,
+                push_toast
+            );
+            push_toast.run(Alert::new(
+                &format!("Backup saved to {path}"),
+                AlertLevel::Success,
+                None,
+            ));
+        });
+    };
+
     let load_user_wt = move || {
         if user_state.loading_state().get_untracked() == LoadingState::Completed
             && app_mode.get_untracked() == AppMode::My
         {
             set_webtoons.set(user_webtoons.get_untracked());
+            load_library();
+        }
+    };
+
+    let load_picked_for_you = move |force_refresh: bool| {
+        if !force_refresh && picked_for_you.get_untracked().is_some() {
+            return;
+        }
+
+        spawn_local(async move {
+            let picked = parse_or_toast!(
+                invoke(
+                    "get_personal_recommandations",
+                    serde_wasm_bindgen::to_value(&PersonalRecommendationsArgs {
+                        limit: PICKED_FOR_YOU_LEN
+                    })
+                    .unwrap()
+                )
+                .await,
+                Ty = Vec<PersonalRecommendation>,
+                push_toast
+            );
+            set_picked_for_you.set(Some(picked));
+        });
+    };
+
+    let refresh_recommendations = move || {
+        if user_rec_state.loading_state().get_untracked() == LoadingState::Loading {
+            return;
+        }
+        user_rec_state.loading_state().set(LoadingState::Loading);
+        load_picked_for_you(true);
+
+        spawn_local(async move {
+            async {
+                let sections = parse_or_toast!(
+                    invoke(
+                        "get_homepage_recommandations",
+                        serde_wasm_bindgen::to_value(&RecommendationsArgs {
+                            today: Weekday::today(),
+                            force_refresh: true,
+                        })
+                        .unwrap()
+                    )
+                    .await,
+                    Ty = Vec<RecommendationSection>,
+                    push_toast
+                );
+                user_rec_state.set(UserRecommendations {
+                    sections,
+                    loading_state: LoadingState::Completed,
+                });
+            }
+            .await;
+
+            // on failure, the previous recommendations are kept
+            if user_rec_state.loading_state().get_untracked() == LoadingState::Loading {
+                user_rec_state
+                    .loading_state()
+                    .set(LoadingState::Completed);
+            }
+        });
+    };
+
+    let pull_start_y = StoredValue::new(None::<f64>);
+    let on_pull_start = move |ev: leptos::ev::TouchEvent| {
+        let container = event_target::<leptos::web_sys::Element>(&ev);
+        pull_start_y.set_value(match container.scroll_top() <= 0 {
+            true => touch_client_y(&ev),
+            false => None,
+        });
+    };
+    let on_pull_end = move |ev: leptos::ev::TouchEvent| {
+        if let (Some(start_y), Some(end_y)) = (pull_start_y.get_value(), touch_client_y(&ev)) {
+            if end_y - start_y >= PULL_TO_REFRESH_DISTANCE {
+                refresh_recommendations();
+            }
         }
+        pull_start_y.set_value(None);
     };
 
-    let load_user_rec = move || {
-        if user_rec_state.loading_state().get_untracked() == LoadingState::Completed
-            && app_mode.get_untracked() == AppMode::Recommandation
+    let load_charts = move || {
+        let chart_params = (chart_kind.get_untracked(), chart_genre.get_untracked());
+        if app_mode.get_untracked() != AppMode::Charts
+            || charts_loaded_for.get_value().as_ref() == Some(&chart_params)
         {
-            let recommendations = user_rec_state.webtoons().get_untracked();
-            set_webtoons.set(recommendations);
+            return;
         }
+        set_charts.set(None);
+
+        let args = serde_wasm_bindgen::to_value(&RankingArgs {
+            kind: chart_params.0,
+            genre: chart_params.1.clone(),
+        })
+        .unwrap();
+        spawn_local(async move {
+            let ranking = parse_or_toast!(
+                invoke("get_ranking", args).await,
+                Ty = Vec<RankedWebtoon>,
+                push_toast
+            );
+            // the user may have picked another chart in the meantime
+            if (chart_kind.get_untracked(), chart_genre.get_untracked()) == chart_params {
+                charts_loaded_for.set_value(Some(chart_params));
+                set_charts.set(Some(ranking));
+            }
+        });
     };
 
-    let search_webtoons = move || {
+    let search_webtoons = move |page: usize| {
         if let AppMode::Search(query) = app_mode.get_untracked() {
             let query = query.get_untracked().trim().to_string();
             if query.is_empty() || query.len() <= 2 {
                 return;
             }
+            match page {
+                1 => search_generation.update_value(|g| *g += 1),
+                _ if search_loading.get_value() => return,
+                _ => (),
+            }
+            search_loading.set_value(true);
+
+            let generation = search_generation.get_value();
+            let (filter, genre) = (search_filter.get_untracked(), search_genre.get_untracked());
             spawn_local(async move {
-                let args = serde_wasm_bindgen::to_value(&SearchWtArgs { query: &query }).unwrap();
-
-                push_toast.run(Alert::new(
-                    "Searching...",
-                    AlertLevel::Info,
-                    Some(Duration::from_millis(400)),
-                ));
-                let webtoons = parse_or_toast!(
-                    invoke("search_webtoon", args).await,
-                    Ty = Vec<WebtoonSearchInfo>,
-                    push_toast
-                );
-                set_webtoons.set(webtoons);
+                async {
+                    if page == 1 {
+                        push_toast.run(Alert::new(
+                            "Searching...",
+                            AlertLevel::Info,
+                            Some(Duration::from_millis(400)),
+                        ));
+                    }
+
+                    for page in page..page + MAX_EMPTY_SEARCH_PAGES {
+                        let args = serde_wasm_bindgen::to_value(&SearchWtArgs {
+                            query: &query,
+                            filter,
+                            page,
+                            genre: genre.clone(),
+                        })
+                        .unwrap();
+                        let search_result = parse_or_toast!(
+                            invoke("search_webtoon", args).await,
+                            Ty = SearchResult,
+                            push_toast
+                        );
+                        if search_generation.get_value() != generation {
+                            return;
+                        }
+
+                        if genre.is_none() {
+                            search_genres.update(|sg| {
+                                sg.extend(
+                                    search_result
+                                        .webtoons
+                                        .iter()
+                                        .filter_map(|wt| wt.genre.clone()),
+                                )
+                            });
+                        }
+                        search_page.set_value(page);
+                        set_search_has_more.set(search_result.has_more);
+
+                        let is_empty = search_result.webtoons.is_empty();
+                        match page {
+                            1 => set_webtoons.set(search_result.webtoons),
+                            _ => set_webtoons.update(|wts| wts.extend(search_result.webtoons)),
+                        }
+
+                        // a genre filtered page can be empty while the next ones aren't
+                        if !is_empty || !search_result.has_more {
+                            break;
+                        }
+                    }
+                }
+                .await;
+
+                if search_generation.get_value() == generation {
+                    search_loading.set_value(false);
+                }
             });
         }
     };
 
+    let load_next_search_page = move |ev: leptos::ev::Event| {
+        let container = event_target::<leptos::web_sys::Element>(&ev);
+        let near_bottom = container.scroll_top() + container.client_height()
+            >= container.scroll_height() - 300;
+        if near_bottom
+            && matches!(app_mode.get_untracked(), AppMode::Search(_))
+            && search_has_more.get_untracked()
+        {
+            search_webtoons(search_page.get_value() + 1);
+        }
+    };
+
     /* Effects */
+    // the backend looks for new episodes of the subscriptions in the background
+    spawn_local(async move {
+        let found = parse_or_toast!(
+            invoke("get_new_episodes", JsValue::null()).await,
+            Ty = NewEpisodes,
+            push_toast
+        );
+        set_new_episodes.set(found);
+    });
+    listen_while_mounted("new_episodes", move |jsv: JsValue| {
+        #[derive(Deserialize)]
+        struct Event {
+            payload: NewEpisodes,
+        }
+
+        if let Ok(Event { payload }) = serde_wasm_bindgen::from_value::<Event>(jsv) {
+            set_new_episodes.set(payload);
+            load_library();
+        }
+    });
+
+    // the shown subscriptions follow the chosen shelf, tag and sort
+    Effect::new(move |_| {
+        let uwt = user_webtoons.get();
+        if user_state.loading_state().get_untracked() == LoadingState::Completed
+            && app_mode.get_untracked() == AppMode::My
+        {
+            set_webtoons.set(uwt);
+        }
+    });
+
+    // a removed shelf or tag can't filter the library anymore
+    Effect::new(move |_| {
+        let shelves = user_state.shelves().get();
+        if library_shelf
+            .with_untracked(|shelf| shelf.as_ref().is_some_and(|s| !shelves.contains(s)))
+        {
+            library_shelf.set(None);
+        }
+        let tags = library_tags.get();
+        if library_tag.with_untracked(|tag| tag.as_ref().is_some_and(|t| !tags.contains(t))) {
+            library_tag.set(None);
+        }
+    });
+
     Effect::new(move |_| match user_state.loading_state().get() {
         LoadingState::Loading => (),
         LoadingState::Completed => load_user_wt(),
@@ -141,15 +501,19 @@ pub fn Home() -> impl IntoView {
                 AlertLevel::Info,
                 Some(Duration::from_millis(300)),
             ));
-            if let AppMode::Recommandation = app_mode.get_untracked() {
-                load_user_rec();
-            }
         }
         LoadingState::Error(e) => {
             push_toast.run(Alert::new(&e, AlertLevel::Error, None));
         }
     });
 
+    // the shelf is just hidden without webtoons, the failure is told to the user
+    Effect::new(move |_| {
+        if let LoadingState::Error(e) = updating_today_state.loading_state().get() {
+            push_toast.run(Alert::new(&e, AlertLevel::Error, None));
+        }
+    });
+
     let search_timeout = StoredValue::new(None::<TimeoutHandle>);
     let before_search_app_mode = StoredValue::new(AppMode::My);
     Effect::new(move || match app_mode.get() {
@@ -159,7 +523,11 @@ pub fn Home() -> impl IntoView {
         }
         AppMode::Recommandation => {
             before_search_app_mode.set_value(AppMode::Recommandation);
-            load_user_rec();
+            load_picked_for_you(false);
+        }
+        AppMode::Charts => {
+            before_search_app_mode.set_value(AppMode::Charts);
+            load_charts();
         }
         AppMode::Search(q) => {
             // search one second after the user stopped typping
@@ -170,9 +538,11 @@ pub fn Home() -> impl IntoView {
                 set_app_mode.set(before_search_app_mode.get_value());
                 return;
             }
+            search_genre.set(None);
+            search_genres.set(BTreeSet::new());
 
             let timeout_handle =
-                set_timeout_with_handle(search_webtoons, Duration::from_millis(500))
+                set_timeout_with_handle(move || search_webtoons(1), Duration::from_millis(500))
                     .expect("No timeout");
             search_timeout.set_value(Some(timeout_handle));
         }
@@ -199,6 +569,7 @@ pub fn Home() -> impl IntoView {
                         prop:value=move || match app_mode.get() {
                             AppMode::My => RwSignal::new(String::new()),
                             AppMode::Recommandation => RwSignal::new(String::new()),
+                            AppMode::Charts => RwSignal::new(String::new()),
                             AppMode::Search(q_signal) => q_signal,
                         }
                     />
@@ -212,42 +583,520 @@ pub fn Home() -> impl IntoView {
                 </div>
 
             </div>
-            <div id="webtoons">
-                <Show
-                    when=move || {
-                        !webtoons.get().is_empty()
-                            || (app_mode.get() == AppMode::My
-                                && user_state.loading_state().get() == LoadingState::Completed)
-                            || (app_mode.get() == AppMode::Recommandation
-                                && user_rec_state.loading_state().get() == LoadingState::Completed)
+            <Show when=move || matches!(app_mode.get(), AppMode::Search(_))>
+                <div id="search_filters">
+                    <div class="chips">
+                        {[SearchFilter::All, SearchFilter::Originals, SearchFilter::Canvas]
+                            .into_iter()
+                            .map(|filter| {
+                                view! {
+                                    <button
+                                        class=move || {
+                                            format!(
+                                                "chip {}",
+                                                if search_filter.get() == filter { "active" } else { "" },
+                                            )
+                                        }
+                                        on:click=move |_| {
+                                            search_filter.set(filter);
+                                            search_webtoons(1);
+                                        }
+                                    >
+                                        {filter.to_string()}
+                                    </button>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                    <Show when=move || !search_genres.get().is_empty()>
+                        <div class="chips">
+                            <button
+                                class=move || {
+                                    format!(
+                                        "chip {}",
+                                        if search_genre.get().is_none() { "active" } else { "" },
+                                    )
+                                }
+                                on:click=move |_| {
+                                    search_genre.set(None);
+                                    search_webtoons(1);
+                                }
+                            >
+                                "Any genre"
+                            </button>
+                            <For
+                                each={move || search_genres.get().into_iter().collect::<Vec<_>>()}
+                                key=|genre| genre.clone()
+                                let(genre: Genre)
+                            >
+                                <button
+                                    class={
+                                        let genre = genre.clone();
+                                        move || {
+                                            format!(
+                                                "chip {}",
+                                                if search_genre.get().as_ref() == Some(&genre) {
+                                                    "active"
+                                                } else {
+                                                    ""
+                                                },
+                                            )
+                                        }
+                                    }
+                                    on:click={
+                                        let genre = genre.clone();
+                                        move |_| {
+                                            search_genre.set(Some(genre.clone()));
+                                            search_webtoons(1);
+                                        }
+                                    }
+                                >
+                                    {genre.to_string()}
+                                </button>
+                            </For>
+                        </div>
+                    </Show>
+                </div>
+            </Show>
+            <Show when=move || {
+                matches!(app_mode.get(), AppMode::My | AppMode::Recommandation)
+                    && !updating_today_state.webtoons().get().is_empty()
+            }>
+                <div id="updating_today">
+                    <h3>"Updating today"</h3>
+                    <div class="shelf">
+                        <For
+                            each=move || updating_today_state.webtoons().get()
+                            key=|twt| twt.webtoon.id.store_key()
+                            let(twt: TodayWebtoon)
+                        >
+                            <div class=format!(
+                                "shelf_item {}",
+                                if twt.subscribed { "subscribed" } else { "" },
+                            )>
+                                <Webtoon wt_info=twt.webtoon.clone() is_local=true />
+                            </div>
+                        </For>
+                    </div>
+                </div>
+            </Show>
+            <Show when=move || app_mode.get() == AppMode::My>
+                <div id="library_links">
+                    <a class="chip" href="/history">
+                        <Icon icon=i::BiHistoryRegular />
+                        "History"
+                    </a>
+                    <a class="chip" href="/stats">
+                        <Icon icon=i::BiStatsRegular />
+                        "Statistics"
+                    </a>
+                    <a class="chip" href="/bookmarks">
+                        <Icon icon=i::BiBookmarkRegular />
+                        "Bookmarks"
+                    </a>
+                    <button class="chip" on:click=move |_| export_backup()>
+                        <Icon icon=i::BiDownloadRegular />
+                        "Export backup"
+                    </button>
+                </div>
+                <div id="library_filters">
+                    <div class="chips">
+                        <button
+                            class=move || {
+                                format!(
+                                    "chip {}",
+                                    if library_shelf.get().is_none() { "active" } else { "" },
+                                )
+                            }
+                            on:click=move |_| library_shelf.set(None)
+                        >
+                            "All"
+                        </button>
+                        <For
+                            each=move || user_state.shelves().get()
+                            key=|shelf| shelf.clone()
+                            let(shelf: String)
+                        >
+                            <button
+                                class={
+                                    let shelf = shelf.clone();
+                                    move || {
+                                        format!(
+                                            "chip {}",
+                                            if library_shelf.get().as_ref() == Some(&shelf) {
+                                                "active"
+                                            } else {
+                                                ""
+                                            },
+                                        )
+                                    }
+                                }
+                                on:click={
+                                    let shelf = shelf.clone();
+                                    move |_| library_shelf.set(Some(shelf.clone()))
+                                }
+                            >
+                                {shelf.clone()}
+                            </button>
+                        </For>
+                        <button
+                            class=move || {
+                                format!(
+                                    "chip {}",
+                                    if show_shelves_editor.get() { "active" } else { "" },
+                                )
+                            }
+                            title="Edit shelves"
+                            on:click=move |_| show_shelves_editor.update(|show| *show = !*show)
+                        >
+                            <Icon icon=i::BiEditAltRegular />
+                        </button>
+                    </div>
+                    <Show when=move || show_shelves_editor.get()>
+                        <ShelvesEditor />
+                    </Show>
+                    <Show when=move || !library_tags.get().is_empty()>
+                        <div class="chips">
+                            <button
+                                class=move || {
+                                    format!(
+                                        "chip {}",
+                                        if library_tag.get().is_none() { "active" } else { "" },
+                                    )
+                                }
+                                on:click=move |_| library_tag.set(None)
+                            >
+                                "Any tag"
+                            </button>
+                            <For
+                                each={move || library_tags.get().into_iter().collect::<Vec<_>>()}
+                                key=|tag| tag.clone()
+                                let(tag: String)
+                            >
+                                <button
+                                    class={
+                                        let tag = tag.clone();
+                                        move || {
+                                            format!(
+                                                "chip {}",
+                                                if library_tag.get().as_ref() == Some(&tag) {
+                                                    "active"
+                                                } else {
+                                                    ""
+                                                },
+                                            )
+                                        }
+                                    }
+                                    on:click={
+                                        let tag = tag.clone();
+                                        move |_| library_tag.set(Some(tag.clone()))
+                                    }
+                                >
+                                    {format!("#{tag}")}
+                                </button>
+                            </For>
+                        </div>
+                    </Show>
+                    <select on:change:target=move |ev| {
+                        let label = ev.target().value();
+                        if let Some(sort) = LibrarySort::ALL
+                            .into_iter()
+                            .find(|sort| sort.label() == label)
+                        {
+                            library_sort.set(sort);
+                        }
+                    }>
+                        {LibrarySort::ALL
+                            .into_iter()
+                            .map(|sort| {
+                                view! {
+                                    <option
+                                        value=sort.label()
+                                        selected=move || library_sort.get() == sort
+                                    >
+                                        {format!("Sort by: {}", sort.label())}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+                <LocalLibrary />
+            </Show>
+            <Show when=move || app_mode.get() == AppMode::Charts>
+                <div id="charts_filters">
+                    <div class="chips">
+                        {[
+                            RankingKind::Trending,
+                            RankingKind::Popular,
+                            RankingKind::Originals,
+                            RankingKind::Canvas,
+                            RankingKind::RisingCanvas,
+                        ]
+                            .into_iter()
+                            .map(|kind| {
+                                view! {
+                                    <button
+                                        class=move || {
+                                            format!(
+                                                "chip {}",
+                                                if chart_kind.get() == kind { "active" } else { "" },
+                                            )
+                                        }
+                                        on:click=move |_| {
+                                            chart_kind.set(kind);
+                                            load_charts();
+                                        }
+                                    >
+                                        {kind.to_string()}
+                                    </button>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                    <select on:change:target=move |ev| {
+                        let value = ev.target().value();
+                        chart_genre
+                            .set(
+                                Genre::known().into_iter().find(|g| format!("{g:?}") == value),
+                            );
+                        load_charts();
+                    }>
+                        <option value="" selected=move || chart_genre.get().is_none()>
+                            "All genres"
+                        </option>
+                        {Genre::known()
+                            .into_iter()
+                            .map(|genre| {
+                                let value = format!("{genre:?}");
+                                let label = genre.to_string();
+                                view! {
+                                    <option
+                                        value=value
+                                        selected=move || chart_genre.get().as_ref() == Some(&genre)
+                                    >
+                                        {label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+            </Show>
+            <Show
+                when=move || app_mode.get() != AppMode::Charts
+                fallback=move || {
+                    view! {
+                        <div id="webtoons">
+                            <Show
+                                when=move || charts.get().is_some()
+                                fallback=|| {
+                                    view! {
+                                        {(1..=10)
+                                            .map(|_| {
+                                                view! { <StandaloneWebtoon /> }
+                                            })
+                                            .collect::<Vec<_>>()}
+                                    }
+                                }
+                            >
+                                <For
+                                    each=move || charts.get().unwrap_or_default()
+                                    key=|rwt| (rwt.rank, rwt.webtoon.id.store_key())
+                                    let(rwt: RankedWebtoon)
+                                >
+                                    <div class="ranked">
+                                        <span class=format!(
+                                            "rank {}",
+                                            match rwt.movement {
+                                                RankMovement::Up(_) => "up",
+                                                RankMovement::Down(_) => "down",
+                                                RankMovement::New => "new",
+                                                RankMovement::Same => "",
+                                            },
+                                        )>
+                                            {format!("#{}", rwt.rank)}
+                                            {match rwt.movement {
+                                                RankMovement::Up(delta) => format!(" ▲{delta}"),
+                                                RankMovement::Down(delta) => format!(" ▼{delta}"),
+                                                RankMovement::New => " NEW".to_string(),
+                                                RankMovement::Same => String::new(),
+                                            }}
+                                        </span>
+                                        <Webtoon wt_info=rwt.webtoon.clone() is_local=true />
+                                    </div>
+                                </For>
+                            </Show>
+                        </div>
                     }
-                    fallback=|| {
+                }
+            >
+                <Show
+                    when=move || app_mode.get() != AppMode::Recommandation
+                    fallback=move || {
                         view! {
-                            {(1..=10)
-                                .map(|_| {
-                                    view! { <StandaloneWebtoon /> }
-                                })
-                                .collect::<Vec<_>>()}
+                            <div
+                                id="recommendations"
+                                on:touchstart=on_pull_start
+                                on:touchend=on_pull_end
+                            >
+                                <div class="refresh">
+                                    <button
+                                        on:click=move |_| refresh_recommendations()
+                                        disabled=move || {
+                                            user_rec_state.loading_state().get()
+                                                == LoadingState::Loading
+                                        }
+                                    >
+                                        <Icon icon=i::MdiReload />
+                                        "Pull or click to refresh"
+                                    </button>
+                                </div>
+                                <Show
+                                    when=move || {
+                                        user_rec_state.loading_state().get()
+                                            != LoadingState::Loading
+                                    }
+                                    fallback=|| {
+                                        view! {
+                                            <div class="webtoons_grid">
+                                                {(1..=10)
+                                                    .map(|_| {
+                                                        view! { <StandaloneWebtoon /> }
+                                                    })
+                                                    .collect::<Vec<_>>()}
+                                            </div>
+                                        }
+                                    }
+                                >
+                                    <Show when=move || {
+                                        !picked_for_you.get().unwrap_or_default().is_empty()
+                                    }>
+                                        <section class="picked_for_you">
+                                            <h3>"Picked for you"</h3>
+                                            <div class="webtoons_grid">
+                                                <For
+                                                    each=move || {
+                                                        picked_for_you.get().unwrap_or_default()
+                                                    }
+                                                    key=|rec| rec.webtoon.id.store_key()
+                                                    let(rec: PersonalRecommendation)
+                                                >
+                                                    <div class="picked">
+                                                        <Webtoon wt_info=rec.webtoon is_local=true />
+                                                        <p class="reason">{rec.reason}</p>
+                                                    </div>
+                                                </For>
+                                            </div>
+                                        </section>
+                                    </Show>
+                                    <For
+                                        each=move || user_rec_state.sections().get()
+                                        key=|section| section.kind.to_string()
+                                        let(section: RecommendationSection)
+                                    >
+                                        <section>
+                                            <h3>{section.kind.to_string()}</h3>
+                                            <div class="webtoons_grid">
+                                                {section
+                                                    .webtoons
+                                                    .into_iter()
+                                                    .map(|wt| {
+                                                        view! { <Webtoon wt_info=wt is_local=true /> }
+                                                    })
+                                                    .collect_view()}
+                                            </div>
+                                        </section>
+                                    </For>
+                                </Show>
+                            </div>
                         }
                     }
                 >
-                    <Show
-                        when=move || { !webtoons.get().is_empty() }
-                        fallback=|| {
-                            view! { <p>"Nothing to show!"</p> }
-                        }
-                    >
-                        <For
-                            each=move || webtoons.get()
-                            key=|wt| (wt.id.wt_id, wt.thumbnail.clone())
-                            let(wt: WebtoonSearchInfo)
+                    <div id="webtoons" on:scroll=load_next_search_page>
+                        <Show
+                            when=move || {
+                                !webtoons.get().is_empty()
+                                    || (app_mode.get() == AppMode::My
+                                        && user_state.loading_state().get() == LoadingState::Completed)
+                            }
+                            fallback=|| {
+                                view! {
+                                    {(1..=10)
+                                        .map(|_| {
+                                            view! { <StandaloneWebtoon /> }
+                                        })
+                                        .collect::<Vec<_>>()}
+                                }
+                            }
                         >
-                            <Webtoon wt_info=wt.clone() is_local=true />
-                        </For>
-                    </Show>
+                            <Show
+                                when=move || { !webtoons.get().is_empty() }
+                                fallback=|| {
+                                    view! { <p>"Nothing to show!"</p> }
+                                }
+                            >
+                                <For
+                                    each=move || webtoons.get()
+                                    key=|wt| (wt.id.store_key(), wt.thumbnail.clone())
+                                    let(wt: WebtoonSearchInfo)
+                                >
+                                    <div class="subscription">
+                                        <Show when=move || {
+                                            new_episodes.with(|eps| eps.contains_key(&wt.id.store_key()))
+                                        }>
+                                            <span class="new_episodes">"NEW"</span>
+                                        </Show>
+                                        <Webtoon
+                                            wt_info=wt.clone()
+                                            is_local=true
+                                            badge=Signal::derive(move || {
+                                                library
+                                                    .with(|lib| {
+                                                        lib.get(&wt.id.store_key())
+                                                            .map_or(0, |entry| entry.unread_count)
+                                                    })
+                                            })
+                                        />
+                                        {move || {
+                                            library
+                                                .with(|lib| lib.get(&wt.id.store_key()).cloned())
+                                                .and_then(|entry| {
+                                                    entry
+                                                        .next_unread
+                                                        .map(|ep_num| {
+                                                            view! {
+                                                                <a
+                                                                    class="btn continue"
+                                                                    href=format!(
+                                                                        "/webtoon/episode/{ep_num}?{}",
+                                                                        entry.id.to_query(),
+                                                                    )
+                                                                    title=entry
+                                                                        .latest_episode_date
+                                                                        .map(|date| format!("Latest episode: {date}"))
+                                                                >
+                                                                    {format!("Continue #{ep_num}")}
+                                                                </a>
+                                                            }
+                                                        })
+                                                })
+                                        }}
+                                    </div>
+                                </For>
+                                <Show when=move || {
+                                    matches!(app_mode.get(), AppMode::Search(_)) && search_has_more.get()
+                                }>
+                                    <div class="load_more">
+                                        <Spinner />
+                                    </div>
+                                </Show>
+                            </Show>
 
+                        </Show>
+                    </div>
                 </Show>
-            </div>
+            </Show>
             <nav>
                 <button
                     on:click=move |_| set_app_mode.set(AppMode::My)
@@ -279,6 +1128,21 @@ pub fn Home() -> impl IntoView {
                     <Icon icon=i::BiWorldRegular />
                     "Explore"
                 </button>
+                <button
+                    on:click=move |_| set_app_mode.set(AppMode::Charts)
+                    class=move || {
+                        format!(
+                            "btn {}",
+                            match app_mode.get() {
+                                AppMode::Charts => "active",
+                                _ => "",
+                            },
+                        )
+                    }
+                >
+                    <Icon icon=i::BiBarChartAltRegular />
+                    "Charts"
+                </button>
             </nav>
         </main>
     }
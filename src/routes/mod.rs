@@ -1,4 +1,8 @@
+pub mod bookmarks;
 pub mod creator;
 pub mod episode;
+pub mod genre;
+pub mod history;
 pub mod home;
+pub mod stats;
 pub mod webtoon;
@@ -0,0 +1,251 @@
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Style;
+
+use icondata as i;
+use leptos_icons::Icon;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    components::{
+        chart::{Bar, BarChart, RankChart},
+        spinner::Spinner,
+    },
+    parse_or_toast,
+    utility::{
+        duration_label,
+        types::{Alert, AlertLevel, PeriodStats, ReadingStats},
+    },
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct StatsArgs {
+    utc_offset: i64,
+}
+
+/// What the period charts show
+#[derive(Clone, Copy, PartialEq)]
+enum Measure {
+    Episodes,
+    Time,
+}
+
+/// `label_every` periods are labeled with the day of the month of their start, the last one always is
+fn period_bars(periods: &[PeriodStats], measure: Measure, label_every: usize) -> Vec<Bar> {
+    periods
+        .iter()
+        .enumerate()
+        .map(|(i, period)| {
+            let label = match (periods.len() - 1 - i) % label_every {
+                0 => {
+                    // js_sys bindings calls JavaScript's standard lib, which knows the user's timezone
+                    let start = js_sys::Date::new(&JsValue::from_f64(period.start as f64 * 1000.0));
+                    format!("{}/{}", start.get_date(), start.get_month() + 1)
+                }
+                _ => String::new(),
+            };
+            let (value, value_label) = match measure {
+                Measure::Episodes => (period.episodes as f64, period.episodes.to_string()),
+                Measure::Time => (period.time as f64, duration_label(period.time)),
+            };
+            Bar {
+                label,
+                value,
+                value_label,
+            }
+        })
+        .collect()
+}
+
+#[component]
+pub fn StatsPage() -> impl IntoView {
+    /* context */
+    let push_toast =
+        use_context::<Callback<Alert>>().expect("expected a 'set_alerts' context provided");
+
+    /* states */
+    let (stats, set_stats) = signal(None::<ReadingStats>);
+    let (measure, set_measure) = signal(Measure::Episodes);
+
+    let day_bars = Signal::derive(move || {
+        stats.with(|stats| {
+            stats
+                .as_ref()
+                .map(|stats| period_bars(&stats.days, measure.get(), 7))
+                .unwrap_or_default()
+        })
+    });
+    let week_bars = Signal::derive(move || {
+        stats.with(|stats| {
+            stats
+                .as_ref()
+                .map(|stats| period_bars(&stats.weeks, measure.get(), 3))
+                .unwrap_or_default()
+        })
+    });
+    let genre_bars = Signal::derive(move || {
+        stats.with(|stats| {
+            stats
+                .iter()
+                .flat_map(|stats| &stats.genres)
+                .map(|genre| Bar {
+                    label: genre.genre.to_string(),
+                    value: genre.episodes as f64,
+                    value_label: genre.episodes.to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    let creator_bars = Signal::derive(move || {
+        stats.with(|stats| {
+            stats
+                .iter()
+                .flat_map(|stats| &stats.creators)
+                .map(|creator| Bar {
+                    label: creator.creator.clone(),
+                    value: creator.episodes as f64,
+                    value_label: creator.episodes.to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    let completion_bars = Signal::derive(move || {
+        stats.with(|stats| {
+            stats
+                .iter()
+                .flat_map(|stats| &stats.completion)
+                .map(|series| Bar {
+                    label: series.title.clone(),
+                    value: series.read as f64 / series.total.max(1) as f64,
+                    value_label: format!("{}/{}", series.read, series.total),
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+
+    /* Effects */
+    Effect::new(move |_| {
+        // getTimezoneOffset is the number of minutes to add to the local time to get UTC
+        let utc_offset = -js_sys::Date::new_0().get_timezone_offset() as i64;
+        spawn_local(async move {
+            let reading_stats = parse_or_toast!(
+                invoke(
+                    "get_reading_stats",
+                    serde_wasm_bindgen::to_value(&StatsArgs { utc_offset }).unwrap()
+                )
+                .await,
+                Ty = ReadingStats,
+                push_toast
+            );
+            set_stats.set(Some(reading_stats));
+        });
+    });
+
+    view! {
+        <Style>{include_str!("stats.css")}</Style>
+        <div id="stats_page">
+            <header>
+                <a href="/">
+                    <Icon icon=i::IoCaretBackOutline />
+                </a>
+                <h1>"Statistics"</h1>
+            </header>
+            <Show
+                when=move || stats.get().is_some()
+                fallback=|| {
+                    view! {
+                        <div class="loading">
+                            <Spinner />
+                        </div>
+                    }
+                }
+            >
+                <div id="stats">
+                    <div class="totals">
+                        <div>
+                            <strong>
+                                {move || stats.get().map(|s| s.total_episodes).unwrap_or_default()}
+                            </strong>
+                            <span>"episodes read"</span>
+                        </div>
+                        <div>
+                            <strong>
+                                {move || {
+                                    duration_label(
+                                        stats.get().map(|s| s.total_time).unwrap_or_default(),
+                                    )
+                                }}
+                            </strong>
+                            <span>"spent reading"</span>
+                        </div>
+                        <div>
+                            <strong>
+                                {move || stats.get().map(|s| s.current_streak).unwrap_or_default()}
+                            </strong>
+                            <span>"days streak"</span>
+                        </div>
+                        <div>
+                            <strong>
+                                {move || stats.get().map(|s| s.longest_streak).unwrap_or_default()}
+                            </strong>
+                            <span>"longest streak"</span>
+                        </div>
+                    </div>
+                    <div class="chips">
+                        {[(Measure::Episodes, "Episodes"), (Measure::Time, "Time spent")]
+                            .into_iter()
+                            .map(|(m, label)| {
+                                view! {
+                                    <button
+                                        class=move || {
+                                            format!(
+                                                "chip {}",
+                                                if measure.get() == m { "active" } else { "" },
+                                            )
+                                        }
+                                        on:click=move |_| set_measure.set(m)
+                                    >
+                                        {label}
+                                    </button>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                    <section>
+                        <h3>"Last 30 days"</h3>
+                        <BarChart bars=day_bars />
+                    </section>
+                    <section>
+                        <h3>"Last 12 weeks"</h3>
+                        <BarChart bars=week_bars />
+                    </section>
+                    <Show when=move || !genre_bars.get().is_empty()>
+                        <section>
+                            <h3>"Most read genres"</h3>
+                            <RankChart bars=genre_bars />
+                        </section>
+                    </Show>
+                    <Show when=move || !creator_bars.get().is_empty()>
+                        <section>
+                            <h3>"Most read creators"</h3>
+                            <RankChart bars=creator_bars />
+                        </section>
+                    </Show>
+                    <Show when=move || !completion_bars.get().is_empty()>
+                        <section>
+                            <h3>"Completion"</h3>
+                            <RankChart bars=completion_bars max=1.0 />
+                        </section>
+                    </Show>
+                </div>
+            </Show>
+        </div>
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use leptos::prelude::*;
 use leptos::task::spawn_local;
@@ -15,11 +18,15 @@ use wasm_bindgen::prelude::*;
 use icondata as i;
 use leptos_icons::Icon;
 
+use crate::components::shelves::ShelfPicker;
 use crate::components::waiting_screen::WaitingScreen;
 use crate::utility::convert_file_src;
-use crate::utility::store::{LoadingState, UserData, UserDataStoreFields};
+use crate::utility::store::{
+    EpisodeSelection, LoadingState, ReadProgress, UserData, UserDataStoreFields,
+};
 use crate::utility::types::{
-    Alert, AlertLevel, DownloadState, EpisodePreview, Schedule, WebtoonId, WebtoonInfo, WtType,
+    Alert, AlertLevel, DownloadState, EpisodePreview, Language, Schedule, SourceId, WebtoonId,
+    WebtoonInfo, WtType,
 };
 use crate::{parse_or_navigate, parse_or_toast};
 
@@ -48,10 +55,18 @@ struct SubWtInfoArgs {
     webtoon_id: WebtoonId,
 }
 
+#[derive(Serialize)]
+struct MarkEpsArgs {
+    wt_id: WebtoonId,
+    selection: EpisodeSelection,
+}
+
 #[derive(Params, PartialEq, Debug, Clone)]
 struct WebtoonQueryArgs {
     wt_id: Option<usize>,
     wt_type: Option<WtType>,
+    source: Option<SourceId>,
+    lang: Option<Language>,
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +75,14 @@ enum EpOrder {
     Oldest,
 }
 
+/// What the episodes are selected for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SelectMode {
+    Off,
+    Delete,
+    ReadState,
+}
+
 #[component]
 pub fn WebtoonPage() -> impl IntoView {
     /* query */
@@ -76,8 +99,8 @@ pub fn WebtoonPage() -> impl IntoView {
     let (dl_state, set_dl_state) = signal(DownloadState::Idle);
     let (ep_order, set_ep_order) = signal(EpOrder::Latest);
 
-    let (delete_mode, set_delete_mode) = signal(false);
-    let eps2delete = RwSignal::new(HashSet::<usize>::new());
+    let (select_mode, set_select_mode) = signal(SelectMode::Off);
+    let selected_eps = RwSignal::new(HashSet::<usize>::new());
 
     let is_subscribed = Memo::new(move |_| {
         webtoon_info
@@ -86,7 +109,7 @@ pub fn WebtoonPage() -> impl IntoView {
                 user_state
                     .webtoons()
                     .get()
-                    .contains_key(&wt.id.wt_id.to_string())
+                    .contains_key(&wt.id.store_key())
             })
             .unwrap_or_default()
     });
@@ -141,12 +164,12 @@ pub fn WebtoonPage() -> impl IntoView {
                         match is_sub {
                             true => {
                                 user_state.update(|us| {
-                                    us.webtoons.remove(&wt.id.wt_id.to_string());
+                                    us.webtoons.remove(&wt.id.store_key());
                                 });
                             }
                             false => {
                                 user_state.update(|us| {
-                                    us.webtoons.insert(wt.id.wt_id.to_string(), wt.into());
+                                    us.webtoons.insert(wt.id.store_key(), wt.into());
                                 });
                             }
                         };
@@ -243,7 +266,7 @@ pub fn WebtoonPage() -> impl IntoView {
                     push_toast
                 );
                 user_state.update(|state| {
-                    state.webtoons.remove(&wt.id.wt_id.to_string());
+                    state.webtoons.remove(&wt.id.store_key());
                 });
                 set_wt_info.set(None);
                 push_toast.run(Alert::new(
@@ -255,13 +278,13 @@ pub fn WebtoonPage() -> impl IntoView {
         }
     };
     let handle_ep_delete = move |_| {
-        let eps2d = eps2delete.get_untracked().into_iter().collect::<Vec<_>>();
+        let eps2d = selected_eps.get_untracked().into_iter().collect::<Vec<_>>();
         match (
-            delete_mode.get_untracked(),
+            select_mode.get_untracked(),
             webtoon_info.get_untracked(),
             eps2d.is_empty(),
         ) {
-            (true, Some(wt), false) => {
+            (SelectMode::Delete, Some(wt), false) => {
                 spawn_local(async move {
                     // delete webtoon episodes
                     let wt_wo_deleted_eps = parse_or_toast!(
@@ -278,12 +301,62 @@ pub fn WebtoonPage() -> impl IntoView {
                         push_toast
                     );
                     set_wt_info.set(Some(wt_wo_deleted_eps));
-                    set_delete_mode.set(false);
-                    eps2delete.set(HashSet::new());
+                    set_select_mode.set(SelectMode::Off);
+                    selected_eps.set(HashSet::new());
                 });
             }
-            (true, _, _) => set_delete_mode.set(false),
-            (false, _, _) => set_delete_mode.set(true),
+            (SelectMode::Delete, _, _) => set_select_mode.set(SelectMode::Off),
+            (_, _, _) => {
+                selected_eps.set(HashSet::new());
+                set_select_mode.set(SelectMode::Delete);
+            }
+        }
+    };
+    let toggle_read_state_mode = move |_| match select_mode.get_untracked() {
+        SelectMode::ReadState => set_select_mode.set(SelectMode::Off),
+        _ => {
+            selected_eps.set(HashSet::new());
+            set_select_mode.set(SelectMode::ReadState);
+        }
+    };
+    let mark_episodes = move |selection: EpisodeSelection, read: bool| {
+        let Some(wt) = webtoon_info.get_untracked() else {
+            return;
+        };
+        spawn_local(async move {
+            let episode_progress = parse_or_toast!(
+                invoke(
+                    match read {
+                        true => "mark_episodes_read",
+                        false => "mark_episodes_unread",
+                    },
+                    serde_wasm_bindgen::to_value(&MarkEpsArgs {
+                        wt_id: wt.id,
+                        selection
+                    })
+                    .unwrap()
+                )
+                .await,
+                Ty = HashMap<String, ReadProgress>,
+                push_toast
+            );
+            user_state.update(|us| {
+                us.webtoons
+                    .entry(wt.id.store_key())
+                    .and_modify(|uwt| uwt.episode_progress = episode_progress);
+            });
+            set_select_mode.set(SelectMode::Off);
+            selected_eps.set(HashSet::new());
+        });
+    };
+    let mark_selected = move |read: bool| {
+        let selected = selected_eps.get_untracked().into_iter().collect::<Vec<_>>();
+        mark_episodes(EpisodeSelection::Set(selected), read);
+    };
+    // every episode from the first one to the last selected one
+    let mark_up_to_selected = move |_| {
+        if let Some(&last) = selected_eps.get_untracked().iter().max() {
+            mark_episodes(EpisodeSelection::Range { from: 0, to: last }, true);
         }
     };
 
@@ -295,8 +368,12 @@ pub fn WebtoonPage() -> impl IntoView {
             Ok(WebtoonQueryArgs {
                 wt_id: Some(wt_id),
                 wt_type: Some(wt_type),
+                source,
+                lang,
             }) => {
-                let webtoon_id = WebtoonId::new(wt_id, wt_type);
+                let webtoon_id =
+                    WebtoonId::from_source(source.unwrap_or_default(), wt_id, wt_type)
+                        .with_language(lang.unwrap_or_default());
                 fetch_wt_info(webtoon_id);
             }
             Ok(WebtoonQueryArgs { wt_id: None, .. }) => {
@@ -413,9 +490,26 @@ pub fn WebtoonPage() -> impl IntoView {
                             .unwrap()
                             .genres
                             .into_iter()
-                            .map(|g| view! { <div class="genre">{move || g.to_string()}</div> })
+                            .map(|g| {
+                                view! {
+                                    <a
+                                        class="genre"
+                                        href=format!(
+                                            "/genre/{}",
+                                            urlencoding::encode(&g.to_string()),
+                                        )
+                                    >
+                                        {g.to_string()}
+                                    </a>
+                                }
+                            })
                             .collect_view()}
                     </div>
+                    <Show when=move || is_subscribed.get()>
+                        {move || {
+                            webtoon_info.get().map(|wt| view! { <ShelfPicker wt_id=wt.id /> })
+                        }}
+                    </Show>
 
                     <Show when=move || episodes.get().is_some()>
                         <div id="episodes">
@@ -424,10 +518,25 @@ pub fn WebtoonPage() -> impl IntoView {
                                     {move || episodes.get().unwrap().len()} " episodes"
                                 </p>
                                 <div>
+                                    <Show when=move || is_subscribed.get()>
+                                        <button
+                                            on:click=toggle_read_state_mode
+                                            title="Mark episodes as read or unread"
+                                        >
+                                            {move || match select_mode.get() {
+                                                SelectMode::ReadState => {
+                                                    view! { <Icon icon=i::BiXRegular /> }
+                                                }
+                                                _ => view! { <Icon icon=i::BiCheckDoubleRegular /> },
+                                            }}
+                                        </button>
+                                    </Show>
                                     <button on:click=handle_ep_delete>
-                                        {move || match delete_mode.get() {
-                                            true => view! { <Icon icon=i::BiCheckRegular /> },
-                                            false => view! { <Icon icon=i::BiEditAltRegular /> },
+                                        {move || match select_mode.get() {
+                                            SelectMode::Delete => {
+                                                view! { <Icon icon=i::BiCheckRegular /> }
+                                            }
+                                            _ => view! { <Icon icon=i::BiEditAltRegular /> },
                                         }}
                                     </button>
                                     <button on:click=toggle_ep_order>
@@ -449,6 +558,47 @@ pub fn WebtoonPage() -> impl IntoView {
                                 </div>
 
                             </header>
+                            <Show when=move || select_mode.get() == SelectMode::ReadState>
+                                <div class="read_state_actions">
+                                    <button
+                                        class="btn"
+                                        disabled=move || selected_eps.get().is_empty()
+                                        on:click=move |_| mark_selected(true)
+                                    >
+                                        "Read"
+                                    </button>
+                                    <button
+                                        class="btn"
+                                        disabled=move || selected_eps.get().is_empty()
+                                        on:click=move |_| mark_selected(false)
+                                    >
+                                        "Unread"
+                                    </button>
+                                    <button
+                                        class="btn"
+                                        disabled=move || selected_eps.get().is_empty()
+                                        on:click=mark_up_to_selected
+                                        title="Mark as read every episode up to the last selected one"
+                                    >
+                                        {move || match selected_eps.get().iter().max() {
+                                            Some(last) => format!("Read up to #{last}"),
+                                            None => "Read up to here".to_string(),
+                                        }}
+                                    </button>
+                                    <button
+                                        class="btn"
+                                        on:click=move |_| mark_episodes(EpisodeSelection::All, true)
+                                    >
+                                        "All read"
+                                    </button>
+                                    <button
+                                        class="btn"
+                                        on:click=move |_| mark_episodes(EpisodeSelection::All, false)
+                                    >
+                                        "All unread"
+                                    </button>
+                                </div>
+                            </Show>
 
                             <For
                                 each=move || episodes.get().unwrap()
@@ -456,20 +606,24 @@ pub fn WebtoonPage() -> impl IntoView {
                                 let(episode)
                             >
                                 <Episode
-                                    seen=match (webtoon_info.get(), is_subscribed.get()) {
+                                    progress=match (webtoon_info.get(), is_subscribed.get()) {
                                         (Some(wt), true) => {
                                             user_state
                                                 .webtoons()
                                                 .get()
-                                                .get(&wt.id.wt_id.to_string())
+                                                .get(&wt.id.store_key())
                                                 .unwrap()
-                                                .episode_seen
-                                                .contains_key(&episode.number.to_string())
+                                                .episode_progress
+                                                .get(&episode.number.to_string())
+                                                .copied()
+                                                .unwrap_or_default()
                                         }
-                                        _ => false,
+                                        _ => ReadProgress::default(),
                                     }
-                                    delete_mode
-                                    eps2delete
+                                    selecting=Signal::derive(move || {
+                                        select_mode.get() != SelectMode::Off
+                                    })
+                                    selected=selected_eps
                                     episode
                                 />
 
@@ -485,50 +639,54 @@ pub fn WebtoonPage() -> impl IntoView {
 #[component]
 fn Episode(
     episode: EpisodePreview,
-    eps2delete: RwSignal<HashSet<usize>>,
-    delete_mode: ReadSignal<bool>,
-    seen: bool,
+    selected: RwSignal<HashSet<usize>>,
+    selecting: Signal<bool>,
+    progress: ReadProgress,
 ) -> impl IntoView {
-    let toggle_mark2delete = move || match eps2delete.get_untracked().contains(&episode.number) {
-        true => eps2delete.update(|ep2d| {
-            ep2d.remove(&episode.number);
+    let toggle_selected = move || match selected.get_untracked().contains(&episode.number) {
+        true => selected.update(|selected| {
+            selected.remove(&episode.number);
         }),
-        false => eps2delete.update(|ep2d| {
-            ep2d.insert(episode.number);
+        false => selected.update(|selected| {
+            selected.insert(episode.number);
         }),
     };
 
     view! {
         <a
             on:click=move |e| {
-                if delete_mode.get() {
+                if selecting.get() {
                     e.prevent_default();
-                    toggle_mark2delete();
+                    toggle_selected();
                 }
             }
             href=move || {
                 format!(
-                    "/webtoon/episode/{}?wt_id={}&wt_type={}",
+                    "/webtoon/episode/{}?{}",
                     episode.number,
-                    episode.parent_wt_id.wt_id,
-                    episode.parent_wt_id.wt_type,
+                    episode.parent_wt_id.to_query(),
                 )
             }
-            class=format!("episode {}", if seen { "active" } else { "" })
+            class=format!("episode {}", if progress.is_read() { "active" } else { "" })
         >
-            <Show when=move || delete_mode.get()>
+            <Show when=move || selecting.get()>
                 <input
                     type="checkbox"
-                    class="ep2deletebtn"
+                    class="ep_select"
                     on:click=move |e| e.stop_propagation()
-                    prop:checked=move || eps2delete.get().contains(&episode.number)
-                    on:change=move |_| toggle_mark2delete()
+                    prop:checked=move || selected.get().contains(&episode.number)
+                    on:change=move |_| toggle_selected()
                 />
             </Show>
             <img src=move || convert_file_src(&episode.thumbnail) alt="Episode thumbnail" />
             <div class="ep_info">
                 <p class="ep_title">{move || format!("#{} - {}", episode.number, episode.title)}</p>
                 <p class="ep_date">{episode.posted_at}</p>
+                <Show when=move || { progress.percent > 0 && !progress.is_read() }>
+                    <div class="ep_progress">
+                        <span style=format!("width: {}%", progress.percent)></span>
+                    </div>
+                </Show>
             </div>
             <p class="ep_likes">
                 <Icon icon=i::AiHeartFilled />
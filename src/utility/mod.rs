@@ -1,6 +1,18 @@
+use std::{cell::RefCell, rc::Rc};
+
+use leptos::{prelude::on_cleanup, task::spawn_local};
+use send_wrapper::SendWrapper;
+use wasm_bindgen::prelude::*;
+
 pub mod store;
 pub mod types;
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &js_sys::Function) -> JsValue;
+}
+
 #[macro_export]
 /// The macro handles the command result from tauri backend and parse the result in the desired struct.
 ///
@@ -76,3 +88,68 @@ pub fn convert_file_src(file_path: &str) -> String {
     let urlencoded_path = urlencoding::encode(file_path);
     format!("{base}{urlencoded_path}")
 }
+
+/// vertical position of the first finger that changed in a touch event
+///
+/// read through `Reflect` as the `TouchList` web_sys bindings aren't enabled
+pub fn touch_client_y(ev: &leptos::ev::TouchEvent) -> Option<f64> {
+    let touches = js_sys::Reflect::get(ev.as_ref(), &"changedTouches".into()).ok()?;
+    let touch = js_sys::Reflect::get(&touches, &0.into()).ok()?;
+    js_sys::Reflect::get(&touch, &"clientY".into())
+        .ok()?
+        .as_f64()
+}
+
+/// reading time as displayed to the user, from seconds
+pub fn duration_label(secs: u64) -> String {
+    match (secs / 3600, secs / 60 % 60) {
+        (0, 0) => "< 1 min".to_string(),
+        (0, min) => format!("{min} min"),
+        (h, min) => format!("{h} h {min:02}"),
+    }
+}
+
+/// A backend event listener, the handler is kept alive until the listener is removed
+struct EventListener {
+    handler: Option<Closure<dyn FnMut(JsValue)>>,
+    unlisten: Option<js_sys::Function>,
+    unmounted: bool,
+}
+
+/// listen to a backend event until the component calling this is unmounted
+pub fn listen_while_mounted(event: &'static str, handler: impl FnMut(JsValue) + 'static) {
+    let handler = Closure::<dyn FnMut(_)>::new(handler);
+    let handler_fn = handler.as_ref().unchecked_ref::<js_sys::Function>().clone();
+    let listener = Rc::new(RefCell::new(EventListener {
+        handler: Some(handler),
+        unlisten: None,
+        unmounted: false,
+    }));
+
+    let cleanup = SendWrapper::new({
+        let listener = listener.clone();
+        move || {
+            let mut listener = listener.borrow_mut();
+            listener.unmounted = true;
+            // otherwise `listen` hasn't resolved yet and will remove the listener itself
+            if let Some(unlisten) = listener.unlisten.take() {
+                let _ = unlisten.call0(&JsValue::NULL);
+                listener.handler = None;
+            }
+        }
+    });
+    on_cleanup(move || cleanup.take()());
+
+    spawn_local(async move {
+        let unlisten = listen(event, &handler_fn)
+            .await
+            .unchecked_into::<js_sys::Function>();
+        let mut listener = listener.borrow_mut();
+        if listener.unmounted {
+            let _ = unlisten.call0(&JsValue::NULL);
+            listener.handler = None;
+        } else {
+            listener.unlisten = Some(unlisten);
+        }
+    });
+}
@@ -3,7 +3,9 @@ use std::{collections::HashMap, time::SystemTime};
 use reactive_stores::Store;
 use serde::{Deserialize, Serialize};
 
-use crate::utility::types::{Language, WebtoonId, WebtoonInfo, WebtoonSearchInfo};
+use crate::utility::types::{
+    Language, RecommendationSection, TodayWebtoon, WebtoonId, WebtoonInfo, WebtoonSearchInfo,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct UserWebtoon {
@@ -12,11 +14,65 @@ pub struct UserWebtoon {
     pub thumbnail: String,
     pub creator: String,
     pub last_seen: Option<SystemTime>,
-    pub episode_seen: HashMap<String, bool>,
+    pub episode_progress: HashMap<String, ReadProgress>,
+    /// one of `UserData.shelves`
+    #[serde(default)]
+    pub shelf: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// How far the user got in an episode, saved by the reader
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReadProgress {
+    /// from 0 to 100, an episode at 100 is read
+    pub percent: u8,
+    /// index of the panel at the top of the reader
+    pub panel: usize,
+    /// scrolled fraction of that panel, from 0 to 1
+    pub offset: f64,
+}
+
+impl ReadProgress {
+    pub const READ: Self = Self {
+        percent: 100,
+        panel: 0,
+        offset: 0.0,
+    };
+
+    pub fn is_read(&self) -> bool {
+        self.percent >= 100
+    }
+}
+
+/// Episodes targeted by a bulk change of read state
+#[derive(Serialize, Clone, Debug)]
+pub enum EpisodeSelection {
+    /// both ends included
+    Range { from: usize, to: usize },
+    Set(Vec<usize>),
+    All,
 }
 
 pub type UserWebtoons = HashMap<String, UserWebtoon>;
 
+/// Reading progress of a subscription, computed by the backend
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LibraryEntry {
+    pub id: WebtoonId,
+    pub unread_count: usize,
+    pub next_unread: Option<usize>,
+    pub latest_episode_date: Option<String>,
+    /// unix timestamp in seconds
+    pub latest_episode_at: Option<u64>,
+}
+
+/// keyed by `WebtoonId::store_key`
+pub type LibraryOverview = HashMap<String, LibraryEntry>;
+
+/// episodes found by the backend's background refresh, keyed by `WebtoonId::store_key`
+pub type NewEpisodes = HashMap<String, Vec<usize>>;
+
 impl From<UserWebtoon> for WebtoonSearchInfo {
     fn from(
         UserWebtoon {
@@ -32,6 +88,7 @@ impl From<UserWebtoon> for WebtoonSearchInfo {
             title,
             thumbnail,
             creator: Some(creator),
+            genre: None,
         }
     }
 }
@@ -52,7 +109,9 @@ impl From<WebtoonInfo> for UserWebtoon {
             thumbnail,
             creator: creators.first().cloned().unwrap_or_default(),
             last_seen: None,
-            episode_seen: HashMap::default(),
+            episode_progress: HashMap::default(),
+            shelf: None,
+            tags: vec![],
         }
     }
 }
@@ -65,11 +124,64 @@ pub enum LoadingState {
     Error(String),
 }
 
+/// Comments matching one of these rules are hidden by the backend
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MuteRules {
+    pub words: Vec<String>,
+    pub regexes: Vec<String>,
+    pub posters: Vec<String>,
+    pub hide_spoilers: bool,
+}
+
+/// How the episode reader behaves, defaults are set by the backend
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReaderSettings {
+    /// mark the episode as read once its end is reached
+    pub auto_mark_read: bool,
+    /// percentage of the last panel to scroll past for the end to be reached
+    pub end_threshold: u8,
+}
+
+/// Part of a panel, in fractions of its width and height
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CropRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A panel bookmarked from the reader
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: u64,
+    pub wt_id: WebtoonId,
+    pub title: String,
+    pub episode: usize,
+    pub episode_title: String,
+    pub panel: usize,
+    /// the whole panel when `None`
+    pub crop: Option<CropRect>,
+    pub note: String,
+    /// unix timestamp in seconds
+    pub created_at: u64,
+}
+
 #[derive(Clone, Debug, Default, Store, Deserialize)]
 pub struct UserData {
     #[allow(dead_code)]
     pub language: Language,
     pub webtoons: UserWebtoons,
+    #[serde(default)]
+    pub mute_rules: MuteRules,
+    #[serde(default)]
+    pub reader_settings: ReaderSettings,
+    /// in creation order
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    /// in the order of the library tabs
+    #[serde(default)]
+    pub shelves: Vec<String>,
 
     #[serde(default)]
     pub loading_state: LoadingState,
@@ -77,7 +189,15 @@ pub struct UserData {
 
 #[derive(Clone, Debug, Default, Store, Deserialize)]
 pub struct UserRecommendations {
-    pub webtoons: Vec<WebtoonSearchInfo>,
+    pub sections: Vec<RecommendationSection>,
+
+    #[serde(default)]
+    pub loading_state: LoadingState,
+}
+
+#[derive(Clone, Debug, Default, Store, Deserialize)]
+pub struct UpdatingToday {
+    pub webtoons: Vec<TodayWebtoon>,
 
     #[serde(default)]
     pub loading_state: LoadingState,
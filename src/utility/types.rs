@@ -108,15 +108,82 @@ impl Display for WtType {
     }
 }
 
+/// Provider a series comes from
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub enum SourceId {
+    #[default]
+    Webtoons,
+    /// the user's comics folder
+    Local,
+}
+
+impl FromStr for SourceId {
+    type Err = WtTypeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "webtoons" => Ok(Self::Webtoons),
+            "local" => Ok(Self::Local),
+            _ => Err(WtTypeParseError("non existing source".to_string())),
+        }
+    }
+}
+
+impl Display for SourceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SourceId::Webtoons => "webtoons",
+                SourceId::Local => "local",
+            }
+        )
+    }
+}
+
 #[derive(Serialize, Clone, Copy, Deserialize, Debug, PartialEq)]
 pub struct WebtoonId {
     pub wt_id: usize,
     pub wt_type: WtType,
+    #[serde(default)]
+    pub source: SourceId,
+    #[serde(default)]
+    pub language: Language,
 }
 
 impl WebtoonId {
-    pub fn new(id: usize, wt_type: WtType) -> Self {
-        Self { wt_id: id, wt_type }
+    pub fn from_source(source: SourceId, id: usize, wt_type: WtType) -> Self {
+        Self {
+            wt_id: id,
+            wt_type,
+            source,
+            language: Language::default(),
+        }
+    }
+
+    pub fn with_language(self, language: Language) -> Self {
+        Self { language, ..self }
+    }
+
+    /// query params identifying the webtoon in the routes urls
+    pub fn to_query(self) -> String {
+        format!(
+            "wt_id={}&wt_type={}&source={}&lang={}",
+            self.wt_id, self.wt_type, self.source, self.language
+        )
+    }
+
+    /// key of the webtoon in the user's webtoons, must match the backend `WebtoonId::store_key`
+    pub fn store_key(&self) -> String {
+        let wt_type = match self.wt_type {
+            WtType::Original => "original",
+            WtType::Canvas => "canvas",
+        };
+        format!(
+            "{}:{wt_type}:{}:{}",
+            self.source, self.wt_id, self.language
+        )
     }
 }
 
@@ -126,6 +193,8 @@ pub struct WebtoonSearchInfo {
     pub title: String,
     pub thumbnail: String,
     pub creator: Option<String>,
+    #[serde(default)]
+    pub genre: Option<Genre>,
 }
 
 impl PartialEq for WebtoonSearchInfo {
@@ -134,6 +203,79 @@ impl PartialEq for WebtoonSearchInfo {
     }
 }
 
+/// Which kind of webtoons a search should return
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum SearchFilter {
+    #[default]
+    All,
+    Originals,
+    Canvas,
+}
+
+impl Display for SearchFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SearchFilter::All => "All",
+                SearchFilter::Originals => "Originals",
+                SearchFilter::Canvas => "Canvas",
+            }
+        )
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SearchResult {
+    pub webtoons: Vec<WebtoonSearchInfo>,
+    #[allow(dead_code)]
+    pub page: usize,
+    pub has_more: bool,
+}
+
+/// The ranking views of the site
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum RankingKind {
+    #[default]
+    Trending,
+    Popular,
+    Originals,
+    Canvas,
+    RisingCanvas,
+}
+
+impl Display for RankingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RankingKind::Trending => "Trending",
+                RankingKind::Popular => "Popular",
+                RankingKind::Originals => "Originals",
+                RankingKind::Canvas => "Canvas",
+                RankingKind::RisingCanvas => "Rising",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub enum RankMovement {
+    Up(usize),
+    Down(usize),
+    Same,
+    New,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RankedWebtoon {
+    pub rank: usize,
+    pub movement: RankMovement,
+    pub webtoon: WebtoonSearchInfo,
+}
+
 /// Represents the languages that `webtoons.com` has.
 #[derive(
     Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash,
@@ -156,6 +298,42 @@ pub enum Language {
     De,
 }
 
+impl FromStr for Language {
+    type Err = WtTypeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "en" => Ok(Self::En),
+            "zh-hant" => Ok(Self::Zh),
+            "th" => Ok(Self::Th),
+            "id" => Ok(Self::Id),
+            "es" => Ok(Self::Es),
+            "fr" => Ok(Self::Fr),
+            "de" => Ok(Self::De),
+            _ => Err(WtTypeParseError("non existing language".to_string())),
+        }
+    }
+}
+
+impl Display for Language {
+    /// language code used in the site urls
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Language::En => "en",
+                Language::Zh => "zh-hant",
+                Language::Th => "th",
+                Language::Id => "id",
+                Language::Es => "es",
+                Language::Fr => "fr",
+                Language::De => "de",
+            }
+        )
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct WebtoonInfo {
     pub id: WebtoonId,
@@ -208,29 +386,176 @@ pub struct EpisodeData {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Post {
-    #[allow(dead_code)]
     pub wt_id: WebtoonId,
-    #[allow(dead_code)]
     pub ep_num: usize,
 
     pub id: String,
+    #[allow(dead_code)]
+    pub parent_id: Option<String>,
     pub content: String,
     pub is_spoiler: bool,
+    #[allow(dead_code)]
+    pub is_top: bool,
     pub upvotes: u32,
     pub downvotes: u32,
+    pub reply_count: u32,
     pub posted_at: u64,
     pub poster_name: String,
 }
 
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum PostSort {
+    #[default]
+    Top,
+    Newest,
+}
+
+impl Display for PostSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PostSort::Top => "Top",
+                PostSort::Newest => "Newest",
+            }
+        )
+    }
+}
+
+/// One api page of comments or replies, `next_cursor` requests the following one
+#[derive(Deserialize, Clone, Debug)]
+pub struct PostBatch {
+    pub posts: Vec<Post>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct PostPage {
+    pub posts: Vec<Post>,
+    pub page: usize,
+    pub has_more: bool,
+    pub is_stale: bool,
+    pub hidden_count: usize,
+}
+
+/// An episode opened in the reader, timestamps are unix seconds
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub wt_id: WebtoonId,
+    pub title: String,
+    pub episode: usize,
+    pub episode_title: String,
+    pub opened_at: u64,
+    pub finished_at: Option<u64>,
+    /// seconds spent in the episode
+    pub duration: u64,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    pub page: usize,
+    pub has_more: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct PeriodStats {
+    /// unix timestamp in seconds of the local midnight starting the period
+    pub start: i64,
+    pub episodes: usize,
+    /// seconds spent reading
+    pub time: u64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct GenreStats {
+    pub genre: Genre,
+    pub episodes: usize,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct CreatorStats {
+    pub creator: String,
+    pub episodes: usize,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct SeriesCompletion {
+    pub id: WebtoonId,
+    pub title: String,
+    pub read: usize,
+    pub total: usize,
+}
+
+/// Computed by the backend from the reading history
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ReadingStats {
+    pub days: Vec<PeriodStats>,
+    pub weeks: Vec<PeriodStats>,
+    pub total_episodes: usize,
+    pub total_time: u64,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub genres: Vec<GenreStats>,
+    pub creators: Vec<CreatorStats>,
+    pub completion: Vec<SeriesCompletion>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub enum SectionKind {
+    OriginalsToday,
+    PopularCanvas,
+    FollowedGenre(Genre),
+}
+
+impl Display for SectionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionKind::OriginalsToday => write!(f, "Originals today"),
+            SectionKind::PopularCanvas => write!(f, "Popular Canvas"),
+            SectionKind::FollowedGenre(genre) => write!(f, "Because you follow {genre}"),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RecommendationSection {
+    pub kind: SectionKind,
+    pub webtoons: Vec<WebtoonSearchInfo>,
+}
+
+/// already ordered by the backend, its score isn't needed here
+#[derive(Deserialize, Clone, Debug)]
+pub struct PersonalRecommendation {
+    pub webtoon: WebtoonSearchInfo,
+    pub reason: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct TodayWebtoon {
+    pub webtoon: WebtoonSearchInfo,
+    pub subscribed: bool,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct CreatorInfo {
     #[allow(dead_code)]
     pub profile_id: String,
     pub name: String,
     pub followers: Option<u32>,
+    pub bio: Option<String>,
+    pub socials: Vec<SocialLink>,
     pub webtoons: Vec<WebtoonSearchInfo>,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct SocialLink {
+    pub platform: String,
+    pub url: String,
+}
+
 /* HELPERS TYPES */
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -246,7 +571,7 @@ pub enum Schedule {
 }
 
 /// Represents a day of the week
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Weekday {
     /// Released on Sunday
     Sunday,
@@ -265,6 +590,19 @@ pub enum Weekday {
 }
 
 impl Weekday {
+    /// current day of the week, in the user's local time
+    pub fn today() -> Self {
+        match js_sys::Date::new_0().get_day() {
+            0 => Weekday::Sunday,
+            1 => Weekday::Monday,
+            2 => Weekday::Tuesday,
+            3 => Weekday::Wednesday,
+            4 => Weekday::Thursday,
+            5 => Weekday::Friday,
+            _ => Weekday::Saturday,
+        }
+    }
+
     pub fn to_acronym(self) -> &'static str {
         match self {
             Weekday::Sunday => "Sun",
@@ -279,7 +617,7 @@ impl Weekday {
 }
 
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, Clone, Deserialize, Ord, PartialOrd, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Deserialize, Serialize, Ord, PartialOrd, PartialEq, Eq, Hash)]
 pub enum Genre {
     Comedy,
     Fantasy,
@@ -332,3 +670,82 @@ impl Display for Genre {
         }
     }
 }
+
+impl Genre {
+    /// every genre known by the app (so without `Genre::Other`)
+    pub fn known() -> Vec<Genre> {
+        vec![
+            Genre::Comedy,
+            Genre::Fantasy,
+            Genre::Romance,
+            Genre::SliceOfLife,
+            Genre::SciFi,
+            Genre::Drama,
+            Genre::ShortStory,
+            Genre::Action,
+            Genre::Superhero,
+            Genre::Heartwarming,
+            Genre::Thriller,
+            Genre::Horror,
+            Genre::PostApocalyptic,
+            Genre::Zombies,
+            Genre::School,
+            Genre::Supernatural,
+            Genre::Animals,
+            Genre::Mystery,
+            Genre::Historical,
+            Genre::Informative,
+            Genre::Sports,
+            Genre::Inspirational,
+            Genre::AllAges,
+            Genre::LGBTQ,
+            Genre::RomanticFantasy,
+            Genre::MartialArts,
+            Genre::WesternPalace,
+            Genre::EasternPalace,
+            Genre::MatureRomance,
+            Genre::TimeSlip,
+            Genre::Local,
+            Genre::CityOffice,
+            Genre::Adaptation,
+            Genre::Shonen,
+            Genre::WebNovel,
+            Genre::GraphicNovel,
+        ]
+    }
+}
+
+impl FromStr for Genre {
+    type Err = std::convert::Infallible;
+
+    /// inverse of `Display`: unknown genres are parsed as `Genre::Other`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match serde_json::from_value::<Genre>(serde_json::Value::String(s.to_string())) {
+            Ok(g) => Ok(g),
+            Err(_) => Ok(Self::Other(s.to_string())),
+        }
+    }
+}
+
+/// How the genre listings are sorted
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum GenreSortOrder {
+    #[default]
+    Popularity,
+    Likes,
+    Update,
+}
+
+impl Display for GenreSortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                GenreSortOrder::Popularity => "Popular",
+                GenreSortOrder::Likes => "Most liked",
+                GenreSortOrder::Update => "Last updated",
+            }
+        )
+    }
+}
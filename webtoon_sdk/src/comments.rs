@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{source::ContentSource, WebtoonId, WtType};
+
+const COMMENTS_API: &str = "https://www.webtoons.com/p/api/community/v2";
+/// how many comments or replies are requested per api call
+pub const API_PAGE_SIZE: usize = 20;
+
+/// A comment of an episode, or a reply to one of them
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Post {
+    pub wt_id: WebtoonId,
+    pub ep_num: usize,
+
+    pub id: String,
+    /// id of the replied comment, `None` for a top level comment
+    pub parent_id: Option<String>,
+    pub content: String,
+    pub is_spoiler: bool,
+    /// pinned by the site at the top of the comments section
+    pub is_top: bool,
+    pub upvotes: u32,
+    pub downvotes: u32,
+    pub reply_count: u32,
+    /// unix timestamp in milliseconds
+    pub posted_at: u64,
+    pub poster_name: String,
+}
+
+/// One api page of comments or replies
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PostBatch {
+    pub posts: Vec<Post>,
+    /// cursor of the following page, `None` on the last one
+    pub next_cursor: Option<String>,
+}
+
+impl Post {
+    fn from_api(value: &Value, wt_id: WebtoonId, ep_num: usize) -> Result<Self, String> {
+        let id = value["id"].as_str().ok_or("No post id")?.to_string();
+        let parent_id = value["parentId"]
+            .as_str()
+            .filter(|parent_id| *parent_id != id)
+            .map(|parent_id| parent_id.to_string());
+
+        let emotion_count = |emotion: &str| {
+            value["reactions"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .flat_map(|reaction| reaction["emotions"].as_array().into_iter().flatten())
+                .find(|e| e["emotionId"].as_str() == Some(emotion))
+                .and_then(|e| e["count"].as_u64())
+                .unwrap_or_default() as u32
+        };
+
+        Ok(Self {
+            wt_id,
+            ep_num,
+            parent_id,
+            content: value["body"].as_str().unwrap_or_default().to_string(),
+            is_spoiler: value["settings"]["spoilerFilter"].as_str() == Some("ON"),
+            is_top: false,
+            upvotes: emotion_count("like"),
+            downvotes: emotion_count("dislike"),
+            reply_count: value["childPostCount"].as_u64().unwrap_or_default() as u32,
+            posted_at: value["createdAt"].as_u64().unwrap_or_default(),
+            poster_name: value["createdBy"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            id,
+        })
+    }
+}
+
+/// one page of the top level comments of the episode, newest first, replies are fetched on demand
+///
+/// `cursor` is the `next_cursor` of the previous page, `None` for the first one
+pub async fn fetch_posts(
+    wt_id: WebtoonId,
+    ep_num: usize,
+    cursor: Option<&str>,
+) -> Result<PostBatch, String> {
+    wt_id.source.source().comments(wt_id, ep_num, cursor).await
+}
+
+/// comments picked by the site to be shown first
+pub async fn fetch_top_posts(wt_id: WebtoonId, ep_num: usize) -> Result<Vec<Post>, String> {
+    wt_id.source.source().top_comments(wt_id, ep_num).await
+}
+
+/// one page of the replies of a comment, oldest first
+pub async fn fetch_replies(
+    wt_id: WebtoonId,
+    ep_num: usize,
+    post_id: &str,
+    cursor: Option<&str>,
+) -> Result<PostBatch, String> {
+    wt_id
+        .source
+        .source()
+        .replies(wt_id, ep_num, post_id, cursor)
+        .await
+}
+
+/// comments from the `webtoons.com` api
+pub(crate) async fn scrap_posts(
+    wt_id: WebtoonId,
+    ep_num: usize,
+    cursor: Option<&str>,
+) -> Result<PostBatch, String> {
+    let mut url = format!(
+        "{COMMENTS_API}/posts?pageId={}&pinRepresentation=none&prevSize=0&nextSize={API_PAGE_SIZE}&withCursor=true",
+        page_id(wt_id, ep_num)
+    );
+    if let Some(cursor) = cursor {
+        url.push_str(&format!("&cursor={}", urlencoding::encode(cursor)));
+    }
+
+    let result = get_api_result(&reqwest::Client::new(), &url).await?;
+    post_batch(&result, wt_id, ep_num)
+}
+
+/// pinned comments from the `webtoons.com` api
+pub(crate) async fn scrap_top_posts(wt_id: WebtoonId, ep_num: usize) -> Result<Vec<Post>, String> {
+    let result = get_api_result(
+        &reqwest::Client::new(),
+        &format!("{COMMENTS_API}/posts/top?pageId={}", page_id(wt_id, ep_num)),
+    )
+    .await?;
+
+    result["topPosts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|post| {
+            Post::from_api(post, wt_id, ep_num).map(|post| Post {
+                is_top: true,
+                ..post
+            })
+        })
+        .collect()
+}
+
+/// replies from the `webtoons.com` api
+pub(crate) async fn scrap_replies(
+    wt_id: WebtoonId,
+    ep_num: usize,
+    post_id: &str,
+    cursor: Option<&str>,
+) -> Result<PostBatch, String> {
+    let mut url = format!(
+        "{COMMENTS_API}/post/{}/child-posts?sort=oldest&prevSize=0&nextSize={API_PAGE_SIZE}&withCursor=true",
+        urlencoding::encode(post_id)
+    );
+    if let Some(cursor) = cursor {
+        url.push_str(&format!("&cursor={}", urlencoding::encode(cursor)));
+    }
+
+    let result = get_api_result(&reqwest::Client::new(), &url).await?;
+    let mut batch = post_batch(&result, wt_id, ep_num)?;
+    for reply in batch.posts.iter_mut() {
+        reply.parent_id.get_or_insert_with(|| post_id.to_string());
+    }
+    batch.posts.sort_by_key(|reply| reply.posted_at);
+
+    Ok(batch)
+}
+
+/* HELPERS */
+
+/// identifier of an episode comments section in the api
+fn page_id(wt_id: WebtoonId, ep_num: usize) -> String {
+    let prefix = match wt_id.wt_type {
+        WtType::Original => "w",
+        WtType::Canvas => "c",
+    };
+    format!("{prefix}_{}_{ep_num}", wt_id.wt_id)
+}
+
+fn post_batch(result: &Value, wt_id: WebtoonId, ep_num: usize) -> Result<PostBatch, String> {
+    Ok(PostBatch {
+        posts: result["posts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|post| Post::from_api(post, wt_id, ep_num))
+            .collect::<Result<_, _>>()?,
+        next_cursor: result["pagination"]["next"]
+            .as_str()
+            .map(|next| next.to_string()),
+    })
+}
+
+async fn get_api_result(http_client: &reqwest::Client, url: &str) -> Result<Value, String> {
+    let resp = http_client
+        .get(url)
+        .header("Service-Ticket-Id", "epicom")
+        .header("Referer", "https://www.webtoons.com/")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("Comments api answered {}", resp.status()));
+    }
+
+    let raw_json = resp.text().await.map_err(|e| e.to_string())?;
+    let mut body = serde_json::from_str::<Value>(&raw_json).map_err(|e| e.to_string())?;
+    match body["status"].as_str() {
+        Some("success") | None => Ok(body["result"].take()),
+        Some(_) => Err("Comments api failed".to_string()),
+    }
+}
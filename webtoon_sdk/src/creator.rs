@@ -0,0 +1,235 @@
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{search::WebtoonSearchInfo, Language, WebtoonId, WtType};
+
+/// social platforms recognized in the creator's links, matched against the link host
+const SOCIAL_PLATFORMS: [(&str, &str); 10] = [
+    ("instagram.com", "Instagram"),
+    ("twitter.com", "Twitter"),
+    ("x.com", "X"),
+    ("youtube.com", "YouTube"),
+    ("tiktok.com", "TikTok"),
+    ("patreon.com", "Patreon"),
+    ("ko-fi.com", "Ko-fi"),
+    ("facebook.com", "Facebook"),
+    ("tumblr.com", "Tumblr"),
+    ("bsky.app", "Bluesky"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SocialLink {
+    /// e.g. `Instagram`
+    pub platform: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreatorInfo {
+    pub profile_id: String,
+    pub name: String,
+    pub followers: Option<u32>,
+    pub bio: Option<String>,
+    pub socials: Vec<SocialLink>,
+    /// thumbnails are urls, they aren't downloaded
+    pub webtoons: Vec<WebtoonSearchInfo>,
+}
+
+impl Language {
+    /// language name used by the site api
+    fn api_name(&self) -> &'static str {
+        match self {
+            Language::En => "ENGLISH",
+            Language::Zh => "TRADITIONAL_CHINESE",
+            Language::Th => "THAI",
+            Language::Id => "INDONESIAN",
+            Language::Es => "SPANISH",
+            Language::Fr => "FRENCH",
+            Language::De => "GERMAN",
+        }
+    }
+}
+
+impl CreatorInfo {
+    /// gather the creator's profile and series list
+    pub async fn fetch(profile_id: &str, language: Language) -> Result<Self, String> {
+        let (profile, webtoons) = futures::future::join(
+            fetch_profile_page(profile_id, language),
+            fetch_creator_webtoons(profile_id, language),
+        )
+        .await;
+        let ProfilePage {
+            name,
+            followers,
+            bio,
+            socials,
+        } = profile?;
+
+        Ok(Self {
+            profile_id: profile_id.to_string(),
+            name,
+            followers,
+            bio,
+            socials,
+            webtoons: webtoons?,
+        })
+    }
+}
+
+/* HELPERS */
+
+struct ProfilePage {
+    name: String,
+    followers: Option<u32>,
+    bio: Option<String>,
+    socials: Vec<SocialLink>,
+}
+
+async fn fetch_profile_page(profile_id: &str, language: Language) -> Result<ProfilePage, String> {
+    let resp = reqwest::get(format!(
+        "https://www.webtoons.com/p/community/{}/u/{}",
+        language.url_code(),
+        urlencoding::encode(profile_id)
+    ))
+    .await
+    .map_err(|e| e.to_string())?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err("Creator not found".to_string());
+    }
+
+    let raw_html = resp.text().await.map_err(|e| e.to_string())?;
+    let document = Html::parse_document(&raw_html);
+
+    let name_selector = Selector::parse("meta[property='og:title']").unwrap();
+    let bio_selector = Selector::parse("meta[property='og:description']").unwrap();
+    let followers_selector = Selector::parse("[class*='follower'], [class*='Follower']").unwrap();
+    let link_selector = Selector::parse("a[href^='http']").unwrap();
+
+    let name = document
+        .select(&name_selector)
+        .next()
+        .and_then(|meta| meta.attr("content"))
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .ok_or("No creator name")?;
+    let bio = document
+        .select(&bio_selector)
+        .next()
+        .and_then(|meta| meta.attr("content"))
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty());
+    // displayed as "1,234 followers" or "1.2M followers"
+    let followers = document.select(&followers_selector).find_map(|elem| {
+        let text = elem.text().collect::<String>();
+        text.split_whitespace().find_map(parse_follower_count)
+    });
+
+    let mut socials = Vec::<SocialLink>::new();
+    for link in document.select(&link_selector) {
+        let Some(url) = link.attr("href") else {
+            continue;
+        };
+        let host = url
+            .split("://")
+            .nth(1)
+            .and_then(|rest| rest.split('/').next())
+            .unwrap_or_default()
+            .trim_start_matches("www.");
+        if host.is_empty()
+            || host.ends_with("webtoons.com")
+            || socials.iter().any(|s| s.url == url)
+        {
+            continue;
+        }
+
+        if let Some((_, platform)) = SOCIAL_PLATFORMS
+            .iter()
+            .find(|(domain, _)| host == *domain || host.ends_with(&format!(".{domain}")))
+        {
+            socials.push(SocialLink {
+                platform: platform.to_string(),
+                url: url.to_string(),
+            });
+        }
+    }
+
+    Ok(ProfilePage {
+        name,
+        followers,
+        bio,
+        socials,
+    })
+}
+
+/// the series list isn't in the profile html, it's loaded from the community api
+async fn fetch_creator_webtoons(
+    profile_id: &str,
+    language: Language,
+) -> Result<Vec<WebtoonSearchInfo>, String> {
+    let resp = reqwest::Client::new()
+        .get(format!(
+            "https://www.webtoons.com/p/community/api/v1/creator/{}/titles?language={}&nextSize=50",
+            urlencoding::encode(profile_id),
+            language.api_name()
+        ))
+        .header("Referer", "https://www.webtoons.com/")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let raw_json = resp.text().await.map_err(|e| e.to_string())?;
+    let body = serde_json::from_str::<Value>(&raw_json).map_err(|e| e.to_string())?;
+
+    let titles = body["result"]["titles"]
+        .as_array()
+        .ok_or("Failed to parse creator webtoons")?;
+    titles
+        .iter()
+        .map(|title| {
+            let wt_id = title["id"]
+                .as_u64()
+                .map(|id| id as usize)
+                .or_else(|| title["id"].as_str().and_then(|id| id.parse().ok()))
+                .ok_or("No wt id")?;
+            let wt_type = match title["webtoonType"].as_str().unwrap_or("WEBTOON") {
+                "WEBTOON" => WtType::Original,
+                "CHALLENGE" => WtType::Canvas,
+                _ => return Err("Failed to parse wt type".to_string()),
+            };
+            let creator = title["authorNameList"]
+                .as_array()
+                .and_then(|authors| authors.first())
+                .and_then(|author| author.as_str())
+                .map(|author| author.to_string());
+
+            Ok(WebtoonSearchInfo {
+                id: WebtoonId::new(wt_id, wt_type).with_language(language),
+                title: title["subject"]
+                    .as_str()
+                    .ok_or("No title")?
+                    .trim()
+                    .to_string(),
+                thumbnail: title["thumbnailUrl"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                creator,
+                genre: None,
+            })
+        })
+        .collect()
+}
+
+/// parses "1,234" or "1.2K"/"3M" like counts
+fn parse_follower_count(raw: &str) -> Option<u32> {
+    let raw = raw.trim().replace(',', "");
+    let (number, multiplier) = match raw.chars().last()? {
+        'K' | 'k' => (&raw[..raw.len() - 1], 1_000.0),
+        'M' | 'm' => (&raw[..raw.len() - 1], 1_000_000.0),
+        _ => (raw.as_str(), 1.0),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|n| (n * multiplier).round() as u32)
+}
@@ -1,9 +1,15 @@
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path};
 
 use scraper::{ElementRef, Html, Selector};
 use serde::{Deserialize, Serialize};
 
-use crate::{generate_webtoon_url, image_dl::download_images, DownloadState, WebtoonId};
+use crate::{
+    generate_webtoon_url, image_dl::download_images, source::ContentSource, DownloadState,
+    WebtoonId,
+};
+
+/// Episodes of a webtoon ordered and looked up by their number
+pub type Episodes = BTreeMap<usize, EpisodePreview>;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EpisodePreview {
@@ -91,8 +97,173 @@ impl EpisodePreview {
     }
 }
 
+impl EpisodePreview {
+    /// `episode_no` of the episode url, unlike `number` it doesn't change when the site renumbers the episodes
+    ///
+    /// `None` for sources without such an id, e.g. the local one
+    pub fn episode_no(&self) -> Option<usize> {
+        self.ep_url
+            .split(['?', '&'])
+            .find_map(|param| param.strip_prefix("episode_no="))
+            .and_then(|no| no.parse().ok())
+    }
+
+    /// `posted_at` as a unix timestamp in seconds (midnight UTC of that day)
+    ///
+    /// `None` when the date isn't in a known format: the english one of the site ("Jan 1, 2024")
+    /// or the ISO one of the local episodes ("2024-01-01", "2024-01", "2024")
+    pub fn posted_timestamp(&self) -> Option<u64> {
+        parse_posted_date(&self.posted_at)
+    }
+
+    /// what identifies the same episode between two listings
+    fn identity(&self) -> EpisodeIdentity {
+        match self.episode_no() {
+            Some(episode_no) => EpisodeIdentity::EpisodeNo(episode_no),
+            None => EpisodeIdentity::Url(self.ep_url.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum EpisodeIdentity {
+    EpisodeNo(usize),
+    Url(String),
+}
+
+/// What changed in the stored episodes after a reconciliation, as episode numbers
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct EpisodesChanges {
+    pub added: Vec<usize>,
+    /// numbers these episodes had before being removed
+    pub removed: Vec<usize>,
+    /// same episode, new title or number
+    pub modified: Vec<usize>,
+}
+
+impl EpisodesChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// replaces `stored` with the complete listing `fetched`, episodes are matched by their url rather than their number
+pub fn reconcile_episodes(stored: &mut Episodes, fetched: Vec<EpisodePreview>) -> EpisodesChanges {
+    let mut stored_by_identity = std::mem::take(stored)
+        .into_values()
+        .map(|ep| (ep.identity(), ep))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut changes = EpisodesChanges::default();
+    for mut ep in fetched {
+        match stored_by_identity.remove(&ep.identity()) {
+            Some(previous) => {
+                if previous.number != ep.number || previous.title != ep.title {
+                    changes.modified.push(ep.number);
+                }
+                // the thumbnail was already downloaded
+                ep.thumbnail = previous.thumbnail;
+            }
+            None => changes.added.push(ep.number),
+        }
+        stored.insert(ep.number, ep);
+    }
+    changes.removed = stored_by_identity
+        .into_values()
+        .map(|ep| ep.number)
+        .collect();
+
+    changes.added.sort_unstable();
+    changes.removed.sort_unstable();
+    changes.modified.sort_unstable();
+    changes
+}
+
+/// adds the episodes released since the last stored one, `fetched` only contains those new episodes
+///
+/// an already stored number pointing to another url means the site renumbered it, the new one wins
+pub fn merge_new_episodes(stored: &mut Episodes, fetched: Vec<EpisodePreview>) -> EpisodesChanges {
+    let mut changes = EpisodesChanges::default();
+    for ep in fetched {
+        match stored.get(&ep.number) {
+            Some(previous) if previous.identity() == ep.identity() => continue,
+            Some(_) => changes.modified.push(ep.number),
+            None => changes.added.push(ep.number),
+        }
+        stored.insert(ep.number, ep);
+    }
+    changes
+}
+
+/// (de)serializes `Option<Episodes>` as a list, so that stored webtoons and the frontend keep the same format
+pub(crate) mod episodes_as_list {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{EpisodePreview, Episodes};
+
+    pub fn serialize<S: Serializer>(
+        episodes: &Option<Episodes>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        episodes
+            .as_ref()
+            .map(|eps| eps.values().collect::<Vec<_>>())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Episodes>, D::Error> {
+        Ok(
+            Option::<Vec<EpisodePreview>>::deserialize(deserializer)?.map(|eps| {
+                eps.into_iter()
+                    .map(|ep| (ep.number, ep))
+                    .collect::<Episodes>()
+            }),
+        )
+    }
+}
+
 /* Functions */
 
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+fn parse_posted_date(date: &str) -> Option<u64> {
+    let date = date.trim();
+    let (year, month, day) = match date.split_once(", ") {
+        Some((month_day, year)) => {
+            let (month, day) = month_day.split_once(' ')?;
+            let month = MONTHS.iter().position(|&name| month.starts_with(name))? + 1;
+            (
+                year.parse::<i64>().ok()?,
+                month as i64,
+                day.parse::<i64>().ok()?,
+            )
+        }
+        None => {
+            let mut parts = date.split('-').map(|part| part.parse::<i64>().ok());
+            let year = parts.next()??;
+            let month = parts.next().unwrap_or(Some(1))?;
+            let day = parts.next().unwrap_or(Some(1))?;
+            (year, month, day)
+        }
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // days since the epoch of a proleptic gregorian date, from http://howardhinnant.github.io/date_algorithms.html
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    u64::try_from(days * 24 * 60 * 60).ok()
+}
+
 pub enum ScrapEdgeCase {
     Inclusive,
     Exclusive,
@@ -174,7 +345,7 @@ async fn scrap_episodes_info_until<F: Fn(DownloadState) + Clone>(
 /// Therefore `last_stored_ep` start at `1` and not at `0` - becarful
 ///
 /// It returns the potential missing episodes info (so if it returns an empty Vec there are no missing ep)
-pub async fn check_for_new_eps<F: Fn(DownloadState) + Clone>(
+pub(crate) async fn check_for_new_eps<F: Fn(DownloadState) + Clone>(
     id: WebtoonId,
     last_stored_ep: usize,
     info_cb: F,
@@ -182,7 +353,7 @@ pub async fn check_for_new_eps<F: Fn(DownloadState) + Clone>(
     scrap_episodes_info_until(id, last_stored_ep, ScrapEdgeCase::Exclusive, info_cb).await
 }
 
-pub async fn scrap_episodes_info<F: Fn(DownloadState) + Clone>(
+pub(crate) async fn scrap_episodes_info<F: Fn(DownloadState) + Clone>(
     id: WebtoonId,
     info_cb: F,
 ) -> Result<Vec<EpisodePreview>, String> {
@@ -239,9 +410,22 @@ impl EpisodeData {
 }
 
 impl EpisodePreview {
+    /// fetch the episode panels and author info from the webtoon source
     pub async fn get_episode_data<F: Fn(DownloadState) + Clone>(
         &self,
         info_cb: F,
+    ) -> Result<EpisodeData, String> {
+        self.parent_wt_id
+            .source
+            .source()
+            .episode_data(self, info_cb)
+            .await
+    }
+
+    /// scraps the `webtoons.com` page of the episode
+    pub(crate) async fn scrap_episode_data<F: Fn(DownloadState) + Clone>(
+        &self,
+        info_cb: F,
     ) -> Result<EpisodeData, String> {
         info_cb(DownloadState::EpisodeInfo(0));
         let raw_html = reqwest::get(&self.ep_url)
@@ -310,3 +494,140 @@ impl EpisodePreview {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WtType;
+
+    fn episode(number: usize, episode_no: usize, title: &str) -> EpisodePreview {
+        EpisodePreview {
+            parent_wt_id: WebtoonId::new(95, WtType::Original),
+            number,
+            title: title.to_string(),
+            thumbnail: format!("https://thumb/{episode_no}.jpg"),
+            likes: 0,
+            posted_at: "Jan 1, 2024".to_string(),
+            ep_url: format!("/en/x/y/ep/viewer?title_no=95&episode_no={episode_no}"),
+        }
+    }
+
+    fn local_episode(number: usize, dir: &str) -> EpisodePreview {
+        EpisodePreview {
+            ep_url: format!("/library/series/{dir}"),
+            ..episode(number, 0, dir)
+        }
+    }
+
+    fn stored(episodes: Vec<EpisodePreview>) -> Episodes {
+        episodes.into_iter().map(|ep| (ep.number, ep)).collect()
+    }
+
+    #[test]
+    fn episode_no_is_read_from_the_url() {
+        assert_eq!(episode(1, 42, "a").episode_no(), Some(42));
+        assert_eq!(local_episode(1, "chapter 1").episode_no(), None);
+    }
+
+    #[test]
+    fn reconcile_keeps_unchanged_episodes() {
+        let mut eps = stored(vec![episode(1, 1, "a"), episode(2, 2, "b")]);
+        eps.get_mut(&1).unwrap().thumbnail = "/local/1.jpg".to_string();
+
+        let changes = reconcile_episodes(&mut eps, vec![episode(1, 1, "a"), episode(2, 2, "b")]);
+
+        assert!(changes.is_empty());
+        assert_eq!(eps.keys().copied().collect::<Vec<_>>(), [1, 2]);
+        // the downloaded thumbnail is kept
+        assert_eq!(eps[&1].thumbnail, "/local/1.jpg");
+    }
+
+    #[test]
+    fn reconcile_detects_added_and_removed_episodes() {
+        let mut eps = stored(vec![episode(1, 1, "a"), episode(2, 2, "b")]);
+
+        let changes = reconcile_episodes(&mut eps, vec![episode(1, 1, "a"), episode(3, 3, "c")]);
+
+        assert_eq!(changes.added, [3]);
+        assert_eq!(changes.removed, [2]);
+        assert!(changes.modified.is_empty());
+        assert_eq!(eps.keys().copied().collect::<Vec<_>>(), [1, 3]);
+    }
+
+    #[test]
+    fn reconcile_matches_renumbered_episodes_by_episode_no() {
+        // episode 2 was removed, the following ones moved down by one
+        let mut eps = stored(vec![
+            episode(1, 1, "a"),
+            episode(2, 2, "b"),
+            episode(3, 3, "c"),
+        ]);
+
+        let changes = reconcile_episodes(&mut eps, vec![episode(1, 1, "a"), episode(2, 3, "c")]);
+
+        assert!(changes.added.is_empty());
+        assert_eq!(changes.removed, [2]);
+        assert_eq!(changes.modified, [2]);
+        assert_eq!(eps[&2].episode_no(), Some(3));
+        assert_eq!(eps[&2].title, "c");
+    }
+
+    #[test]
+    fn reconcile_matches_episodes_without_episode_no_by_url() {
+        let mut eps = stored(vec![local_episode(1, "one"), local_episode(2, "two")]);
+
+        let changes = reconcile_episodes(
+            &mut eps,
+            vec![local_episode(1, "two"), local_episode(2, "three")],
+        );
+
+        assert_eq!(changes.added, [2]);
+        assert_eq!(changes.removed, [1]);
+        assert_eq!(changes.modified, [1]);
+        assert_eq!(eps[&1].ep_url, "/library/series/two");
+    }
+
+    #[test]
+    fn reconcile_detects_renamed_episodes() {
+        let mut eps = stored(vec![episode(1, 1, "a")]);
+
+        let changes = reconcile_episodes(&mut eps, vec![episode(1, 1, "a (remastered)")]);
+
+        assert_eq!(changes.modified, [1]);
+        assert_eq!(eps[&1].title, "a (remastered)");
+    }
+
+    #[test]
+    fn merge_appends_new_episodes() {
+        let mut eps = stored(vec![episode(1, 1, "a"), episode(2, 2, "b")]);
+
+        let changes = merge_new_episodes(&mut eps, vec![episode(2, 2, "b"), episode(3, 3, "c")]);
+
+        assert_eq!(changes.added, [3]);
+        assert!(changes.removed.is_empty());
+        assert!(changes.modified.is_empty());
+        assert_eq!(eps.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn merge_replaces_renumbered_episodes() {
+        let mut eps = stored(vec![episode(1, 1, "a"), episode(2, 2, "b")]);
+
+        // the site removed episode 2, its number now belongs to the next one
+        let changes = merge_new_episodes(&mut eps, vec![episode(2, 3, "c")]);
+
+        assert!(changes.added.is_empty());
+        assert_eq!(changes.modified, [2]);
+        assert_eq!(eps[&2].episode_no(), Some(3));
+    }
+
+    #[test]
+    fn posted_dates_become_timestamps() {
+        assert_eq!(parse_posted_date("Jan 1, 2024"), Some(1_704_067_200));
+        assert_eq!(parse_posted_date("Mar 15, 2024"), Some(1_710_460_800));
+        assert_eq!(parse_posted_date("2024-03-15"), Some(1_710_460_800));
+        assert_eq!(parse_posted_date("2024"), Some(1_704_067_200));
+        assert_eq!(parse_posted_date("15 mars 2024"), None);
+        assert_eq!(parse_posted_date(""), None);
+    }
+}
@@ -0,0 +1,159 @@
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    recommandations::{parse_canvas_item, parse_original_item},
+    search::{has_next_page, SearchResult},
+    Genre, Language, WtType,
+};
+
+/// How the genre listings are sorted on the site
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GenreSortOrder {
+    /// most read first
+    #[default]
+    Popularity,
+    /// most liked first
+    Likes,
+    /// last updated first
+    Update,
+}
+
+impl GenreSortOrder {
+    fn as_query(&self) -> &'static str {
+        match self {
+            GenreSortOrder::Popularity => "MANA",
+            GenreSortOrder::Likes => "LIKEIT",
+            GenreSortOrder::Update => "UPDATE",
+        }
+    }
+}
+
+impl Genre {
+    /// name of the genre in the originals listing url (e.g. `slice-of-life`)
+    pub fn slug(&self) -> String {
+        match self {
+            Genre::Comedy => "comedy",
+            Genre::Fantasy => "fantasy",
+            Genre::Romance => "romance",
+            Genre::SliceOfLife => "slice-of-life",
+            Genre::SciFi => "sf",
+            Genre::Drama => "drama",
+            Genre::ShortStory => "short-story",
+            Genre::Action => "action",
+            Genre::Superhero => "superhero",
+            Genre::Heartwarming => "heartwarming",
+            Genre::Thriller => "thriller",
+            Genre::Horror => "horror",
+            Genre::PostApocalyptic => "post-apocalyptic",
+            Genre::Zombies => "zombies",
+            Genre::School => "school",
+            Genre::Supernatural => "supernatural",
+            Genre::Animals => "animals",
+            Genre::Mystery => "mystery",
+            Genre::Historical => "historical",
+            Genre::Informative => "tiptoon",
+            Genre::Sports => "sports",
+            Genre::Inspirational => "inspirational",
+            Genre::AllAges => "all-ages",
+            Genre::LGBTQ => "lgbtq",
+            Genre::RomanticFantasy => "romantic-fantasy",
+            Genre::MartialArts => "martial-arts",
+            Genre::WesternPalace => "western-palace",
+            Genre::EasternPalace => "eastern-palace",
+            Genre::MatureRomance => "mature-romance",
+            Genre::TimeSlip => "time-slip",
+            Genre::Local => "local",
+            Genre::CityOffice => "city-office",
+            Genre::Adaptation => "adaptation",
+            Genre::Shonen => "shonen",
+            Genre::WebNovel => "web-novel",
+            Genre::GraphicNovel => "graphic-novel",
+            Genre::Other(raw_genre) => {
+                return raw_genre
+                    .trim()
+                    .to_lowercase()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join("-");
+            }
+        }
+        .to_string()
+    }
+
+    /// name of the genre in the canvas `genreTab` query (e.g. `SLICE_OF_LIFE`)
+    pub fn canvas_tab(&self) -> String {
+        self.slug().replace("-", "_").to_uppercase()
+    }
+}
+
+/// fetch the webtoons of one genre, `page` starts at `1`
+///
+/// the originals listing is not paginated: every webtoon is returned on the first page
+pub async fn fetch_genre(
+    genre: &Genre,
+    wt_type: WtType,
+    sort_order: GenreSortOrder,
+    page: usize,
+    language: Language,
+) -> Result<SearchResult, String> {
+    let page = page.max(1);
+    if let WtType::Original = wt_type
+        && page > 1
+    {
+        return Ok(SearchResult {
+            webtoons: vec![],
+            page,
+            has_more: false,
+        });
+    }
+
+    let url = match wt_type {
+        WtType::Original => format!(
+            "https://www.webtoons.com/{}/genres/{}?sortOrder={}",
+            language.url_code(),
+            genre.slug(),
+            sort_order.as_query()
+        ),
+        WtType::Canvas => format!(
+            "https://www.webtoons.com/{}/canvas/list?genreTab={}&sortOrder={}&page={page}",
+            language.url_code(),
+            genre.canvas_tab(),
+            sort_order.as_query()
+        ),
+    };
+    let resp = reqwest::get(&url).await.map_err(|e| e.to_string())?;
+    let raw_html = resp.text().await.map_err(|e| e.to_string())?;
+    let document = Html::parse_document(&raw_html);
+
+    let (mut webtoons, has_more) = match wt_type {
+        WtType::Original => {
+            let webtoons_selectors = Selector::parse(".webtoon_list > li").unwrap();
+            let webtoons = document
+                .select(&webtoons_selectors)
+                .map(|wt_elem| parse_original_item(&wt_elem, language))
+                .collect::<Result<Vec<_>, _>>()?;
+            (webtoons, false)
+        }
+        WtType::Canvas => {
+            let webtoons_selectors = Selector::parse(".challenge_lst li").unwrap();
+            let webtoons = document
+                .select(&webtoons_selectors)
+                .map(|wt_elem| parse_canvas_item(&wt_elem, language))
+                .collect::<Result<Vec<_>, _>>()?;
+            let has_more = !webtoons.is_empty() && has_next_page(&document, page);
+            (webtoons, has_more)
+        }
+    };
+
+    // every webtoon of the listing is from the requested genre, even when the card doesn't say it
+    for wt in webtoons.iter_mut() {
+        wt.genre.get_or_insert_with(|| genre.clone());
+    }
+
+    Ok(SearchResult {
+        webtoons,
+        page,
+        has_more,
+    })
+}
@@ -3,10 +3,14 @@ use std::path::Path;
 use futures::{stream::FuturesUnordered, StreamExt};
 use tokio::fs;
 
-use crate::DownloadState;
+use crate::{
+    local::{is_local_image, local_image_filename, read_local_image},
+    DownloadState,
+};
+
+const PREFIX: &str = "fosstoon_unique_id__";
 
 fn to_unique_filename(filename: &str, fuid: &str) -> String {
-    const PREFIX: &str = "fosstoon_unique_id__";
     match filename.starts_with(PREFIX) {
         true => filename.to_owned(),
         false => format!("{PREFIX}{fuid}__{filename}"),
@@ -26,14 +30,19 @@ pub async fn download_images<F: Fn(DownloadState) + Clone>(
         .iter()
         .map(|url| {
             let filename = url
-                .split("/")
-                .last()
+                .split(['/', '\\'])
+                .next_back()
                 .expect("Impossible no filename")
                 .split("?")
                 .next()
                 .expect("Impossible no filename");
+            // images of the local source share their names between episodes, unless they are already cached
+            let filename = match is_local_image(url) && !filename.starts_with(PREFIX) {
+                true => local_image_filename(url),
+                false => filename.to_string(),
+            };
             cache_dir
-                .join(to_unique_filename(filename, &fuid))
+                .join(to_unique_filename(&filename, &fuid))
                 .to_string_lossy()
                 .to_string()
         })
@@ -70,13 +79,24 @@ pub async fn download_images<F: Fn(DownloadState) + Clone>(
         let futures = images_url_to_cache.iter().enumerate().map(|(i, iurl)| {
             let value = http_client.clone();
             async move {
+                // images of the local source are copied from the disk
+                if is_local_image(iurl) {
+                    let src = iurl.clone();
+                    let data = tokio::task::spawn_blocking(move || read_local_image(&src))
+                        .await
+                        .map_err(|e| e.to_string())??;
+                    return Ok((i, data));
+                }
+
                 let resp = value
                     .get(iurl)
                     .header("Referer", "https://www.webtoons.com/")
                     .send()
-                    .await?;
+                    .await
+                    .map_err(|e| e.to_string())?;
 
-                Ok::<_, reqwest::Error>((i, resp.bytes().await))
+                let data = resp.bytes().await.map_err(|e| e.to_string())?;
+                Ok::<_, String>((i, data.to_vec()))
             }
         });
 
@@ -90,12 +110,7 @@ pub async fn download_images<F: Fn(DownloadState) + Clone>(
         let mut responses_data = vec![None; requests_num];
 
         while let Some(result) = futures_unordered.next().await {
-            let (order, bytes_resp) = result
-                .map(|(i, b_resp)| match b_resp {
-                    Ok(b) => Ok((i, b)),
-                    Err(e) => Err(e.to_string()),
-                })
-                .map_err(|e| e.to_string())??;
+            let (order, bytes_resp) = result?;
 
             responses_num += 1;
             info_cb(DownloadState::CachingImages(
@@ -1,12 +1,22 @@
-// only implements episode scrapping, as it seem the only problem with the "webtoon" crate
+pub mod comments;
+pub mod creator;
 pub mod episodes;
+pub mod genres;
 pub mod image_dl;
+pub mod local;
+pub mod ranking;
 pub mod recommandations;
+pub mod schedule;
 pub mod search;
+pub mod source;
 pub mod webtoon;
 
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
+use crate::source::SourceId;
+
 /* Type Definition */
 #[derive(Debug, Clone, Serialize)]
 pub enum DownloadState {
@@ -18,19 +28,58 @@ pub enum DownloadState {
     Completed,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct WebtoonId {
     pub wt_id: usize,
     pub wt_type: WtType,
+    /// defaults to `webtoons.com`, for the ids stored before sources existed
+    #[serde(default)]
+    pub source: SourceId,
+    /// the same title number is another series in another language
+    #[serde(default)]
+    pub language: Language,
 }
 
 impl WebtoonId {
+    /// id of a `webtoons.com` series
     pub fn new(wt_id: usize, wt_type: WtType) -> Self {
-        Self { wt_id, wt_type }
+        Self::from_source(SourceId::Webtoons, wt_id, wt_type)
+    }
+
+    pub fn from_source(source: SourceId, wt_id: usize, wt_type: WtType) -> Self {
+        Self {
+            wt_id,
+            wt_type,
+            source,
+            language: Language::default(),
+        }
+    }
+
+    pub fn with_language(self, language: Language) -> Self {
+        Self { language, ..self }
+    }
+
+    /// canonical key of the webtoon in the app stores, e.g. `webtoons:original:95:en`
+    ///
+    /// `wt_id` alone isn't unique: an original and a canvas can share the same title number
+    pub fn store_key(&self) -> String {
+        let source = match self.source {
+            SourceId::Webtoons => "webtoons",
+            SourceId::Local => "local",
+        };
+        let wt_type = match self.wt_type {
+            WtType::Original => "original",
+            WtType::Canvas => "canvas",
+        };
+        format!(
+            "{source}:{wt_type}:{}:{}",
+            self.wt_id,
+            self.language.url_code()
+        )
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum WtType {
     Canvas,
     Original,
@@ -58,6 +107,21 @@ pub enum Language {
     De,
 }
 
+impl Language {
+    /// language code used in the site urls
+    pub fn url_code(&self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::Zh => "zh-hant",
+            Language::Th => "th",
+            Language::Id => "id",
+            Language::Es => "es",
+            Language::Fr => "fr",
+            Language::De => "de",
+        }
+    }
+}
+
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Deserialize, Serialize, Ord, PartialOrd, PartialEq, Eq, Hash)]
 pub enum Genre {
@@ -105,10 +169,51 @@ pub enum Genre {
 }
 
 impl From<String> for Genre {
+    /// maps the genre as displayed by the site (e.g. "Slice of life", "Sci-fi") to its variant
     fn from(raw_genre: String) -> Self {
-        match serde_json::from_str::<Genre>(&raw_genre) {
-            Ok(g) => g,
-            Err(_) => Self::Other(raw_genre),
+        let normalized_genre = raw_genre
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>();
+        match normalized_genre.as_str() {
+            "comedy" => Self::Comedy,
+            "fantasy" => Self::Fantasy,
+            "romance" => Self::Romance,
+            "sliceoflife" => Self::SliceOfLife,
+            "scifi" | "sf" => Self::SciFi,
+            "drama" => Self::Drama,
+            "shortstory" => Self::ShortStory,
+            "action" => Self::Action,
+            "superhero" => Self::Superhero,
+            "heartwarming" => Self::Heartwarming,
+            "thriller" => Self::Thriller,
+            "horror" => Self::Horror,
+            "postapocalyptic" => Self::PostApocalyptic,
+            "zombies" => Self::Zombies,
+            "school" => Self::School,
+            "supernatural" => Self::Supernatural,
+            "animals" => Self::Animals,
+            "mystery" => Self::Mystery,
+            "historical" => Self::Historical,
+            "informative" | "tiptoon" => Self::Informative,
+            "sports" => Self::Sports,
+            "inspirational" => Self::Inspirational,
+            "allages" => Self::AllAges,
+            "lgbtq" => Self::LGBTQ,
+            "romanticfantasy" | "romancefantasy" => Self::RomanticFantasy,
+            "martialarts" => Self::MartialArts,
+            "westernpalace" => Self::WesternPalace,
+            "easternpalace" => Self::EasternPalace,
+            "matureromance" => Self::MatureRomance,
+            "timeslip" | "reincarnationtimetravel" => Self::TimeSlip,
+            "local" => Self::Local,
+            "cityoffice" | "modernworkplace" => Self::CityOffice,
+            "adaptation" => Self::Adaptation,
+            "shonen" => Self::Shonen,
+            "webnovel" => Self::WebNovel,
+            "graphicnovel" => Self::GraphicNovel,
+            _ => Self::Other(raw_genre),
         }
     }
 }
@@ -132,6 +237,24 @@ pub enum Weekday {
     Saturday,
 }
 
+impl FromStr for Weekday {
+    type Err = String;
+
+    /// parses the full or abbreviated english name of the day, case insensitive
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mon" | "monday" => Ok(Weekday::Monday),
+            "tue" | "tuesday" => Ok(Weekday::Tuesday),
+            "wed" | "wednesday" => Ok(Weekday::Wednesday),
+            "thu" | "thursday" => Ok(Weekday::Thursday),
+            "fri" | "friday" => Ok(Weekday::Friday),
+            "sat" | "saturday" => Ok(Weekday::Saturday),
+            "sun" | "sunday" => Ok(Weekday::Sunday),
+            _ => Err("Failed to parse weekday".to_string()),
+        }
+    }
+}
+
 /// Represents a kind of release schedule for Originals.
 ///
 /// For the days of the week, a Webtoon can have multiple.
@@ -161,16 +284,7 @@ impl TryFrom<String> for Schedule {
                 let weekdays = raw_schedule
                     .replace("every ", "")
                     .split(", ")
-                    .map(|s| match s {
-                        "mon" | "monday" => Ok(Weekday::Monday),
-                        "tue" | "tuesday" => Ok(Weekday::Tuesday),
-                        "wed" | "wednesday" => Ok(Weekday::Wednesday),
-                        "thu" | "thursday" => Ok(Weekday::Thursday),
-                        "fri" | "friday" => Ok(Weekday::Friday),
-                        "sat" | "saturday" => Ok(Weekday::Saturday),
-                        "sun" | "sunday" => Ok(Weekday::Sunday),
-                        _ => Err("Failed to parse weekday".to_string()),
-                    })
+                    .map(Weekday::from_str)
                     .collect::<Result<Vec<_>, String>>()?;
                 match weekdays.as_slice() {
                     &[single] => Ok(Self::Weekday(single)),
@@ -186,11 +300,14 @@ impl TryFrom<String> for Schedule {
 pub(crate) fn generate_webtoon_url(id: WebtoonId) -> String {
     match id.wt_type {
         WtType::Canvas => format!(
-            "https://www.webtoons.com/en/canvas/*/list?title_no={}",
+            "https://www.webtoons.com/{}/canvas/*/list?title_no={}",
+            id.language.url_code(),
             id.wt_id,
         ),
-        WtType::Original => {
-            format!("https://www.webtoons.com/en/*/*/list?title_no={}", id.wt_id)
-        }
+        WtType::Original => format!(
+            "https://www.webtoons.com/{}/*/*/list?title_no={}",
+            id.language.url_code(),
+            id.wt_id
+        ),
     }
 }
@@ -0,0 +1,549 @@
+//! Series read from the disk
+//!
+//! The library root is a directory where every sub-directory is a series, and every entry of a series is an episode:
+//! either a `.cbz` archive or a folder of images. A series folder containing images directly is a one-episode series.
+//!
+//! Metadata are read from the `ComicInfo.xml` of the series folder, or else of its first episode.
+
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    comments::{Post, PostBatch},
+    episodes::{EpisodeData, EpisodePreview},
+    search::{SearchFilter, SearchResult, WebtoonSearchInfo},
+    source::{ContentSource, SourceId},
+    webtoon::WebtoonInfo,
+    DownloadState, Genre, Language, WebtoonId, WtType,
+};
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "webp", "gif", "avif"];
+const COMIC_INFO: &str = "ComicInfo.xml";
+/// separates the archive path from the entry name in a panel path, e.g. `/comics/ep1.cbz::001.jpg`
+const ARCHIVE_ENTRY_SEPARATOR: &str = "::";
+
+static LIBRARY_ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// sets the directory scanned by the local source, `None` disables it
+pub fn set_library_root(root: Option<PathBuf>) {
+    if let Ok(mut library_root) = LIBRARY_ROOT.write() {
+        *library_root = root;
+    }
+}
+
+pub fn library_root() -> Option<PathBuf> {
+    LIBRARY_ROOT.read().ok().and_then(|root| root.clone())
+}
+
+/// Metadata of a `ComicInfo.xml`, every field is optional
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ComicInfo {
+    pub series: Option<String>,
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    pub writer: Option<String>,
+    pub genre: Option<String>,
+    pub year: Option<u32>,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+}
+
+impl ComicInfo {
+    fn parse(raw_xml: &str) -> Result<Self, String> {
+        let document = roxmltree::Document::parse(raw_xml).map_err(|e| e.to_string())?;
+        let field = |name: &str| {
+            document
+                .root_element()
+                .children()
+                .find(|node| node.has_tag_name(name))
+                .and_then(|node| node.text())
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+
+        Ok(Self {
+            series: field("Series"),
+            title: field("Title"),
+            summary: field("Summary"),
+            writer: field("Writer"),
+            genre: field("Genre"),
+            year: field("Year").and_then(|y| y.parse().ok()),
+            month: field("Month").and_then(|m| m.parse().ok()),
+            day: field("Day").and_then(|d| d.parse().ok()),
+        })
+    }
+
+    fn release_date(&self) -> Option<String> {
+        match (self.year, self.month, self.day) {
+            (Some(y), Some(m), Some(d)) => Some(format!("{y}-{m:02}-{d:02}")),
+            (Some(y), Some(m), None) => Some(format!("{y}-{m:02}")),
+            (Some(y), None, _) => Some(y.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// One episode on the disk
+#[derive(Debug, Clone)]
+enum LocalEpisode {
+    Archive(PathBuf),
+    Folder(PathBuf),
+}
+
+impl LocalEpisode {
+    fn path(&self) -> &Path {
+        match self {
+            LocalEpisode::Archive(path) | LocalEpisode::Folder(path) => path,
+        }
+    }
+
+    fn name(&self) -> String {
+        self.path()
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// panels in reading order, archive entries are given as `archive::entry`
+    fn panels(&self) -> Result<Vec<String>, String> {
+        match self {
+            LocalEpisode::Folder(dir) => Ok(list_images(dir)?
+                .into_iter()
+                .map(|img| img.to_string_lossy().to_string())
+                .collect()),
+            LocalEpisode::Archive(archive_path) => {
+                let archive = File::open(archive_path).map_err(|e| e.to_string())?;
+                let archive = zip::ZipArchive::new(archive).map_err(|e| e.to_string())?;
+
+                let mut entries = archive
+                    .file_names()
+                    .filter(|name| is_image(Path::new(name)))
+                    .map(|name| name.to_string())
+                    .collect::<Vec<_>>();
+                entries.sort_by(|a, b| natural_cmp(a, b));
+                Ok(entries
+                    .into_iter()
+                    .map(|entry| {
+                        format!(
+                            "{}{ARCHIVE_ENTRY_SEPARATOR}{entry}",
+                            archive_path.to_string_lossy()
+                        )
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    fn comic_info(&self) -> Option<ComicInfo> {
+        let raw_xml = match self {
+            LocalEpisode::Folder(dir) => std::fs::read_to_string(dir.join(COMIC_INFO)).ok()?,
+            LocalEpisode::Archive(archive_path) => {
+                let archive = File::open(archive_path).ok()?;
+                let mut archive = zip::ZipArchive::new(archive).ok()?;
+                let mut entry = archive.by_name(COMIC_INFO).ok()?;
+                let mut raw_xml = String::new();
+                entry.read_to_string(&mut raw_xml).ok()?;
+                raw_xml
+            }
+        };
+        ComicInfo::parse(&raw_xml).ok()
+    }
+}
+
+/// One series folder of the library
+#[derive(Debug, Clone)]
+struct LocalSeries {
+    id: WebtoonId,
+    dir: PathBuf,
+    episodes: Vec<LocalEpisode>,
+}
+
+impl LocalSeries {
+    fn from_dir(dir: PathBuf) -> Result<Option<Self>, String> {
+        let dir_name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or("No series folder name")?;
+
+        let mut episodes = vec![];
+        for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.is_dir() && !list_images(&path)?.is_empty() {
+                episodes.push(LocalEpisode::Folder(path));
+            } else if has_extension(&path, &["cbz"]) {
+                episodes.push(LocalEpisode::Archive(path));
+            }
+        }
+        // images right in the series folder
+        if episodes.is_empty() && !list_images(&dir)?.is_empty() {
+            episodes.push(LocalEpisode::Folder(dir.clone()));
+        }
+        if episodes.is_empty() {
+            return Ok(None);
+        }
+        episodes.sort_by(|a, b| natural_cmp(&a.name(), &b.name()));
+
+        Ok(Some(Self {
+            id: WebtoonId::from_source(
+                SourceId::Local,
+                series_id(&dir_name),
+                WtType::Original,
+            ),
+            dir,
+            episodes,
+        }))
+    }
+
+    fn comic_info(&self) -> ComicInfo {
+        std::fs::read_to_string(self.dir.join(COMIC_INFO))
+            .ok()
+            .and_then(|raw_xml| ComicInfo::parse(&raw_xml).ok())
+            .or_else(|| self.episodes.first().and_then(|ep| ep.comic_info()))
+            .unwrap_or_default()
+    }
+
+    fn title(&self, comic_info: &ComicInfo) -> String {
+        comic_info.series.clone().unwrap_or_else(|| {
+            self.dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+
+    /// a `cover` image of the series folder, or else the first panel
+    fn cover(&self) -> Result<String, String> {
+        if let Some(cover) = list_images(&self.dir)?.into_iter().find(|img| {
+            img.file_stem().is_some_and(|stem| {
+                matches!(
+                    stem.to_string_lossy().to_lowercase().as_str(),
+                    "cover" | "folder"
+                )
+            })
+        }) {
+            return Ok(cover.to_string_lossy().to_string());
+        }
+        self.episodes
+            .first()
+            .ok_or("No episodes")?
+            .panels()?
+            .into_iter()
+            .next()
+            .ok_or("No panels".to_string())
+    }
+
+    fn search_info(&self) -> Result<WebtoonSearchInfo, String> {
+        let comic_info = self.comic_info();
+        Ok(WebtoonSearchInfo {
+            id: self.id,
+            title: self.title(&comic_info),
+            thumbnail: self.cover()?,
+            creator: comic_info.writer.clone(),
+            genre: comic_info.genre.clone().map(Genre::from),
+        })
+    }
+}
+
+/// every readable series of the library, sorted by folder name
+///
+/// an unreadable folder is skipped rather than hiding the whole library
+fn scan_library() -> Result<Vec<LocalSeries>, String> {
+    let root = library_root().ok_or("No local library folder set")?;
+
+    let mut series = vec![];
+    for entry in std::fs::read_dir(&root).map_err(|e| e.to_string())? {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        if path.is_dir()
+            && let Ok(Some(s)) = LocalSeries::from_dir(path)
+        {
+            series.push(s);
+        }
+    }
+    series.sort_by(|a, b| natural_cmp(&a.dir.to_string_lossy(), &b.dir.to_string_lossy()));
+    Ok(series)
+}
+
+fn find_series(id: WebtoonId) -> Result<LocalSeries, String> {
+    scan_library()?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or("Local series not found, has it been moved?".to_string())
+}
+
+/// runs the blocking disk operations outside of the async runtime
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// every series of the library, thumbnails are disk paths to go through `download_images`
+///
+/// the series whose cover can't be read, e.g. from a corrupt archive, are left out
+pub async fn list_local_series() -> Result<Vec<WebtoonSearchInfo>, String> {
+    blocking(|| {
+        Ok(scan_library()?
+            .iter()
+            .filter_map(|s| s.search_info().ok())
+            .collect())
+    })
+    .await
+}
+
+/// Series from the user's library folder
+pub struct LocalSource;
+
+impl ContentSource for LocalSource {
+    fn id(&self) -> SourceId {
+        SourceId::Local
+    }
+
+    /// matches the titles, everything fits in one page
+    async fn search(
+        &self,
+        query: &str,
+        _filter: SearchFilter,
+        _page: usize,
+        _language: Language,
+    ) -> Result<SearchResult, String> {
+        let query = query.trim().to_lowercase();
+        let mut webtoons = list_local_series().await?;
+        webtoons.retain(|wt| wt.title.to_lowercase().contains(&query));
+
+        Ok(SearchResult {
+            webtoons,
+            page: 1,
+            has_more: false,
+        })
+    }
+
+    async fn series_info<F: Fn(DownloadState) + Clone>(
+        &self,
+        id: WebtoonId,
+        info_cb: F,
+    ) -> Result<WebtoonInfo, String> {
+        info_cb(DownloadState::WebtoonData(0));
+        let (series, comic_info, thumbnail) = blocking(move || {
+            let series = find_series(id)?;
+            let comic_info = series.comic_info();
+            let thumbnail = series.cover()?;
+            Ok((series, comic_info, thumbnail))
+        })
+        .await?;
+        info_cb(DownloadState::WebtoonData(100));
+
+        Ok(WebtoonInfo {
+            id,
+            title: series.title(&comic_info),
+            thumbnail,
+            banner: None,
+            creators: comic_info.writer.clone().into_iter().collect(),
+            creator_id: None,
+            genres: comic_info
+                .genre
+                .clone()
+                .map(Genre::from)
+                .into_iter()
+                .collect(),
+            schedule: None,
+            views: "-".to_string(),
+            subs: "-".to_string(),
+            summary: comic_info.summary.clone().unwrap_or_default(),
+            episodes: None,
+            // rescanning the folder is cheap, new episodes are picked up on each visit
+            refresh_eps_at: SystemTime::now(),
+            expired_at: SystemTime::now()
+                .checked_add(Duration::from_secs(86400)) // add 1 days before refresh
+                .ok_or("are we near 2038?")?,
+        })
+    }
+
+    async fn episodes<F: Fn(DownloadState) + Clone>(
+        &self,
+        id: WebtoonId,
+        after_ep: usize,
+        info_cb: F,
+    ) -> Result<Vec<EpisodePreview>, String> {
+        info_cb(DownloadState::EpisodeInfo(0));
+        let episodes = blocking(move || {
+            let series = find_series(id)?;
+            series
+                .episodes
+                .iter()
+                .enumerate()
+                .skip(after_ep)
+                .map(|(i, episode)| {
+                    let comic_info = episode.comic_info().unwrap_or_default();
+                    Ok(EpisodePreview {
+                        parent_wt_id: id,
+                        number: i + 1,
+                        title: comic_info.title.clone().unwrap_or_else(|| episode.name()),
+                        thumbnail: episode.panels()?.into_iter().next().ok_or("No panels")?,
+                        likes: 0,
+                        posted_at: comic_info.release_date().unwrap_or_default(),
+                        ep_url: episode.path().to_string_lossy().to_string(),
+                    })
+                })
+                .collect()
+        })
+        .await?;
+        info_cb(DownloadState::EpisodeInfo(100));
+
+        Ok(episodes)
+    }
+
+    async fn episode_data<F: Fn(DownloadState) + Clone>(
+        &self,
+        episode: &EpisodePreview,
+        info_cb: F,
+    ) -> Result<EpisodeData, String> {
+        info_cb(DownloadState::EpisodeInfo(0));
+
+        let ep_path = PathBuf::from(&episode.ep_url);
+        let local_episode = match ep_path.is_dir() {
+            true => LocalEpisode::Folder(ep_path),
+            false => LocalEpisode::Archive(ep_path),
+        };
+        let (panels, comic_info) = blocking(move || {
+            Ok((
+                local_episode.panels()?,
+                local_episode.comic_info().unwrap_or_default(),
+            ))
+        })
+        .await?;
+
+        info_cb(DownloadState::EpisodeInfo(100));
+
+        Ok(EpisodeData {
+            parent_wt_id: episode.parent_wt_id,
+            number: episode.number,
+            panels,
+            author_note: comic_info.summary,
+            author_name: comic_info.writer.unwrap_or_default(),
+            author_id: None,
+            author_thumb: None,
+        })
+    }
+
+    /// local series have no comments section
+    async fn comments(
+        &self,
+        _id: WebtoonId,
+        _ep_num: usize,
+        _cursor: Option<&str>,
+    ) -> Result<PostBatch, String> {
+        Ok(PostBatch::default())
+    }
+
+    async fn top_comments(&self, _id: WebtoonId, _ep_num: usize) -> Result<Vec<Post>, String> {
+        Ok(vec![])
+    }
+
+    async fn replies(
+        &self,
+        _id: WebtoonId,
+        _ep_num: usize,
+        _post_id: &str,
+        _cursor: Option<&str>,
+    ) -> Result<PostBatch, String> {
+        Ok(PostBatch::default())
+    }
+}
+
+/* HELPERS */
+
+/// whether `src` is a disk path given by the local source rather than an url
+pub(crate) fn is_local_image(src: &str) -> bool {
+    !src.starts_with("http://") && !src.starts_with("https://")
+}
+
+/// reads a panel given by the local source, either a file or an `archive::entry`
+pub(crate) fn read_local_image(src: &str) -> Result<Vec<u8>, String> {
+    match src.rsplit_once(ARCHIVE_ENTRY_SEPARATOR) {
+        Some((archive_path, entry_name)) if has_extension(Path::new(archive_path), &["cbz"]) => {
+            let archive = File::open(archive_path).map_err(|e| e.to_string())?;
+            let mut archive = zip::ZipArchive::new(archive).map_err(|e| e.to_string())?;
+            let mut entry = archive.by_name(entry_name).map_err(|e| e.to_string())?;
+
+            let mut data = vec![];
+            entry.read_to_end(&mut data).map_err(|e| e.to_string())?;
+            Ok(data)
+        }
+        _ => std::fs::read(src).map_err(|e| e.to_string()),
+    }
+}
+
+/// file name under which a local image is cached, the same panel name is shared by every episode
+pub(crate) fn local_image_filename(src: &str) -> String {
+    let extension = Path::new(src)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "jpg".to_string());
+    format!("local_{:016x}.{extension}", stable_hash(src))
+}
+
+/// the frontend is wasm32, ids must fit in its 32 bits `usize` and be exact as JavaScript numbers
+fn series_id(dir_name: &str) -> usize {
+    let hash = stable_hash(dir_name);
+    ((hash >> 32) ^ hash) as u32 as usize
+}
+
+/// FNV-1a, unlike `DefaultHasher` it doesn't change between rust versions
+fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .is_some_and(|ext| extensions.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+}
+
+fn is_image(path: &Path) -> bool {
+    has_extension(path, &IMAGE_EXTENSIONS)
+}
+
+/// images directly in `dir`, in reading order
+fn list_images(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut images = std::fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && is_image(path))
+        .collect::<Vec<_>>();
+    images.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+    Ok(images)
+}
+
+/// compares names with their numbers by value, so that `ep2` comes before `ep10`
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    fn chunks(s: &str) -> Vec<(Option<u64>, String)> {
+        let mut chunks = Vec::<(Option<u64>, String)>::new();
+        let mut current = String::new();
+        for c in s.to_lowercase().chars() {
+            let is_digit_chunk = current.chars().next().is_some_and(|f| f.is_ascii_digit());
+            if !current.is_empty() && c.is_ascii_digit() != is_digit_chunk {
+                chunks.push((current.parse().ok(), std::mem::take(&mut current)));
+            }
+            current.push(c);
+        }
+        if !current.is_empty() {
+            chunks.push((current.parse().ok(), current));
+        }
+        chunks
+    }
+
+    chunks(a).cmp(&chunks(b))
+}
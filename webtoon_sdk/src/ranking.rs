@@ -0,0 +1,181 @@
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::{search::WebtoonSearchInfo, Genre, Language, WebtoonId, WtType};
+
+/// The ranking views of the site
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RankingKind {
+    /// Originals and Canvas mixed, by recent activity
+    #[default]
+    Trending,
+    /// Originals and Canvas mixed, by views
+    Popular,
+    Originals,
+    Canvas,
+    /// Canvas series climbing the fastest
+    RisingCanvas,
+}
+
+impl RankingKind {
+    fn url(&self, genre: Option<&Genre>, language: Language) -> String {
+        let genre_tab = genre.map(|g| g.canvas_tab()).unwrap_or("ALL".to_string());
+        let lang = language.url_code();
+        match self {
+            RankingKind::Trending => {
+                format!("https://www.webtoons.com/{lang}/ranking/trending?genre={genre_tab}")
+            }
+            RankingKind::Popular => {
+                format!("https://www.webtoons.com/{lang}/ranking/popular?genre={genre_tab}")
+            }
+            RankingKind::Originals => {
+                format!("https://www.webtoons.com/{lang}/ranking/originals?genre={genre_tab}")
+            }
+            RankingKind::Canvas => {
+                format!("https://www.webtoons.com/{lang}/ranking/canvas?genre={genre_tab}")
+            }
+            RankingKind::RisingCanvas => format!(
+                "https://www.webtoons.com/{lang}/ranking/canvas?genre={genre_tab}&sortOrder=RISING"
+            ),
+        }
+    }
+}
+
+/// How the webtoon moved in the ranking since the last update
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankMovement {
+    Up(usize),
+    Down(usize),
+    Same,
+    /// just entered the ranking
+    New,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RankedWebtoon {
+    /// position in the ranking, starts at `1`
+    pub rank: usize,
+    pub movement: RankMovement,
+    pub webtoon: WebtoonSearchInfo,
+}
+
+/// fetch one of the site's ranking, optionally restricted to a genre
+pub async fn fetch_ranking(
+    kind: RankingKind,
+    genre: Option<&Genre>,
+    language: Language,
+) -> Result<Vec<RankedWebtoon>, String> {
+    let resp = reqwest::get(kind.url(genre, language))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let raw_html = resp.text().await.map_err(|e| e.to_string())?;
+    let document = Html::parse_document(&raw_html);
+
+    let ranking_selector = Selector::parse(".ranking_list > li").unwrap();
+    document
+        .select(&ranking_selector)
+        .enumerate()
+        .map(|(i, rank_elem)| parse_ranked_item(&rank_elem, i + 1, language))
+        .collect()
+}
+
+/* HELPERS */
+
+fn parse_ranked_item(
+    rank_elem: &ElementRef<'_>,
+    fallback_rank: usize,
+    language: Language,
+) -> Result<RankedWebtoon, String> {
+    let link_selector = Selector::parse("a").unwrap();
+    let rank_selector = Selector::parse(".rank_num").unwrap();
+    let movement_selector = Selector::parse(".rank_change").unwrap();
+    let thumb_selector = Selector::parse(".image_wrap > img").unwrap();
+    let title_selector = Selector::parse(".info_text .title").unwrap();
+    let author_selector = Selector::parse(".info_text .author").unwrap();
+    let genre_selector = Selector::parse(".genre").unwrap();
+
+    let link = rank_elem
+        .select(&link_selector)
+        .next()
+        .ok_or("No ranking link")?;
+    let wt_id = link
+        .attr("data-title-no")
+        .ok_or("No wt id")?
+        .trim()
+        .parse::<usize>()
+        .map_err(|e| e.to_string())?;
+    let wt_type = match link
+        .attr("data-webtoon-type")
+        .unwrap_or("webtoon")
+        .to_lowercase()
+        .trim()
+    {
+        "webtoon" => WtType::Original,
+        "challenge" => WtType::Canvas,
+        _ => return Err("Failed to parse wt type".to_string()),
+    };
+
+    let rank = rank_elem
+        .select(&rank_selector)
+        .next()
+        .and_then(|r| r.text().collect::<String>().trim().parse::<usize>().ok())
+        .unwrap_or(fallback_rank);
+    let movement = match rank_elem.select(&movement_selector).next() {
+        Some(movement_elem) => {
+            let delta = movement_elem
+                .text()
+                .collect::<String>()
+                .trim()
+                .parse::<usize>()
+                .unwrap_or_default();
+            let classes = movement_elem.value().classes().collect::<Vec<_>>();
+            if classes.contains(&"new") {
+                RankMovement::New
+            } else if classes.contains(&"up") && delta > 0 {
+                RankMovement::Up(delta)
+            } else if classes.contains(&"down") && delta > 0 {
+                RankMovement::Down(delta)
+            } else {
+                RankMovement::Same
+            }
+        }
+        None => RankMovement::Same,
+    };
+
+    let title = rank_elem
+        .select(&title_selector)
+        .next()
+        .ok_or("No title".to_string())?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    let thumbnail = rank_elem
+        .select(&thumb_selector)
+        .next()
+        .ok_or("No thumbnail".to_string())?
+        .attr("src")
+        .ok_or("No src".to_string())?
+        .to_string();
+    let creator = rank_elem
+        .select(&author_selector)
+        .next()
+        .map(|a| a.text().collect::<String>().trim().to_string());
+    let genre = rank_elem
+        .select(&genre_selector)
+        .next()
+        .map(|g| Genre::from(g.text().collect::<String>().trim().to_string()));
+
+    Ok(RankedWebtoon {
+        rank,
+        movement,
+        webtoon: WebtoonSearchInfo {
+            id: WebtoonId::new(wt_id, wt_type).with_language(language),
+            title,
+            thumbnail,
+            creator,
+            genre,
+        },
+    })
+}
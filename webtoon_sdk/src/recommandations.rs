@@ -1,54 +1,23 @@
-use nanorand::{Rng, WyRand};
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 
-use crate::{search::WebtoonSearchInfo, WebtoonId, WtType};
+use crate::{search::WebtoonSearchInfo, Genre, Language, WebtoonId, WtType};
 
-pub async fn fetch_original() -> Result<Vec<WebtoonSearchInfo>, String> {
-    let resp = reqwest::get("https://www.webtoons.com/en/originals")
-        .await
-        .map_err(|e| e.to_string())?;
+pub async fn fetch_original(language: Language) -> Result<Vec<WebtoonSearchInfo>, String> {
+    let resp = reqwest::get(format!(
+        "https://www.webtoons.com/{}/originals",
+        language.url_code()
+    ))
+    .await
+    .map_err(|e| e.to_string())?;
 
     let raw_html = resp.text().await.map_err(|e| e.to_string())?;
     let document = Html::parse_document(&raw_html);
 
     let webtoons_selectors = Selector::parse(".webtoon_list > li").unwrap();
-    let id_selector = Selector::parse("a").unwrap();
-    let title_selector = Selector::parse(".title").unwrap();
-    let thumb_selector = Selector::parse(".image_wrap > img").unwrap();
 
     let mut todays_originals = vec![];
     for wt_elem in document.select(&webtoons_selectors) {
-        let id = wt_elem
-            .select(&id_selector)
-            .next()
-            .ok_or("No id :(".to_string())?
-            .attr("data-title-no")
-            .ok_or("No id")?
-            .trim()
-            .parse::<usize>()
-            .map_err(|e| e.to_string())?;
-        let title = wt_elem
-            .select(&title_selector)
-            .next()
-            .ok_or("No title".to_string())?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string();
-        let thumbnail = wt_elem
-            .select(&thumb_selector)
-            .next()
-            .ok_or("No thumbnail".to_string())?
-            .attr("src")
-            .ok_or("No src".to_string())?
-            .to_string();
-
-        todays_originals.push(WebtoonSearchInfo {
-            id: WebtoonId::new(id, WtType::Original),
-            title,
-            thumbnail,
-            creator: None,
-        });
+        todays_originals.push(parse_original_item(&wt_elem, language)?);
 
         if todays_originals.len() >= 20 {
             break;
@@ -58,14 +27,15 @@ pub async fn fetch_original() -> Result<Vec<WebtoonSearchInfo>, String> {
     Ok(todays_originals)
 }
 
-pub async fn fetch_canvas() -> Result<Vec<WebtoonSearchInfo>, String> {
-    let canvas_page = {
-        let mut rng = WyRand::new();
-        rng.generate_range(1_u8..=5)
-    };
-
+/// fetch one page of the most popular canvas, `page` starts at `1`
+pub async fn fetch_canvas(
+    page: usize,
+    language: Language,
+) -> Result<Vec<WebtoonSearchInfo>, String> {
     let resp = reqwest::get(&format!(
-        "https://www.webtoons.com/en/canvas/list?genreTab=ALL&sortOrder=MANA&page={canvas_page}",
+        "https://www.webtoons.com/{}/canvas/list?genreTab=ALL&sortOrder=MANA&page={}",
+        language.url_code(),
+        page.max(1)
     ))
     .await
     .map_err(|e| e.to_string())?;
@@ -74,56 +44,124 @@ pub async fn fetch_canvas() -> Result<Vec<WebtoonSearchInfo>, String> {
     let document = Html::parse_document(&raw_html);
 
     let webtoons_selectors = Selector::parse(".challenge_lst li").unwrap();
+
+    document
+        .select(&webtoons_selectors)
+        .map(|wt_elem| parse_canvas_item(&wt_elem, language))
+        .collect()
+}
+
+/* HELPERS */
+
+/// parses one `.webtoon_list > li` card, as found on the originals pages
+pub(crate) fn parse_original_item(
+    wt_elem: &ElementRef<'_>,
+    language: Language,
+) -> Result<WebtoonSearchInfo, String> {
+    let id_selector = Selector::parse("a").unwrap();
+    let title_selector = Selector::parse(".title").unwrap();
+    let thumb_selector = Selector::parse(".image_wrap > img").unwrap();
+    let author_selector = Selector::parse(".author").unwrap();
+    let genre_selector = Selector::parse(".genre").unwrap();
+
+    let id = wt_elem
+        .select(&id_selector)
+        .next()
+        .ok_or("No id :(".to_string())?
+        .attr("data-title-no")
+        .ok_or("No id")?
+        .trim()
+        .parse::<usize>()
+        .map_err(|e| e.to_string())?;
+    let title = wt_elem
+        .select(&title_selector)
+        .next()
+        .ok_or("No title".to_string())?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    let thumbnail = wt_elem
+        .select(&thumb_selector)
+        .next()
+        .ok_or("No thumbnail".to_string())?
+        .attr("src")
+        .ok_or("No src".to_string())?
+        .to_string();
+    let creator = wt_elem
+        .select(&author_selector)
+        .next()
+        .map(|a| a.text().collect::<String>().trim().to_string());
+    let genre = wt_elem
+        .select(&genre_selector)
+        .next()
+        .map(|g| Genre::from(g.text().collect::<String>().trim().to_string()));
+
+    Ok(WebtoonSearchInfo {
+        id: WebtoonId::new(id, WtType::Original).with_language(language),
+        title,
+        thumbnail,
+        creator,
+        genre,
+    })
+}
+
+/// parses one `.challenge_lst li` card, as found on the canvas pages
+pub(crate) fn parse_canvas_item(
+    wt_elem: &ElementRef<'_>,
+    language: Language,
+) -> Result<WebtoonSearchInfo, String> {
     let id_selector = Selector::parse("a").unwrap();
     let title_selector = Selector::parse(".subj").unwrap();
     let thumb_selector = Selector::parse(".img_area > img").unwrap();
     let author_selector = Selector::parse(".author").unwrap();
+    let genre_selector = Selector::parse(".genre").unwrap();
 
-    let mut canvas = vec![];
-    for wt_elem in document.select(&webtoons_selectors) {
-        let id = wt_elem
-            .select(&id_selector)
-            .next()
-            .ok_or("No id :(".to_string())?
-            .attr("href")
-            .ok_or("No id")?
-            .trim()
-            .split("=")
-            .nth(1)
-            .ok_or("No id")?
-            .parse::<usize>()
-            .map_err(|e| e.to_string())?;
-        let title = wt_elem
-            .select(&title_selector)
-            .next()
-            .ok_or("No title".to_string())?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string();
-        let thumbnail = wt_elem
-            .select(&thumb_selector)
-            .next()
-            .ok_or("No thumbnail".to_string())?
-            .attr("src")
-            .ok_or("No src".to_string())?
-            .to_string();
-        let creator = wt_elem
-            .select(&author_selector)
-            .next()
-            .ok_or("No author".to_string())?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string();
-
-        canvas.push(WebtoonSearchInfo {
-            id: WebtoonId::new(id, WtType::Canvas),
-            title,
-            thumbnail,
-            creator: Some(creator),
-        });
-    }
+    let id = wt_elem
+        .select(&id_selector)
+        .next()
+        .ok_or("No id :(".to_string())?
+        .attr("href")
+        .ok_or("No id")?
+        .trim()
+        .split("=")
+        .nth(1)
+        .ok_or("No id")?
+        .parse::<usize>()
+        .map_err(|e| e.to_string())?;
+    let title = wt_elem
+        .select(&title_selector)
+        .next()
+        .ok_or("No title".to_string())?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    let thumbnail = wt_elem
+        .select(&thumb_selector)
+        .next()
+        .ok_or("No thumbnail".to_string())?
+        .attr("src")
+        .ok_or("No src".to_string())?
+        .to_string();
+    let creator = wt_elem
+        .select(&author_selector)
+        .next()
+        .ok_or("No author".to_string())?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    let genre = wt_elem
+        .select(&genre_selector)
+        .next()
+        .map(|g| Genre::from(g.text().collect::<String>().trim().to_string()));
 
-    Ok(canvas)
+    Ok(WebtoonSearchInfo {
+        id: WebtoonId::new(id, WtType::Canvas).with_language(language),
+        title,
+        thumbnail,
+        creator: Some(creator),
+        genre,
+    })
 }
@@ -0,0 +1,104 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::{recommandations::parse_original_item, search::WebtoonSearchInfo, Language, Weekday};
+
+/// The originals release calendar, as shown on the originals page
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OriginalsSchedule {
+    /// daily webtoons are listed under every weekday
+    pub weekdays: BTreeMap<Weekday, Vec<WebtoonSearchInfo>>,
+    pub completed: Vec<WebtoonSearchInfo>,
+}
+
+impl OriginalsSchedule {
+    pub fn releasing_on(&self, weekday: Weekday) -> &[WebtoonSearchInfo] {
+        self.weekdays
+            .get(&weekday)
+            .map(|wts| wts.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// fetch the whole originals calendar (every weekday and the completed series)
+pub async fn fetch_originals_schedule(language: Language) -> Result<OriginalsSchedule, String> {
+    let (weekdays, completed) = futures::future::join(
+        fetch_weekdays_schedule(language),
+        fetch_completed_originals(language),
+    )
+    .await;
+
+    Ok(OriginalsSchedule {
+        weekdays: weekdays?,
+        completed: completed?,
+    })
+}
+
+async fn fetch_weekdays_schedule(
+    language: Language,
+) -> Result<BTreeMap<Weekday, Vec<WebtoonSearchInfo>>, String> {
+    let resp = reqwest::get(format!(
+        "https://www.webtoons.com/{}/originals",
+        language.url_code()
+    ))
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let raw_html = resp.text().await.map_err(|e| e.to_string())?;
+    let document = Html::parse_document(&raw_html);
+
+    let days_selector = Selector::parse(".daily_section").unwrap();
+    let day_title_selector = Selector::parse(".sub_title").unwrap();
+    let webtoons_selectors = Selector::parse(".webtoon_list > li").unwrap();
+
+    let mut weekdays = BTreeMap::new();
+    for day_elem in document.select(&days_selector) {
+        // the section is tagged with a `_list_MONDAY` like class, the title is used as fallback
+        let weekday = match day_elem
+            .value()
+            .classes()
+            .find_map(|c| c.strip_prefix("_list_"))
+        {
+            Some(raw_weekday) => Weekday::from_str(raw_weekday)?,
+            None => Weekday::from_str(
+                &day_elem
+                    .select(&day_title_selector)
+                    .next()
+                    .ok_or("No weekday")?
+                    .text()
+                    .collect::<String>(),
+            )?,
+        };
+
+        let day_webtoons = day_elem
+            .select(&webtoons_selectors)
+            .map(|wt_elem| parse_original_item(&wt_elem, language))
+            .collect::<Result<Vec<_>, _>>()?;
+        weekdays
+            .entry(weekday)
+            .or_insert_with(Vec::new)
+            .extend(day_webtoons);
+    }
+
+    Ok(weekdays)
+}
+
+async fn fetch_completed_originals(language: Language) -> Result<Vec<WebtoonSearchInfo>, String> {
+    let resp = reqwest::get(format!(
+        "https://www.webtoons.com/{}/originals/complete",
+        language.url_code()
+    ))
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let raw_html = resp.text().await.map_err(|e| e.to_string())?;
+    let document = Html::parse_document(&raw_html);
+
+    let webtoons_selectors = Selector::parse(".webtoon_list > li").unwrap();
+    document
+        .select(&webtoons_selectors)
+        .map(|wt_elem| parse_original_item(&wt_elem, language))
+        .collect()
+}
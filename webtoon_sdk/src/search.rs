@@ -1,7 +1,7 @@
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
-use crate::{WebtoonId, WtType};
+use crate::{Genre, Language, WebtoonId, WtType};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WebtoonSearchInfo {
@@ -12,76 +12,178 @@ pub struct WebtoonSearchInfo {
 
     /// option because depending on whether it's an orignal or not the data can't be scrapped easily
     pub creator: Option<String>,
+    /// only filled when the scrapped page shows it (search results, genre listings...)
+    #[serde(default)]
+    pub genre: Option<Genre>,
+}
+
+/// Which kind of webtoons a search should return
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchFilter {
+    #[default]
+    All,
+    Originals,
+    Canvas,
+}
+
+/// One page of search results
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchResult {
+    pub webtoons: Vec<WebtoonSearchInfo>,
+    /// page number of this result, starts at `1`
+    pub page: usize,
+    /// whether requesting `page + 1` will return more webtoons
+    pub has_more: bool,
+}
+
+impl SearchResult {
+    /// only keeps the webtoons of the requested genre
+    ///
+    /// webtoons whose genre couldn't be scrapped are removed too
+    pub fn filter_genre(mut self, genre: &Genre) -> Self {
+        self.webtoons.retain(|wt| wt.genre.as_ref() == Some(genre));
+        self
+    }
 }
 
 impl WebtoonSearchInfo {
-    pub async fn from_query(query: &str) -> Result<Vec<WebtoonSearchInfo>, String> {
+    /// search webtoons matching `query`, `page` starts at `1`
+    ///
+    /// with `SearchFilter::All` the originals and canvas pages are fetched together and merged (originals first)
+    pub async fn from_query(
+        query: &str,
+        filter: SearchFilter,
+        page: usize,
+        language: Language,
+    ) -> Result<SearchResult, String> {
+        let page = page.max(1);
         let encoded_query = urlencoding::encode(query);
-        let resp = reqwest::get(format!(
-            "https://www.webtoons.com/en/search?keyword={encoded_query}"
-        ))
-        .await
-        .map_err(|e| e.to_string())?;
-
-        let raw_html = resp.text().await.map_err(|e| e.to_string())?;
-        let document = Html::parse_document(&raw_html);
-
-        let webtoons_selectors = Selector::parse(".webtoon_list > li > a").unwrap();
-        let thumb_selector = Selector::parse(".image_wrap > img").unwrap();
-        let title_selector = Selector::parse(".info_text > .title").unwrap();
-        let author_selector = Selector::parse(".info_text > .author").unwrap();
-
-        let mut search_results = vec![];
-        for wt_elem in document.select(&webtoons_selectors) {
-            let wt_id = wt_elem
-                .attr("data-title-no")
-                .ok_or("No wt id")?
-                .trim()
-                .parse::<usize>()
-                .map_err(|e| e.to_string())?;
-            let wt_type = match wt_elem
-                .attr("data-webtoon-type")
-                .ok_or("No wt type")?
-                .to_lowercase()
-                .trim()
-            {
-                "webtoon" => WtType::Original,
-                "challenge" => WtType::Canvas,
-                _ => return Err("Failed to parse wt type".to_string()),
-            };
-
-            let title = wt_elem
-                .select(&title_selector)
-                .next()
-                .ok_or("No title".to_string())?
-                .text()
-                .collect::<String>()
-                .trim()
-                .to_string();
-            let thumbnail = wt_elem
-                .select(&thumb_selector)
-                .next()
-                .ok_or("No thumbnail".to_string())?
-                .attr("src")
-                .ok_or("No src".to_string())?
-                .to_string();
-            let creator = wt_elem
-                .select(&author_selector)
-                .next()
-                .ok_or("No author".to_string())?
-                .text()
-                .collect::<String>()
-                .trim()
-                .to_string();
-
-            search_results.push(WebtoonSearchInfo {
-                id: WebtoonId::new(wt_id, wt_type),
-                title,
-                thumbnail,
-                creator: Some(creator),
-            });
-        }
-
-        Ok(search_results)
+
+        let (webtoons, has_more) = match filter {
+            SearchFilter::Originals => {
+                scrap_search_page(&encoded_query, WtType::Original, page, language).await?
+            }
+            SearchFilter::Canvas => {
+                scrap_search_page(&encoded_query, WtType::Canvas, page, language).await?
+            }
+            SearchFilter::All => {
+                let (originals, canvas) = futures::future::join(
+                    scrap_search_page(&encoded_query, WtType::Original, page, language),
+                    scrap_search_page(&encoded_query, WtType::Canvas, page, language),
+                )
+                .await;
+                let ((mut originals, originals_more), (mut canvas, canvas_more)) =
+                    (originals?, canvas?);
+
+                originals.append(&mut canvas);
+                (originals, originals_more || canvas_more)
+            }
+        };
+
+        Ok(SearchResult {
+            webtoons,
+            page,
+            has_more,
+        })
     }
 }
+
+/// scraps one page of the type specific search, returns the webtoons and whether a next page exists
+async fn scrap_search_page(
+    encoded_query: &str,
+    wt_type: WtType,
+    page: usize,
+    language: Language,
+) -> Result<(Vec<WebtoonSearchInfo>, bool), String> {
+    let type_path = match wt_type {
+        WtType::Original => "originals",
+        WtType::Canvas => "canvas",
+    };
+    let resp = reqwest::get(format!(
+        "https://www.webtoons.com/{}/search/{type_path}?keyword={encoded_query}&page={page}",
+        language.url_code()
+    ))
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let raw_html = resp.text().await.map_err(|e| e.to_string())?;
+    let document = Html::parse_document(&raw_html);
+
+    let webtoons_selectors = Selector::parse(".webtoon_list > li > a").unwrap();
+    let thumb_selector = Selector::parse(".image_wrap > img").unwrap();
+    let title_selector = Selector::parse(".info_text > .title").unwrap();
+    let author_selector = Selector::parse(".info_text > .author").unwrap();
+    let genre_selector = Selector::parse(".genre").unwrap();
+
+    let mut search_results = vec![];
+    for wt_elem in document.select(&webtoons_selectors) {
+        let wt_id = wt_elem
+            .attr("data-title-no")
+            .ok_or("No wt id")?
+            .trim()
+            .parse::<usize>()
+            .map_err(|e| e.to_string())?;
+        let wt_type = match wt_elem
+            .attr("data-webtoon-type")
+            .ok_or("No wt type")?
+            .to_lowercase()
+            .trim()
+        {
+            "webtoon" => WtType::Original,
+            "challenge" => WtType::Canvas,
+            _ => return Err("Failed to parse wt type".to_string()),
+        };
+
+        let title = wt_elem
+            .select(&title_selector)
+            .next()
+            .ok_or("No title".to_string())?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let thumbnail = wt_elem
+            .select(&thumb_selector)
+            .next()
+            .ok_or("No thumbnail".to_string())?
+            .attr("src")
+            .ok_or("No src".to_string())?
+            .to_string();
+        let creator = wt_elem
+            .select(&author_selector)
+            .next()
+            .ok_or("No author".to_string())?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let genre = wt_elem
+            .select(&genre_selector)
+            .next()
+            .map(|g| Genre::from(g.text().collect::<String>().trim().to_string()));
+
+        search_results.push(WebtoonSearchInfo {
+            id: WebtoonId::new(wt_id, wt_type).with_language(language),
+            title,
+            thumbnail,
+            creator: Some(creator),
+            genre,
+        });
+    }
+
+    let has_more = !search_results.is_empty() && has_next_page(&document, page);
+    Ok((search_results, has_more))
+}
+
+/// the paginate bar only lists the surrounding pages, so any link after the current one means there is more
+pub(crate) fn has_next_page(document: &Html, page: usize) -> bool {
+    let pages_selector = Selector::parse(".paginate a").unwrap();
+    document.select(&pages_selector).any(|p| {
+        p.text()
+            .collect::<String>()
+            .trim()
+            .parse::<usize>()
+            .map(|p_num| p_num > page)
+            .unwrap_or(p.value().classes().any(|c| c == "pg_next"))
+    })
+}
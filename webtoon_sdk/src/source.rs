@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    comments::{scrap_posts, scrap_replies, scrap_top_posts, Post, PostBatch},
+    episodes::{check_for_new_eps, scrap_episodes_info, EpisodeData, EpisodePreview},
+    local::LocalSource,
+    search::{SearchFilter, SearchResult, WebtoonSearchInfo},
+    webtoon::{scrap_webtoon_info, WebtoonInfo},
+    DownloadState, Language, WebtoonId,
+};
+
+/// Identifies the provider a series comes from
+#[derive(
+    Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum SourceId {
+    /// `webtoons.com`
+    #[default]
+    Webtoons,
+    /// the user's library folder, see `local::set_library_root`
+    Local,
+}
+
+/// Everything the app needs from a series provider
+///
+/// sources are dispatched statically through [`Source`], so the futures don't need a `Send` bound
+#[allow(async_fn_in_trait)]
+pub trait ContentSource {
+    fn id(&self) -> SourceId;
+
+    /// `page` starts at `1`, `language` picks the site edition for sources having several
+    async fn search(
+        &self,
+        query: &str,
+        filter: SearchFilter,
+        page: usize,
+        language: Language,
+    ) -> Result<SearchResult, String>;
+
+    /// **DOES NOT INCLUDE EPISODES**
+    async fn series_info<F: Fn(DownloadState) + Clone>(
+        &self,
+        id: WebtoonId,
+        info_cb: F,
+    ) -> Result<WebtoonInfo, String>;
+
+    /// episodes released after the episode number `after_ep` (every episode when `0`), oldest first
+    async fn episodes<F: Fn(DownloadState) + Clone>(
+        &self,
+        id: WebtoonId,
+        after_ep: usize,
+        info_cb: F,
+    ) -> Result<Vec<EpisodePreview>, String>;
+
+    /// panels are returned as given by the source, they still have to go through `EpisodeData::dl_panels`
+    async fn episode_data<F: Fn(DownloadState) + Clone>(
+        &self,
+        episode: &EpisodePreview,
+        info_cb: F,
+    ) -> Result<EpisodeData, String>;
+
+    /// one page of the top level comments of the episode, newest first
+    async fn comments(
+        &self,
+        id: WebtoonId,
+        ep_num: usize,
+        cursor: Option<&str>,
+    ) -> Result<PostBatch, String>;
+
+    /// comments the source puts at the top of the comments section
+    async fn top_comments(&self, id: WebtoonId, ep_num: usize) -> Result<Vec<Post>, String>;
+
+    /// one page of the replies of a comment, oldest first
+    async fn replies(
+        &self,
+        id: WebtoonId,
+        ep_num: usize,
+        post_id: &str,
+        cursor: Option<&str>,
+    ) -> Result<PostBatch, String>;
+}
+
+/// The available sources
+pub enum Source {
+    Webtoons(WebtoonsSource),
+    Local(LocalSource),
+}
+
+impl SourceId {
+    pub fn source(&self) -> Source {
+        match self {
+            SourceId::Webtoons => Source::Webtoons(WebtoonsSource),
+            SourceId::Local => Source::Local(LocalSource),
+        }
+    }
+}
+
+impl ContentSource for Source {
+    fn id(&self) -> SourceId {
+        match self {
+            Source::Webtoons(s) => s.id(),
+            Source::Local(s) => s.id(),
+        }
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        filter: SearchFilter,
+        page: usize,
+        language: Language,
+    ) -> Result<SearchResult, String> {
+        match self {
+            Source::Webtoons(s) => s.search(query, filter, page, language).await,
+            Source::Local(s) => s.search(query, filter, page, language).await,
+        }
+    }
+
+    async fn series_info<F: Fn(DownloadState) + Clone>(
+        &self,
+        id: WebtoonId,
+        info_cb: F,
+    ) -> Result<WebtoonInfo, String> {
+        match self {
+            Source::Webtoons(s) => s.series_info(id, info_cb).await,
+            Source::Local(s) => s.series_info(id, info_cb).await,
+        }
+    }
+
+    async fn episodes<F: Fn(DownloadState) + Clone>(
+        &self,
+        id: WebtoonId,
+        after_ep: usize,
+        info_cb: F,
+    ) -> Result<Vec<EpisodePreview>, String> {
+        match self {
+            Source::Webtoons(s) => s.episodes(id, after_ep, info_cb).await,
+            Source::Local(s) => s.episodes(id, after_ep, info_cb).await,
+        }
+    }
+
+    async fn episode_data<F: Fn(DownloadState) + Clone>(
+        &self,
+        episode: &EpisodePreview,
+        info_cb: F,
+    ) -> Result<EpisodeData, String> {
+        match self {
+            Source::Webtoons(s) => s.episode_data(episode, info_cb).await,
+            Source::Local(s) => s.episode_data(episode, info_cb).await,
+        }
+    }
+
+    async fn comments(
+        &self,
+        id: WebtoonId,
+        ep_num: usize,
+        cursor: Option<&str>,
+    ) -> Result<PostBatch, String> {
+        match self {
+            Source::Webtoons(s) => s.comments(id, ep_num, cursor).await,
+            Source::Local(s) => s.comments(id, ep_num, cursor).await,
+        }
+    }
+
+    async fn top_comments(&self, id: WebtoonId, ep_num: usize) -> Result<Vec<Post>, String> {
+        match self {
+            Source::Webtoons(s) => s.top_comments(id, ep_num).await,
+            Source::Local(s) => s.top_comments(id, ep_num).await,
+        }
+    }
+
+    async fn replies(
+        &self,
+        id: WebtoonId,
+        ep_num: usize,
+        post_id: &str,
+        cursor: Option<&str>,
+    ) -> Result<PostBatch, String> {
+        match self {
+            Source::Webtoons(s) => s.replies(id, ep_num, post_id, cursor).await,
+            Source::Local(s) => s.replies(id, ep_num, post_id, cursor).await,
+        }
+    }
+}
+
+/* Implementations */
+
+/// `webtoons.com`, backed by the scrapers of this crate
+pub struct WebtoonsSource;
+
+impl ContentSource for WebtoonsSource {
+    fn id(&self) -> SourceId {
+        SourceId::Webtoons
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        filter: SearchFilter,
+        page: usize,
+        language: Language,
+    ) -> Result<SearchResult, String> {
+        WebtoonSearchInfo::from_query(query, filter, page, language).await
+    }
+
+    async fn series_info<F: Fn(DownloadState) + Clone>(
+        &self,
+        id: WebtoonId,
+        info_cb: F,
+    ) -> Result<WebtoonInfo, String> {
+        scrap_webtoon_info(id, info_cb).await
+    }
+
+    async fn episodes<F: Fn(DownloadState) + Clone>(
+        &self,
+        id: WebtoonId,
+        after_ep: usize,
+        info_cb: F,
+    ) -> Result<Vec<EpisodePreview>, String> {
+        match after_ep {
+            0 => scrap_episodes_info(id, info_cb).await,
+            _ => check_for_new_eps(id, after_ep, info_cb).await,
+        }
+    }
+
+    async fn episode_data<F: Fn(DownloadState) + Clone>(
+        &self,
+        episode: &EpisodePreview,
+        info_cb: F,
+    ) -> Result<EpisodeData, String> {
+        episode.scrap_episode_data(info_cb).await
+    }
+
+    async fn comments(
+        &self,
+        id: WebtoonId,
+        ep_num: usize,
+        cursor: Option<&str>,
+    ) -> Result<PostBatch, String> {
+        scrap_posts(id, ep_num, cursor).await
+    }
+
+    async fn top_comments(&self, id: WebtoonId, ep_num: usize) -> Result<Vec<Post>, String> {
+        scrap_top_posts(id, ep_num).await
+    }
+
+    async fn replies(
+        &self,
+        id: WebtoonId,
+        ep_num: usize,
+        post_id: &str,
+        cursor: Option<&str>,
+    ) -> Result<PostBatch, String> {
+        scrap_replies(id, ep_num, post_id, cursor).await
+    }
+}
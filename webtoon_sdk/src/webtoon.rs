@@ -8,9 +8,13 @@ use serde::{Deserialize, Serialize};
 use tokio::fs;
 
 use crate::{
-    episodes::{check_for_new_eps, scrap_episodes_info, EpisodePreview},
+    episodes::{
+        episodes_as_list, merge_new_episodes, reconcile_episodes, EpisodePreview, Episodes,
+        EpisodesChanges,
+    },
     generate_webtoon_url,
     image_dl::download_images,
+    source::ContentSource,
     DownloadState, Genre, Schedule, WebtoonId, WtType,
 };
 
@@ -29,166 +33,176 @@ pub struct WebtoonInfo {
     pub subs: String,
     pub summary: String,
 
-    pub episodes: Option<Vec<EpisodePreview>>,
+    /// stored as a list, see `episodes_as_list`
+    #[serde(with = "episodes_as_list")]
+    pub episodes: Option<Episodes>,
     pub refresh_eps_at: SystemTime,
 
     pub expired_at: SystemTime,
 }
 
-impl WebtoonInfo {
-    /// gather all info for the requested webtoon
-    ///
-    /// **DOES NOT INCLUDE EPISODES** (for that you have to call the WebtoonInfo::fetch_episodes method)
-    pub async fn new_from_id<F: Fn(DownloadState) + Clone>(
-        id: WebtoonId,
-        info_cb: F,
-    ) -> Result<Self, String> {
-        info_cb(DownloadState::WebtoonData(10));
+/// scraps the `webtoons.com` page of the webtoon, see `WebtoonInfo::new_from_id`
+pub(crate) async fn scrap_webtoon_info<F: Fn(DownloadState) + Clone>(
+    id: WebtoonId,
+    info_cb: F,
+) -> Result<WebtoonInfo, String> {
+    info_cb(DownloadState::WebtoonData(10));
 
-        let title_selector = Selector::parse(".detail_header .subj").unwrap();
-        let thumb_selector = Selector::parse(".detail_header > .thmb > img").unwrap();
-        let banner_selector = Selector::parse("#content > .detail_bg").unwrap();
-        let creators_selector = Selector::parse(".detail_header .author_area").unwrap();
-        let creator_id_selector = Selector::parse(".detail_header .author_area > a").unwrap();
-        let genre_selector = Selector::parse(".detail_header .genre").unwrap();
-        let schedule_selector = Selector::parse(".detail_body .day_info").unwrap();
-        let grade_selector = Selector::parse(".detail_body .grade_area .cnt").unwrap();
-        let summary_selector = Selector::parse(".detail_body .summary").unwrap();
+    let title_selector = Selector::parse(".detail_header .subj").unwrap();
+    let thumb_selector = Selector::parse(".detail_header > .thmb > img").unwrap();
+    let banner_selector = Selector::parse("#content > .detail_bg").unwrap();
+    let creators_selector = Selector::parse(".detail_header .author_area").unwrap();
+    let creator_id_selector = Selector::parse(".detail_header .author_area > a").unwrap();
+    let genre_selector = Selector::parse(".detail_header .genre").unwrap();
+    let schedule_selector = Selector::parse(".detail_body .day_info").unwrap();
+    let grade_selector = Selector::parse(".detail_body .grade_area .cnt").unwrap();
+    let summary_selector = Selector::parse(".detail_body .summary").unwrap();
 
-        let url = generate_webtoon_url(id);
-        let resp = reqwest::get(&url).await.map_err(|e| e.to_string())?;
+    let url = generate_webtoon_url(id);
+    let resp = reqwest::get(&url).await.map_err(|e| e.to_string())?;
 
-        info_cb(DownloadState::WebtoonData(50));
+    info_cb(DownloadState::WebtoonData(50));
 
-        let raw_html = resp.text().await.map_err(|e| e.to_string())?;
-        let document = Html::parse_document(&raw_html);
+    let raw_html = resp.text().await.map_err(|e| e.to_string())?;
+    let document = Html::parse_document(&raw_html);
 
-        info_cb(DownloadState::WebtoonData(80));
+    info_cb(DownloadState::WebtoonData(80));
 
-        let title = document
-            .select(&title_selector)
-            .next()
-            .ok_or("No title")?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string();
-        let thumbnail = document
-            .select(&thumb_selector)
-            .next()
-            .ok_or("No thumb")?
-            .attr("src")
-            .ok_or("No src")?
-            .to_string();
-        let banner = match id.wt_type {
-            crate::WtType::Canvas => None,
-            crate::WtType::Original => Some(
-                document
-                    .select(&banner_selector)
-                    .next()
-                    .ok_or("No banner")?
-                    .attr("style")
-                    .ok_or("No style")?
-                    .trim_start_matches("background:url('")
-                    .trim_end_matches("') repeat-x")
-                    .to_string(),
-            ),
-        };
-        let creators = {
-            let thumb_elem = document
-                .select(&creators_selector)
+    let title = document
+        .select(&title_selector)
+        .next()
+        .ok_or("No title")?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    let thumbnail = document
+        .select(&thumb_selector)
+        .next()
+        .ok_or("No thumb")?
+        .attr("src")
+        .ok_or("No src")?
+        .to_string();
+    let banner = match id.wt_type {
+        crate::WtType::Canvas => None,
+        crate::WtType::Original => Some(
+            document
+                .select(&banner_selector)
                 .next()
-                .ok_or("No creators")?;
-
-            if let Some(a) = thumb_elem.select(&Selector::parse("a").unwrap()).next() {
-                vec![a.text().collect::<String>().trim().to_string()]
-            } else {
-                thumb_elem
-                    .text()
-                    .collect::<String>()
-                    .trim()
-                    .split(", ")
-                    .map(|a| a.trim().replace("author info", "").trim().to_string())
-                    .collect::<Vec<String>>()
-            }
-        };
-        let creator_id = match creators.len() == 1 {
-            true => Some(
-                document
-                    .select(&creator_id_selector)
-                    .next()
-                    .ok_or("No creator id")?
-                    .attr("href")
-                    .ok_or("No href aid")?
-                    .split("/")
-                    .last()
-                    .map(|aid| aid.to_string())
-                    .ok_or("Author Id not found".to_string())?,
-            ),
-            false => None,
-        };
-        let schedule = match id.wt_type {
-            crate::WtType::Canvas => None,
-            crate::WtType::Original => {
-                let raw_schedule = document
-                    .select(&schedule_selector)
-                    .next()
-                    .ok_or("No schedule")?
-                    .text()
-                    .collect::<String>()
-                    .trim()
-                    .trim_start_matches("UP")
-                    .to_string();
-
-                Some(raw_schedule.try_into()?)
-            }
-        };
-        let genres = document
-            .select(&genre_selector)
-            .map(|g| g.text().collect::<String>().into())
-            .collect::<Vec<Genre>>();
-
-        let (views, subs) = match document
-            .select(&grade_selector)
-            .map(|gr| gr.text().collect::<String>())
-            .collect::<Vec<_>>()
-            .as_slice()
-        {
-            [views, subs, ..] => (views.to_owned(), subs.to_owned()),
-            _ => return Err("wrong format for webtoon views and subs".to_string()),
-        };
-        let summary = document
-            .select(&summary_selector)
+                .ok_or("No banner")?
+                .attr("style")
+                .ok_or("No style")?
+                .trim_start_matches("background:url('")
+                .trim_end_matches("') repeat-x")
+                .to_string(),
+        ),
+    };
+    let creators = {
+        let thumb_elem = document
+            .select(&creators_selector)
             .next()
-            .ok_or("No summary")?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string();
-
-        info_cb(DownloadState::WebtoonData(100));
-
-        Ok(Self {
-            id,
-            title,
-            thumbnail,
-            banner,
-            creators,
-            creator_id,
-            genres,
-            schedule,
-            views,
-            subs,
-            summary,
-            episodes: None,
-            refresh_eps_at: SystemTime::now()
-                .checked_add(Duration::from_secs(86400)) // add 1 days before refresh
-                .ok_or("are we near 2038?")?,
-
-            expired_at: SystemTime::now()
-                .checked_add(Duration::from_secs(864000)) // add 10 days before refresh
-                .ok_or("are we near 2038?")?,
-        })
+            .ok_or("No creators")?;
+
+        if let Some(a) = thumb_elem.select(&Selector::parse("a").unwrap()).next() {
+            vec![a.text().collect::<String>().trim().to_string()]
+        } else {
+            thumb_elem
+                .text()
+                .collect::<String>()
+                .trim()
+                .split(", ")
+                .map(|a| a.trim().replace("author info", "").trim().to_string())
+                .collect::<Vec<String>>()
+        }
+    };
+    let creator_id = match creators.len() == 1 {
+        true => Some(
+            document
+                .select(&creator_id_selector)
+                .next()
+                .ok_or("No creator id")?
+                .attr("href")
+                .ok_or("No href aid")?
+                .split("/")
+                .last()
+                .map(|aid| aid.to_string())
+                .ok_or("Author Id not found".to_string())?,
+        ),
+        false => None,
+    };
+    let schedule = match id.wt_type {
+        crate::WtType::Canvas => None,
+        crate::WtType::Original => {
+            let raw_schedule = document
+                .select(&schedule_selector)
+                .next()
+                .ok_or("No schedule")?
+                .text()
+                .collect::<String>()
+                .trim()
+                .trim_start_matches("UP")
+                .to_string();
+
+            Some(raw_schedule.try_into()?)
+        }
+    };
+    let genres = document
+        .select(&genre_selector)
+        .map(|g| g.text().collect::<String>().into())
+        .collect::<Vec<Genre>>();
+
+    let (views, subs) = match document
+        .select(&grade_selector)
+        .map(|gr| gr.text().collect::<String>())
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [views, subs, ..] => (views.to_owned(), subs.to_owned()),
+        _ => return Err("wrong format for webtoon views and subs".to_string()),
+    };
+    let summary = document
+        .select(&summary_selector)
+        .next()
+        .ok_or("No summary")?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+
+    info_cb(DownloadState::WebtoonData(100));
+
+    Ok(WebtoonInfo {
+        id,
+        title,
+        thumbnail,
+        banner,
+        creators,
+        creator_id,
+        genres,
+        schedule,
+        views,
+        subs,
+        summary,
+        episodes: None,
+        refresh_eps_at: SystemTime::now()
+            .checked_add(Duration::from_secs(86400)) // add 1 days before refresh
+            .ok_or("are we near 2038?")?,
+
+        expired_at: SystemTime::now()
+            .checked_add(Duration::from_secs(864000)) // add 10 days before refresh
+            .ok_or("are we near 2038?")?,
+    })
+}
+
+impl WebtoonInfo {
+    /// gather all info for the requested webtoon from its source
+    ///
+    /// **DOES NOT INCLUDE EPISODES** (for that you have to call the WebtoonInfo::fetch_episodes method)
+    pub async fn new_from_id<F: Fn(DownloadState) + Clone>(
+        id: WebtoonId,
+        info_cb: F,
+    ) -> Result<Self, String> {
+        id.source.source().series_info(id, info_cb).await
     }
 
     pub async fn dl_wt_thumbnail<F: Fn(DownloadState) + Clone>(
@@ -225,13 +239,34 @@ impl WebtoonInfo {
         Ok(())
     }
 
+    /// episode by its number, not its position
+    pub fn episode(&self, number: usize) -> Option<&EpisodePreview> {
+        self.episodes.as_ref().and_then(|eps| eps.get(&number))
+    }
+
+    /// number of the episode following `number`, numbers may have gaps
+    pub fn next_episode(&self, number: usize) -> Option<usize> {
+        self.episodes
+            .as_ref()
+            .and_then(|eps| eps.range(number + 1..).next().map(|(n, _)| *n))
+    }
+
+    /// fetches the whole episode list and reconciles it with the stored one,
+    /// so removed, renumbered and renamed episodes are detected
+    ///
     /// **DOES NOT INCLUDE COMMENTS**
     pub async fn fetch_episodes<F: Fn(DownloadState) + Clone>(
         &mut self,
         thumbnail_path: &Path,
         info_cb: F,
-    ) -> Result<(), String> {
-        self.episodes = Some(scrap_episodes_info(self.id, info_cb.clone()).await?);
+    ) -> Result<EpisodesChanges, String> {
+        let fetched = self
+            .id
+            .source
+            .source()
+            .episodes(self.id, 0, info_cb.clone())
+            .await?;
+        let changes = reconcile_episodes(self.episodes.get_or_insert_default(), fetched);
         self.download_episodes_thumbnail(thumbnail_path, info_cb)
             .await?;
 
@@ -240,31 +275,44 @@ impl WebtoonInfo {
             .checked_add(Duration::from_secs(86400)) // add 1 days before refresh
             .ok_or("are we near 2038?")?;
 
-        Ok(())
+        Ok(changes)
     }
 
+    /// only fetches the episodes released after the last stored one, unless `refresh_eps_at` expired:
+    /// then the whole list is fetched and reconciled like `fetch_episodes` does
+    ///
     /// **DOES NOT INCLUDE COMMENTS**
     pub async fn update_episodes<F: Fn(DownloadState) + Clone>(
         &mut self,
         thumbnail_path: &Path,
         info_cb: F,
-    ) -> Result<(), String> {
-        if let Some(episodes) = self.episodes.as_mut() {
-            let mut new_ep_since_last =
-                check_for_new_eps(self.id, episodes.len(), info_cb.clone()).await?;
-            episodes.append(&mut new_ep_since_last);
-            self.download_episodes_thumbnail(thumbnail_path, info_cb)
-                .await?;
-        } else {
-            self.fetch_episodes(thumbnail_path, info_cb).await?;
-        }
+    ) -> Result<EpisodesChanges, String> {
+        let last_stored_ep = match self
+            .episodes
+            .as_ref()
+            .and_then(|eps| eps.keys().next_back())
+        {
+            Some(last_stored_ep) if self.refresh_eps_at > SystemTime::now() => *last_stored_ep,
+            // removed and renumbered episodes are only detected on the whole list
+            _ => return self.fetch_episodes(thumbnail_path, info_cb).await,
+        };
+
+        let new_ep_since_last = self
+            .id
+            .source
+            .source()
+            .episodes(self.id, last_stored_ep, info_cb.clone())
+            .await?;
+        let changes = merge_new_episodes(self.episodes.get_or_insert_default(), new_ep_since_last);
+        self.download_episodes_thumbnail(thumbnail_path, info_cb)
+            .await?;
 
         // reset expire date
         self.refresh_eps_at = SystemTime::now()
             .checked_add(Duration::from_secs(86400)) // add 1 days before refresh
             .ok_or("are we near 2038?")?;
 
-        Ok(())
+        Ok(changes)
     }
 
     /// locally downaload eps thumbnail and set the disk path as the new eps thumb url
@@ -276,12 +324,12 @@ impl WebtoonInfo {
         if let Some(eps) = self.episodes.as_mut() {
             let new_thumbnails_url = download_images(
                 thumbnail_path,
-                eps.iter().map(|e| e.thumbnail.clone()).collect(),
+                eps.values().map(|e| e.thumbnail.clone()).collect(),
                 self.id.wt_id.to_string(),
                 info_cb,
             )
             .await?;
-            for (e, new_thumb_url) in eps.iter_mut().zip(new_thumbnails_url) {
+            for (e, new_thumb_url) in eps.values_mut().zip(new_thumbnails_url) {
                 e.thumbnail = new_thumb_url
             }
         }
@@ -293,11 +341,60 @@ impl WebtoonInfo {
         thumbnail_path: &Path,
         info_cb: F,
     ) -> Result<(), String> {
-        let eps = self.episodes.clone();
-        *self = WebtoonInfo::new_from_id(self.id, info_cb.clone()).await?;
-        self.episodes = eps;
+        let fresh = WebtoonInfo::new_from_id(self.id, info_cb.clone()).await?;
+        self.replace_info(fresh);
 
         self.dl_wt_thumbnail(thumbnail_path, info_cb).await?;
         Ok(())
     }
+
+    /// takes the info of `fresh`, the stored episodes and when they are due for a full refresh are kept
+    fn replace_info(&mut self, fresh: WebtoonInfo) {
+        *self = WebtoonInfo {
+            episodes: self.episodes.take(),
+            refresh_eps_at: self.refresh_eps_at,
+            ..fresh
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(title: &str, refresh_eps_at: SystemTime) -> WebtoonInfo {
+        WebtoonInfo {
+            id: WebtoonId::new(95, WtType::Original),
+            title: title.to_string(),
+            thumbnail: String::new(),
+            banner: None,
+            creators: vec![],
+            creator_id: None,
+            genres: vec![],
+            schedule: None,
+            views: String::new(),
+            subs: String::new(),
+            summary: String::new(),
+            episodes: None,
+            refresh_eps_at,
+            expired_at: refresh_eps_at,
+        }
+    }
+
+    #[test]
+    fn replace_info_keeps_the_episodes_refresh_date() {
+        let expired = SystemTime::UNIX_EPOCH;
+        let mut stored = WebtoonInfo {
+            episodes: Some(Episodes::new()),
+            ..info("old title", expired)
+        };
+
+        let fresh = info("new title", SystemTime::now() + Duration::from_secs(86400));
+        stored.replace_info(fresh);
+
+        assert_eq!(stored.title, "new title");
+        assert!(stored.episodes.is_some());
+        // still expired, so that `update_episodes` reconciles the whole list
+        assert_eq!(stored.refresh_eps_at, expired);
+    }
 }